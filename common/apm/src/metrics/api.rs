@@ -10,6 +10,7 @@ use lazy_static::lazy_static;
 make_auto_flush_static_metric! {
     pub label_enum RequestKind {
         eth_sendRawTransaction,
+        eth_sendRawTransactionSync,
         eth_getTransactionByHash,
         eth_getBlockByNumber,
         eth_blockNumber,
@@ -19,13 +20,17 @@ make_auto_flush_static_metric! {
         eth_getBalance,
         eth_call,
         eth_estimateGas,
+        eth_createAccessList,
+        eth_simulateV1,
         eth_chainId,
         net_version,
         eth_getCode,
         eth_getTransactionReceipt,
+        eth_getBlockReceipts,
         net_peerCount,
         net_listening,
         eth_gasPrice,
+        eth_maxPriorityFeePerGas,
         eth_syncing,
         eth_getLogs,
         get_block,
@@ -59,6 +64,24 @@ make_auto_flush_static_metric! {
     pub struct RequestTimeHistogramVec: LocalHistogram {
         "type" => RequestKind,
     }
+
+    pub label_enum RateLimitKind {
+        ip,
+        api_key,
+    }
+
+    pub label_enum Transport {
+        http,
+        ws,
+    }
+
+    pub struct ThrottledRequestCounterVec: LocalCounter {
+        "limit" => RateLimitKind,
+    }
+
+    pub struct RequestCostCounterVec: LocalCounter {
+        "transport" => Transport,
+    }
 }
 
 lazy_static! {
@@ -75,6 +98,18 @@ lazy_static! {
         exponential_buckets(0.001, 2.0, 20).expect("api req time expontial")
     )
     .expect("request time cost");
+    pub static ref API_THROTTLED_REQUEST_COUNTER_VEC: CounterVec = register_counter_vec!(
+        "axon_api_throttled_request_total",
+        "Total number of request rejected by the rate limiter",
+        &["limit"]
+    )
+    .expect("throttled request total");
+    pub static ref API_REQUEST_COST_COUNTER_VEC: CounterVec = register_counter_vec!(
+        "axon_api_request_cost_total",
+        "Total cost of the request accepted by the rate limiter",
+        &["transport"]
+    )
+    .expect("request cost total");
 }
 
 lazy_static! {
@@ -82,4 +117,10 @@ lazy_static! {
         auto_flush_from!(API_REQUEST_RESULT_COUNTER_VEC, RequestResultCounterVec);
     pub static ref API_REQUEST_TIME_HISTOGRAM_STATIC: RequestTimeHistogramVec =
         auto_flush_from!(API_REQUEST_TIME_HISTOGRAM_VEC, RequestTimeHistogramVec);
+    pub static ref API_THROTTLED_REQUEST_COUNTER_VEC_STATIC: ThrottledRequestCounterVec = auto_flush_from!(
+        API_THROTTLED_REQUEST_COUNTER_VEC,
+        ThrottledRequestCounterVec
+    );
+    pub static ref API_REQUEST_COST_COUNTER_VEC_STATIC: RequestCostCounterVec =
+        auto_flush_from!(API_REQUEST_COST_COUNTER_VEC, RequestCostCounterVec);
 }
@@ -177,11 +177,62 @@ fn load_privkey_from_file(privkey_path: &Path) -> Result<Key256Bits, clap::Error
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ConfigApi {
-    pub http_listening_address: Option<SocketAddr>,
-    pub ws_listening_address:   Option<SocketAddr>,
-    pub maxconn:                u32,
-    pub max_payload_size:       u32,
-    pub enable_dump_profile:    Option<bool>,
+    pub http_listening_address:    Option<SocketAddr>,
+    pub ws_listening_address:      Option<SocketAddr>,
+    pub graphql_listening_address: Option<SocketAddr>,
+    pub maxconn:                   u32,
+    pub max_payload_size:          u32,
+    pub enable_dump_profile:       Option<bool>,
+    /// The `admin_*` methods are only served on this address, which should
+    /// never be exposed publicly.
+    pub admin_listening_address:   Option<SocketAddr>,
+    /// The bearer token required by the admin server.
+    pub admin_auth_token:          Option<String>,
+    /// The file of the hex encoded HS256 secret verifying the JWT bearer
+    /// tokens, the same as the engine API of geth.
+    pub jwt_secret_path:           Option<PathBuf>,
+    /// The static keys accepted as bearer tokens or `x-api-key` headers. The
+    /// server is open if neither keys nor a JWT secret is configured.
+    pub api_keys:                  Option<Vec<String>>,
+    /// The namespaces such as `eth` or the full method names served, all the
+    /// methods are served by default.
+    pub method_allowlist:          Option<Vec<String>>,
+    /// The namespaces or the full method names never served.
+    pub method_denylist:           Option<Vec<String>>,
+    /// The origins allowed by CORS, any origin is allowed by default.
+    pub cors_origins:              Option<Vec<String>>,
+    /// The limits on the requests of every client, nothing is limited by
+    /// default.
+    pub rate_limit:                Option<ConfigRateLimit>,
+    /// The Unix domain socket serving the same methods as the WebSocket
+    /// server, such as `axon.ipc`. A relative path is in the data path.
+    pub ipc_path:                  Option<PathBuf>,
+    /// The mode of the socket file written as an octal integer such as
+    /// `0o660`, only the owner can connect by default.
+    pub ipc_permissions:           Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ConfigRateLimit {
+    /// The cost budget of every client IP.
+    pub ip: Option<ConfigTokenBucket>,
+    /// The cost budget of every API key or JWT, which is charged instead of
+    /// the IP budget for the requests authorized by one.
+    pub api_key: Option<ConfigTokenBucket>,
+    /// The cost of the methods or the namespaces such as `debug`, which
+    /// overrides the built-in weights. The other methods cost 1.
+    #[serde(default)]
+    pub method_weights: HashMap<String, u64>,
+    pub max_batch_size: Option<u32>,
+    pub max_subscriptions_per_connection: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ConfigTokenBucket {
+    /// The maximum cost of a burst, a request costing more is always rejected.
+    pub capacity:          u64,
+    /// The cost refilled every second.
+    pub refill_per_second: u64,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -244,6 +295,30 @@ pub struct ConfigMempool {
 #[derive(Clone, Debug, Deserialize)]
 pub struct ConfigExecutor {
     pub triedb_cache_size: usize,
+    #[serde(default)]
+    pub state_pruning:     StatePruning,
+}
+
+/// The pruning mode of the state tries, for example
+/// `state_pruning = "archive"` or `state_pruning = { keep_latest = 1024 }`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatePruning {
+    /// Keep the states of all blocks.
+    #[default]
+    Archive,
+    /// Only keep the states of the latest N blocks, the unreachable trie
+    /// nodes of older states are garbage collected.
+    KeepLatest(u64),
+}
+
+impl StatePruning {
+    pub fn keep_latest(&self) -> Option<u64> {
+        match self {
+            StatePruning::Archive => None,
+            StatePruning::KeepLatest(n) => Some(*n),
+        }
+    }
 }
 
 fn default_cache_size() -> usize {
@@ -256,6 +331,9 @@ pub struct ConfigRocksDB {
     #[serde(default = "default_cache_size")]
     pub cache_size:     usize,
     pub options_file:   Option<PathBuf>,
+    /// Index the logs by address and topic to speed up `eth_getLogs`.
+    #[serde(default)]
+    pub log_index:      bool,
 }
 
 impl Default for ConfigRocksDB {
@@ -264,6 +342,7 @@ impl Default for ConfigRocksDB {
             max_open_files: 64,
             cache_size:     default_cache_size(),
             options_file:   None,
+            log_index:      false,
         }
     }
 }
@@ -270,6 +270,23 @@ pub enum HardforkName {
     /// If this hardfork is activated, chain validators can modify the EVM
     /// contract size limit.
     Andromeda = 0b1,
+    /// If this hardfork is activated, the EVM is upgraded to the Cancun
+    /// feature set, which enables the `PUSH0`, `TLOAD`, `TSTORE` and `MCOPY`
+    /// opcodes, limits the size of the init code and restricts `SELFDESTRUCT`
+    /// as EIP-6780 specifies.
+    Antlia = 0b10,
+    /// If this hardfork is activated, the base fee per gas of a block is
+    /// calculated from the gas used of its parent block as EIP-1559 specifies,
+    /// and the gas price RPCs are served by a gas price oracle.
+    Apus = 0b100,
+    /// If this hardfork is activated, the secp256r1 signature verification
+    /// precompile specified by RIP-7212 is enabled.
+    Aquarius = 0b1000,
+    /// If this hardfork is activated, the RSA PKCS#1 v1.5 signature
+    /// verification precompile is enabled.
+    Aquila = 0b10000,
+    /// If this hardfork is activated, the get cell precompile is enabled.
+    Ara = 0b100000,
 }
 
 impl HardforkName {
@@ -1,30 +1,45 @@
-use std::sync::Arc;
+use std::sync::{atomic::AtomicBool, Arc};
+use std::time::Duration;
 
+use parking_lot::RwLock;
+
+use protocol::tokio::{sync::broadcast, task};
 use protocol::traits::{
-    APIAdapter, Context, Executor, ExecutorReadOnlyAdapter, MemPool, Network, ReadOnlyStorage,
+    APIAdapter, ConnectedPeer, Context, Executor, ExecutorReadOnlyAdapter, MemPool, Network,
+    NetworkNodeInfo, ReadOnlyStorage,
 };
 use protocol::trie::Trie as _;
 use protocol::types::{
-    Account, BigEndianHash, Block, BlockNumber, Bytes, CkbRelatedInfo, EthAccountProof,
-    EthStorageProof, ExecutorContext, HardforkInfo, HardforkInfoInner, Hash, Header, Hex, Metadata,
-    Proposal, Receipt, SignedTransaction, TxResp, H160, H256, NIL_DATA, RLP_NULL, U256, U64,
+    AccessList, Account, BigEndianHash, Block, BlockNumber, Bytes, CkbRelatedInfo, EthAccountProof,
+    EthStorageProof, ExecutorContext, HardforkInfo, HardforkInfoInner, Hash, Header, Hex,
+    MemPoolContent, Metadata, PendingBlock, Proof, Proposal, Receipt, SignedTransaction,
+    SimulateBlock, StateOverride, TraceResult, Tracer, TxResp, H160, H256, NIL_DATA, RLP_NULL,
+    U256, U64,
 };
 use protocol::{
     async_trait, codec::ProtocolCodec, constants::MAX_BLOCK_GAS_LIMIT, trie, ProtocolResult,
 };
 
+use common_merkle::TrieMerkle;
+use core_consensus::util::{digest_signed_transactions, time_now};
 use core_executor::{
-    system_contract::metadata::MetadataHandle, AxonExecutor, AxonExecutorReadOnlyAdapter, MPTTrie,
+    next_block_base_fee_per_gas, system_contract::metadata::MetadataHandle, AxonExecutor,
+    AxonExecutorOverlayAdapter, AxonExecutorReadOnlyAdapter, MPTTrie,
 };
 
 use crate::APIError;
 
 #[derive(Clone)]
 pub struct DefaultAPIAdapter<M, S, DB, Net> {
-    mempool: Arc<M>,
-    storage: Arc<S>,
-    trie_db: Arc<DB>,
-    net:     Arc<Net>,
+    mempool:         Arc<M>,
+    storage:         Arc<S>,
+    trie_db:         Arc<DB>,
+    net:             Arc<Net>,
+    pending:         Arc<RwLock<Option<Arc<PendingBlock>>>>,
+    // Broadcasts the blocks committed by consensus.
+    commit_notifier: broadcast::Sender<Block>,
+    // Broadcasts the proofs saved by consensus.
+    proof_notifier:  broadcast::Sender<Proof>,
 }
 
 impl<M, S, DB, Net> DefaultAPIAdapter<M, S, DB, Net>
@@ -34,12 +49,22 @@ where
     DB: trie::DB + Send + Sync + 'static,
     Net: Network + 'static,
 {
-    pub fn new(mempool: Arc<M>, storage: Arc<S>, trie_db: Arc<DB>, net: Arc<Net>) -> Self {
+    pub fn new(
+        mempool: Arc<M>,
+        storage: Arc<S>,
+        trie_db: Arc<DB>,
+        net: Arc<Net>,
+        commit_notifier: broadcast::Sender<Block>,
+        proof_notifier: broadcast::Sender<Proof>,
+    ) -> Self {
         Self {
             mempool,
             storage,
             trie_db,
             net,
+            pending: Arc::new(RwLock::new(None)),
+            commit_notifier,
+            proof_notifier,
         }
     }
 
@@ -59,6 +84,65 @@ where
             Proposal::new_without_state_root(&block.header).into(),
         )
     }
+
+    async fn build_pending_block(
+        &self,
+        ctx: Context,
+        latest: Block,
+        txs: Vec<SignedTransaction>,
+        call_system_script_count: u32,
+    ) -> ProtocolResult<PendingBlock> {
+        let tx_hashes = txs.iter().map(|tx| tx.transaction.hash).collect::<Vec<_>>();
+        let transactions_root = if tx_hashes.is_empty() {
+            RLP_NULL
+        } else {
+            TrieMerkle::from_iter(tx_hashes.iter().enumerate())
+                .root_hash()
+                .map_err(|e| APIError::Adapter(e.to_string()))?
+        };
+
+        let proposal = Proposal {
+            version: latest.header.version,
+            prev_hash: latest.hash(),
+            proposer: latest.header.proposer,
+            prev_state_root: latest.header.state_root,
+            transactions_root,
+            signed_txs_hash: digest_signed_transactions(&txs),
+            timestamp: time_now().max(latest.header.timestamp + 1),
+            number: latest.header.number + 1,
+            gas_limit: MAX_BLOCK_GAS_LIMIT.into(),
+            extra_data: Vec::new(),
+            base_fee_per_gas: next_block_base_fee_per_gas(
+                &latest.header,
+                &self.hardfork_info(ctx.clone()).await?,
+            ),
+            proof: self.storage.get_latest_proof(ctx).await?,
+            chain_id: latest.header.chain_id,
+            call_system_script_count,
+            tx_hashes,
+        };
+
+        let state_root = latest.header.state_root;
+        let exec_ctx = ExecutorContext::from(proposal.clone());
+        let trie_db = Arc::clone(&self.trie_db);
+        let storage = Arc::clone(&self.storage);
+
+        let (resp, state_override, txs) = task::spawn_blocking(move || -> ProtocolResult<_> {
+            let backend =
+                AxonExecutorReadOnlyAdapter::from_root(state_root, trie_db, storage, exec_ctx)?;
+            let mut overlay = AxonExecutorOverlayAdapter::new(backend);
+            let resp = AxonExecutor.speculate(&mut overlay, &txs);
+            Ok((resp, overlay.state_override(), txs))
+        })
+        .await
+        .map_err(|e| APIError::Adapter(e.to_string()))??;
+
+        Ok(PendingBlock {
+            block: Block::new(proposal, resp),
+            txs,
+            state_override,
+        })
+    }
 }
 
 #[async_trait]
@@ -81,6 +165,30 @@ where
         self.mempool.contains(ctx, tx_hash).await
     }
 
+    async fn get_mempool_status(&self, ctx: Context) -> ProtocolResult<(usize, usize)> {
+        Ok(self.mempool.get_status(ctx))
+    }
+
+    async fn get_mempool_content(
+        &self,
+        ctx: Context,
+        sender: Option<H160>,
+    ) -> ProtocolResult<MemPoolContent> {
+        Ok(self.mempool.get_content(ctx, sender))
+    }
+
+    fn subscribe_new_txs(&self, ctx: Context) -> broadcast::Receiver<SignedTransaction> {
+        self.mempool.subscribe_new_txs(ctx)
+    }
+
+    fn subscribe_committed_blocks(&self, _ctx: Context) -> broadcast::Receiver<Block> {
+        self.commit_notifier.subscribe()
+    }
+
+    fn subscribe_proofs(&self, _ctx: Context) -> broadcast::Receiver<Proof> {
+        self.proof_notifier.subscribe()
+    }
+
     async fn get_block_by_number(
         &self,
         ctx: Context,
@@ -119,6 +227,10 @@ where
         self.storage.get_block_number_by_hash(ctx, &hash).await
     }
 
+    async fn get_latest_proof(&self, ctx: Context) -> ProtocolResult<Proof> {
+        self.storage.get_latest_proof(ctx).await
+    }
+
     async fn get_receipt_by_tx_hash(
         &self,
         ctx: Context,
@@ -138,6 +250,19 @@ where
             .await
     }
 
+    async fn get_log_block_numbers(
+        &self,
+        ctx: Context,
+        from: u64,
+        to: u64,
+        addresses: &[H160],
+        topics: &[Option<Vec<Option<H256>>>],
+    ) -> ProtocolResult<Option<Vec<u64>>> {
+        self.storage
+            .get_log_block_numbers(ctx, from, to, addresses, topics)
+            .await
+    }
+
     async fn get_transaction_by_hash(
         &self,
         ctx: Context,
@@ -189,6 +314,41 @@ where
             .map(|(n, b)| (U256::from(n), b))
     }
 
+    async fn get_pending_block(&self, ctx: Context) -> ProtocolResult<Arc<PendingBlock>> {
+        let latest = self.storage.get_latest_block(ctx.clone()).await?;
+        let metadata = self.get_metadata_by_number(ctx.clone(), None).await?;
+        let packed = self
+            .mempool
+            .peek_package(ctx.clone(), metadata.consensus_config.tx_num_limit);
+        // The transactions flushed in the meantime are left out.
+        let txs = packed
+            .hashes
+            .iter()
+            .filter_map(|hash| self.mempool.get_tx_from_mem(ctx.clone(), hash))
+            .collect::<Vec<_>>();
+
+        // The pending block is cached until the next block is committed or the
+        // transactions to package change.
+        if let Some(pending) = self.pending.read().as_ref() {
+            if pending.block.header.prev_hash == latest.hash()
+                && pending
+                    .txs
+                    .iter()
+                    .map(|tx| tx.transaction.hash)
+                    .eq(txs.iter().map(|tx| tx.transaction.hash))
+            {
+                return Ok(Arc::clone(pending));
+            }
+        }
+
+        let pending = Arc::new(
+            self.build_pending_block(ctx, latest, txs, packed.call_system_script_count)
+                .await?,
+        );
+        *self.pending.write() = Some(Arc::clone(&pending));
+        Ok(pending)
+    }
+
     async fn evm_call(
         &self,
         _ctx: Context,
@@ -199,14 +359,11 @@ where
         value: U256,
         data: Vec<u8>,
         estimate: bool,
+        state_override: Option<StateOverride>,
         state_root: Hash,
         mock_header: Proposal,
     ) -> ProtocolResult<TxResp> {
-        let mut exec_ctx = ExecutorContext::from(mock_header);
-        exec_ctx.origin = from.unwrap_or_default();
-        exec_ctx.gas_price = gas_price
-            .map(|p| U256::from(p.low_u64()))
-            .unwrap_or_else(U256::one);
+        let exec_ctx = call_exec_ctx(mock_header, from, gas_price);
 
         let backend = AxonExecutorReadOnlyAdapter::from_root(
             state_root,
@@ -218,7 +375,154 @@ where
             .map(|gas| gas.low_u64())
             .unwrap_or(MAX_BLOCK_GAS_LIMIT);
 
-        Ok(AxonExecutor.call(&backend, gas_limit, from, to, value, data, estimate))
+        match state_override {
+            Some(state_override) => {
+                let mut overlay = AxonExecutorOverlayAdapter::new(backend);
+                overlay.apply_state_override(&state_override);
+                Ok(AxonExecutor.call(&overlay, gas_limit, from, to, value, data, estimate))
+            }
+            None => Ok(AxonExecutor.call(&backend, gas_limit, from, to, value, data, estimate)),
+        }
+    }
+
+    async fn create_access_list(
+        &self,
+        _ctx: Context,
+        from: Option<H160>,
+        to: Option<H160>,
+        gas_price: Option<U64>,
+        gas_limit: Option<U64>,
+        value: U256,
+        data: Vec<u8>,
+        access_list: AccessList,
+        state_root: Hash,
+        mock_header: Proposal,
+    ) -> ProtocolResult<(AccessList, TxResp)> {
+        let exec_ctx = call_exec_ctx(mock_header, from, gas_price);
+
+        let trie_db = Arc::clone(&self.trie_db);
+        let storage = Arc::clone(&self.storage);
+        let gas_limit = gas_limit
+            .map(|gas| gas.low_u64())
+            .unwrap_or(MAX_BLOCK_GAS_LIMIT);
+
+        // The call is repeated until the access list is stable, so it runs on
+        // the blocking thread pool as tracing.
+        task::spawn_blocking(move || -> ProtocolResult<(AccessList, TxResp)> {
+            let backend =
+                AxonExecutorReadOnlyAdapter::from_root(state_root, trie_db, storage, exec_ctx)?;
+            Ok(AxonExecutor.create_access_list(
+                &backend,
+                gas_limit,
+                from,
+                to,
+                value,
+                data,
+                access_list,
+            ))
+        })
+        .await
+        .map_err(|e| APIError::Adapter(e.to_string()))?
+    }
+
+    async fn trace_call(
+        &self,
+        _ctx: Context,
+        from: Option<H160>,
+        to: Option<H160>,
+        gas_price: Option<U64>,
+        gas_limit: Option<U64>,
+        value: U256,
+        data: Vec<u8>,
+        state_root: Hash,
+        mock_header: Proposal,
+        tracer: Tracer,
+        cancel: Arc<AtomicBool>,
+    ) -> ProtocolResult<TraceResult> {
+        let exec_ctx = call_exec_ctx(mock_header, from, gas_price);
+
+        let trie_db = Arc::clone(&self.trie_db);
+        let storage = Arc::clone(&self.storage);
+        let gas_limit = gas_limit
+            .map(|gas| gas.low_u64())
+            .unwrap_or(MAX_BLOCK_GAS_LIMIT);
+
+        // Tracing is expensive, so it runs on the blocking thread pool to avoid
+        // stalling the RPC server.
+        task::spawn_blocking(move || -> ProtocolResult<TraceResult> {
+            let backend =
+                AxonExecutorReadOnlyAdapter::from_root(state_root, trie_db, storage, exec_ctx)?;
+            AxonExecutor
+                .trace_call(&backend, gas_limit, from, to, value, data, &tracer, &cancel)
+                .map_err(|_| APIError::Adapter("trace cancelled".to_string()).into())
+        })
+        .await
+        .map_err(|e| APIError::Adapter(e.to_string()))?
+    }
+
+    async fn trace_transactions(
+        &self,
+        _ctx: Context,
+        state_root: Hash,
+        proposal: Proposal,
+        txs: Vec<SignedTransaction>,
+        trace_from: usize,
+        tracer: Tracer,
+        cancel: Arc<AtomicBool>,
+    ) -> ProtocolResult<Vec<TraceResult>> {
+        let trie_db = Arc::clone(&self.trie_db);
+        let storage = Arc::clone(&self.storage);
+
+        task::spawn_blocking(move || -> ProtocolResult<Vec<TraceResult>> {
+            let backend = AxonExecutorReadOnlyAdapter::from_root(
+                state_root,
+                trie_db,
+                storage,
+                proposal.into(),
+            )?;
+            let mut overlay = AxonExecutorOverlayAdapter::new(backend);
+            AxonExecutor
+                .trace_exec(&mut overlay, &txs, trace_from, &tracer, &cancel)
+                .map_err(|_| APIError::Adapter("trace cancelled".to_string()).into())
+        })
+        .await
+        .map_err(|e| APIError::Adapter(e.to_string()))?
+    }
+
+    async fn simulate_blocks(
+        &self,
+        _ctx: Context,
+        state_root: Hash,
+        blocks: Vec<SimulateBlock>,
+        cancel: Arc<AtomicBool>,
+    ) -> ProtocolResult<Vec<Vec<TxResp>>> {
+        let trie_db = Arc::clone(&self.trie_db);
+        let storage = Arc::clone(&self.storage);
+
+        task::spawn_blocking(move || -> ProtocolResult<Vec<Vec<TxResp>>> {
+            let backend = AxonExecutorReadOnlyAdapter::from_root(
+                state_root,
+                trie_db,
+                storage,
+                ExecutorContext::default(),
+            )?;
+            let mut overlay = AxonExecutorOverlayAdapter::new(backend);
+
+            blocks
+                .into_iter()
+                .map(|block| {
+                    overlay.set_block_context(block.proposal.into());
+                    if let Some(state_override) = block.state_override.as_ref() {
+                        overlay.apply_state_override(state_override);
+                    }
+                    AxonExecutor
+                        .simulate(&mut overlay, &block.calls, &cancel)
+                        .map_err(|_| APIError::Adapter("simulation cancelled".to_string()).into())
+                })
+                .collect()
+        })
+        .await
+        .map_err(|e| APIError::Adapter(e.to_string()))?
     }
 
     async fn get_code_by_hash(&self, ctx: Context, hash: &Hash) -> ProtocolResult<Option<Bytes>> {
@@ -229,6 +533,36 @@ where
         self.net.peer_count(ctx).map(Into::into)
     }
 
+    async fn get_peers(&self, ctx: Context) -> ProtocolResult<Vec<ConnectedPeer>> {
+        self.net.peers(ctx)
+    }
+
+    async fn get_network_node_info(&self, ctx: Context) -> ProtocolResult<NetworkNodeInfo> {
+        self.net.node_info(ctx)
+    }
+
+    async fn add_peer(&self, ctx: Context, multiaddr: String) -> ProtocolResult<()> {
+        self.net.add_peer(ctx, &multiaddr)
+    }
+
+    async fn remove_peer(&self, ctx: Context, peer_id: String) -> ProtocolResult<bool> {
+        self.net.remove_peer(ctx, &peer_id)
+    }
+
+    async fn ban_peer(
+        &self,
+        ctx: Context,
+        peer_id: String,
+        timeout: Duration,
+        reason: String,
+    ) -> ProtocolResult<Vec<String>> {
+        self.net.ban_peer(ctx, &peer_id, timeout, reason)
+    }
+
+    async fn unban(&self, ctx: Context, ip_network: String) -> ProtocolResult<()> {
+        self.net.unban(ctx, &ip_network)
+    }
+
     async fn get_storage_at(
         &self,
         _ctx: Context,
@@ -394,3 +728,16 @@ where
         self.storage.hardfork_proposal(ctx).await
     }
 }
+
+fn call_exec_ctx(
+    mock_header: Proposal,
+    from: Option<H160>,
+    gas_price: Option<U64>,
+) -> ExecutorContext {
+    let mut exec_ctx = ExecutorContext::from(mock_header);
+    exec_ctx.origin = from.unwrap_or_default();
+    exec_ctx.gas_price = gas_price
+        .map(|p| U256::from(p.low_u64()))
+        .unwrap_or_else(U256::one);
+    exec_ctx
+}
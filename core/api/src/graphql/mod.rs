@@ -1 +1,606 @@
+//! The [EIP-1767](https://eips.ethereum.org/EIPS/eip-1767) GraphQL interface,
+//! with the Axon extensions of the block proofs and the metadata.
 
+mod object;
+mod scalar;
+
+use std::{convert::Infallible, sync::Arc};
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    BatchRequest, Context as GraphQLContext, EmptySubscription, Error, ErrorExtensions, Object,
+    Result, Schema,
+};
+use hyper::header::CONTENT_TYPE;
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{body::HttpBody, Body, Method, Request, Response, Server, StatusCode};
+use jsonrpsee::types::ErrorObjectOwned;
+
+use common_config_parser::types::Config;
+use protocol::tokio::{self, task::JoinHandle};
+use protocol::traits::{APIAdapter, Context};
+use protocol::{ProtocolError, ProtocolResult};
+
+use crate::graphql::object::{
+    Block, CallData, CallResult, FilterCriteria, Log, Metadata, Transaction,
+};
+use crate::graphql::scalar::{BigInt, Bytes, Bytes32, Long};
+use crate::jsonrpc::auth::{Auth, AuthLayer};
+use crate::jsonrpc::r#impl::{Web3RpcImpl, MAX_LOG_NUM};
+use crate::jsonrpc::rate_limit::{record_remote_addr, RateLimitLayer, RateLimiter};
+use crate::jsonrpc::{cors_layer, error::RpcError, Web3RpcServer};
+use crate::APIError;
+
+/// The nested queries such as `block { parent { parent { ... } } }` are
+/// limited to avoid the requests which are too expensive.
+const MAX_QUERY_DEPTH: usize = 16;
+
+pub type AxonSchema = Schema<Query, Mutation, EmptySubscription>;
+
+/// The configurations shared by the resolvers.
+struct GraphQLConfig {
+    max_block_range: u64,
+}
+
+pub fn build_schema<Adapter: APIAdapter + 'static>(
+    adapter: Arc<Adapter>,
+    max_gas_cap: u64,
+    max_block_range: u64,
+) -> AxonSchema {
+    let adapter: Arc<dyn APIAdapter> = adapter;
+    let web3 = Web3RpcImpl::new(Arc::clone(&adapter), max_gas_cap, max_block_range);
+
+    Schema::build(Query, Mutation, EmptySubscription)
+        .data(adapter)
+        .data(web3)
+        .data(GraphQLConfig { max_block_range })
+        .limit_depth(MAX_QUERY_DEPTH)
+        .finish()
+}
+
+/// Serves the GraphQL interface with the same auth, CORS and rate limits as the
+/// JSON-RPC HTTP server.
+pub async fn run_graphql_server<Adapter: APIAdapter + 'static>(
+    config: &Config,
+    adapter: Arc<Adapter>,
+) -> ProtocolResult<Option<JoinHandle<()>>> {
+    let addr = match config.rpc.graphql_listening_address {
+        Some(addr) => addr,
+        None => return Ok(None),
+    };
+
+    let schema = build_schema(
+        adapter,
+        config.web3.max_gas_cap,
+        config.web3.log_filter_max_block_range,
+    );
+    let max_payload_size = config.rpc.max_payload_size as usize;
+
+    let auth = Auth::from_config(&config.rpc)?;
+    let rate_limiter = RateLimiter::new(
+        config.rpc.rate_limit.as_ref(),
+        !auth.is_open(),
+        config.rpc.max_payload_size,
+    );
+    let middleware = tower::ServiceBuilder::new()
+        .layer(cors_layer(&config.rpc)?)
+        .layer(AuthLayer::new(auth))
+        .layer(RateLimitLayer::new(Arc::new(rate_limiter)));
+
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let remote_ip = conn.remote_addr().ip();
+        let schema = schema.clone();
+        let service = middleware.service(service_fn(move |req: Request<Body>| {
+            record_remote_addr(&req, remote_ip);
+            handle_request(schema.clone(), req, max_payload_size)
+        }));
+        async move { Ok::<_, Infallible>(service) }
+    });
+
+    let server = Server::try_bind(&addr)
+        .map_err(|e| APIError::GraphQLServer(e.to_string()))?
+        .serve(make_service);
+
+    Ok(Some(tokio::spawn(async move {
+        if let Err(e) = server.await {
+            log::error!("[api] graphql server error {:?}", e);
+        }
+    })))
+}
+
+/// The GraphiQL page posts the queries to the same origin, so it works with
+/// any listening address and behind the proxies.
+async fn handle_request(
+    schema: AxonSchema,
+    req: Request<Body>,
+    max_payload_size: usize,
+) -> Result<Response<Body>, hyper::Error> {
+    let resp = match *req.method() {
+        Method::GET => Response::builder()
+            .header(CONTENT_TYPE, "text/html; charset=utf-8")
+            .body(Body::from(GraphiQLSource::build().endpoint("/").finish())),
+        Method::POST => match read_body(req.into_body(), max_payload_size).await {
+            Ok(body) => match serde_json::from_slice::<BatchRequest>(&body) {
+                Ok(batch) => {
+                    let resp = schema.execute_batch(batch).await;
+                    Response::builder()
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(Body::from(serde_json::to_vec(&resp).unwrap()))
+                }
+                Err(e) => Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Body::from(e.to_string())),
+            },
+            Err(status) => Response::builder().status(status).body(Body::empty()),
+        },
+        _ => Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty()),
+    };
+
+    Ok(resp.unwrap())
+}
+
+async fn read_body(mut body: Body, max_payload_size: usize) -> Result<Vec<u8>, StatusCode> {
+    let mut ret = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if ret.len() + chunk.len() > max_payload_size {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        ret.extend_from_slice(&chunk);
+    }
+
+    Ok(ret)
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Returns the block of the given number or hash, or the latest block if
+    /// neither is given.
+    async fn block(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        number: Option<Long>,
+        hash: Option<Bytes32>,
+    ) -> Result<Option<Block>> {
+        match (number, hash) {
+            (Some(_), Some(_)) => Err(Error::new("only one of number or hash may be given")),
+            (_, Some(hash)) => Block::load_by_hash(adapter(ctx), hash.0).await,
+            (number, None) => Block::load(adapter(ctx), number.map(|n| n.0)).await,
+        }
+    }
+
+    /// Returns the blocks in the range, the end of the range is the latest
+    /// block if it is not given.
+    async fn blocks(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        from: Long,
+        to: Option<Long>,
+    ) -> Result<Vec<Block>> {
+        let adapter = adapter(ctx);
+        let (from, to) = block_range(ctx, Some(from), to).await?;
+
+        let mut ret = Vec::new();
+        for number in from..=to {
+            match Block::load(adapter, Some(number)).await? {
+                Some(block) => ret.push(block),
+                None => break,
+            }
+        }
+
+        Ok(ret)
+    }
+
+    async fn transaction(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        hash: Bytes32,
+    ) -> Result<Option<Transaction>> {
+        Transaction::load(adapter(ctx), hash.0).await
+    }
+
+    /// Returns the logs matching the filter. The block range is only limited
+    /// if the log index can't tell which blocks contain the matching logs,
+    /// and it is an error if there are too many logs.
+    async fn logs(&self, ctx: &GraphQLContext<'_>, filter: FilterCriteria) -> Result<Vec<Log>> {
+        let adapter = adapter(ctx);
+        let (from, to) = block_bounds(ctx, filter.from_block, filter.to_block).await?;
+        let (addresses, topics) = filter.into_parts();
+
+        // An empty position of the topics is a wildcard.
+        let index_topics = topics
+            .iter()
+            .map(|position| {
+                (!position.is_empty()).then(|| position.iter().copied().map(Some).collect())
+            })
+            .collect::<Vec<_>>();
+        let numbers = match adapter
+            .get_log_block_numbers(Context::new(), from, to, &addresses, &index_topics)
+            .await
+            .map_err(protocol_err)?
+        {
+            Some(numbers) => numbers,
+            None => {
+                check_block_range(ctx, from, to)?;
+                (from..=to).collect()
+            }
+        };
+
+        let mut ret = Vec::new();
+        for number in numbers {
+            let block = Block::load(adapter, Some(number))
+                .await?
+                .ok_or_else(|| rpc_err(RpcError::CannotFindBlock))?;
+            ret.extend(block.filter_logs(adapter, &addresses, &topics).await?);
+
+            if ret.len() > MAX_LOG_NUM {
+                return Err(rpc_err(RpcError::TooManyLogs(MAX_LOG_NUM)));
+            }
+        }
+
+        Ok(ret)
+    }
+
+    async fn gas_price(&self, ctx: &GraphQLContext<'_>) -> Result<BigInt> {
+        web3(ctx).gas_price().await.map(Into::into).map_err(rpc_err)
+    }
+
+    async fn max_priority_fee_per_gas(&self, ctx: &GraphQLContext<'_>) -> Result<BigInt> {
+        web3(ctx)
+            .max_priority_fee_per_gas()
+            .await
+            .map(Into::into)
+            .map_err(rpc_err)
+    }
+
+    #[graphql(name = "chainID")]
+    async fn chain_id(&self, ctx: &GraphQLContext<'_>) -> Result<BigInt> {
+        let header = adapter(ctx)
+            .get_block_header_by_number(Context::new(), None)
+            .await
+            .map_err(protocol_err)?
+            .ok_or_else(|| rpc_err(RpcError::CannotFindBlock))?;
+        Ok(BigInt(header.chain_id.into()))
+    }
+
+    /// Executes a call on the latest state without creating a transaction.
+    async fn call(&self, ctx: &GraphQLContext<'_>, data: CallData) -> Result<CallResult> {
+        object::call(ctx, data, None).await
+    }
+
+    async fn estimate_gas(&self, ctx: &GraphQLContext<'_>, data: CallData) -> Result<Long> {
+        object::estimate_gas(ctx, data, None).await
+    }
+
+    /// Returns the metadata of the epoch which the block of the given number
+    /// belongs to, or the one of the latest block.
+    async fn metadata(&self, ctx: &GraphQLContext<'_>, number: Option<Long>) -> Result<Metadata> {
+        Metadata::load(adapter(ctx), number.map(|n| n.0)).await
+    }
+}
+
+pub struct Mutation;
+
+#[Object]
+impl Mutation {
+    async fn send_raw_transaction(&self, ctx: &GraphQLContext<'_>, data: Bytes) -> Result<Bytes32> {
+        web3(ctx)
+            .send_raw_transaction(data.0)
+            .await
+            .map(Into::into)
+            .map_err(rpc_err)
+    }
+}
+
+/// Returns the limited range of block numbers, in which the missing bounds are
+/// the latest block.
+async fn block_range(
+    ctx: &GraphQLContext<'_>,
+    from: Option<Long>,
+    to: Option<Long>,
+) -> Result<(u64, u64)> {
+    let (from, to) = block_bounds(ctx, from, to).await?;
+    check_block_range(ctx, from, to)?;
+    Ok((from, to))
+}
+
+async fn block_bounds(
+    ctx: &GraphQLContext<'_>,
+    from: Option<Long>,
+    to: Option<Long>,
+) -> Result<(u64, u64)> {
+    let latest = adapter(ctx)
+        .get_block_header_by_number(Context::new(), None)
+        .await
+        .map_err(protocol_err)?
+        .ok_or_else(|| rpc_err(RpcError::CannotFindBlock))?
+        .number;
+    let from = from.map(|n| n.0).unwrap_or(latest);
+    let to = to.map(|n| n.0).unwrap_or(latest).min(latest);
+
+    if from > to {
+        return Err(rpc_err(RpcError::InvalidFromBlockAndToBlockUnion));
+    }
+
+    Ok((from, to))
+}
+
+fn check_block_range(ctx: &GraphQLContext<'_>, from: u64, to: u64) -> Result<()> {
+    let max_block_range = ctx.data_unchecked::<GraphQLConfig>().max_block_range;
+    if to - from > max_block_range {
+        return Err(rpc_err(RpcError::InvalidBlockRange(
+            from,
+            to,
+            max_block_range,
+        )));
+    }
+
+    Ok(())
+}
+
+fn adapter<'a>(ctx: &GraphQLContext<'a>) -> &'a dyn APIAdapter {
+    ctx.data_unchecked::<Arc<dyn APIAdapter>>().as_ref()
+}
+
+fn web3<'a>(ctx: &GraphQLContext<'a>) -> &'a Web3RpcImpl<dyn APIAdapter> {
+    ctx.data_unchecked::<Web3RpcImpl<dyn APIAdapter>>()
+}
+
+fn protocol_err(e: ProtocolError) -> Error {
+    Error::new(e.to_string())
+}
+
+/// Convert the errors shared with the JSON-RPC, the code is kept in the
+/// extensions.
+fn rpc_err<E: Into<ErrorObjectOwned>>(e: E) -> Error {
+    let e: ErrorObjectOwned = e.into();
+    Error::new(e.message()).extend_with(|_, ext| ext.set("code", e.code()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use protocol::tokio;
+    use protocol::types::{SignedTransaction, H160, H256};
+
+    use crate::tests::{mock_log, mock_signed_tx, MockAdapter, MOCK_TX_GAS_USED};
+
+    use super::*;
+
+    const MAX_BLOCK_RANGE: u64 = 1;
+
+    struct MockChain {
+        schema:  AxonSchema,
+        txs:     Vec<SignedTransaction>,
+        address: H160,
+        topic:   H256,
+    }
+
+    /// Block 1 contains two transactions, the first one emits a log of
+    /// `address` and a log of another address, and the second one emits a
+    /// log of `address` with `topic`. Block 2 is empty.
+    fn mock_chain(log_index: bool) -> MockChain {
+        let (address, topic) = (H160::random(), H256::random());
+        let txs = vec![
+            mock_signed_tx(H160::random(), 0),
+            mock_signed_tx(H160::random(), 0),
+        ];
+
+        let mut adapter = MockAdapter::default();
+        adapter.log_index = log_index;
+        adapter.push_block(vec![
+            (txs[0].clone(), vec![
+                mock_log(address, vec![]),
+                mock_log(H160::random(), vec![topic]),
+            ]),
+            (txs[1].clone(), vec![mock_log(address, vec![topic])]),
+        ]);
+        adapter.push_block(vec![]);
+
+        MockChain {
+            schema: build_schema(Arc::new(adapter), u64::MAX, MAX_BLOCK_RANGE),
+            txs,
+            address,
+            topic,
+        }
+    }
+
+    async fn query(schema: &AxonSchema, query: &str) -> Value {
+        let resp = schema.execute(query).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        resp.data.into_json().unwrap()
+    }
+
+    async fn query_err(schema: &AxonSchema, query: &str) -> String {
+        let resp = schema.execute(query).await;
+        assert_eq!(resp.errors.len(), 1);
+        resp.errors[0].message.clone()
+    }
+
+    fn hex<T: serde::Serialize>(value: T) -> Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_block() {
+        let chain = mock_chain(false);
+
+        let data = query(
+            &chain.schema,
+            "{ block(number: 1) { number transactionCount transactions { hash index } parent { number } } }",
+        )
+        .await;
+        assert_eq!(
+            data,
+            json!({ "block": {
+                "number": 1,
+                "transactionCount": 2,
+                "transactions": [
+                    { "hash": hex(chain.txs[0].transaction.hash), "index": 0 },
+                    { "hash": hex(chain.txs[1].transaction.hash), "index": 1 },
+                ],
+                "parent": { "number": 0 },
+            }})
+        );
+
+        // The latest block.
+        let data = query(&chain.schema, "{ block { number } }").await;
+        assert_eq!(data, json!({ "block": { "number": 2 } }));
+
+        let data = query(&chain.schema, "{ block(number: 3) { number } }").await;
+        assert_eq!(data, json!({ "block": null }));
+    }
+
+    #[tokio::test]
+    async fn test_transaction() {
+        let chain = mock_chain(false);
+        let tx = &chain.txs[1];
+
+        let data = query(
+            &chain.schema,
+            &format!(
+                "{{ transaction(hash: \"{:#x}\") {{ hash index from {{ address }} block {{ number }} \
+                 status gasUsed cumulativeGasUsed logs {{ index }} }} }}",
+                tx.transaction.hash
+            ),
+        )
+        .await;
+        assert_eq!(
+            data,
+            json!({ "transaction": {
+                "hash": hex(tx.transaction.hash),
+                "index": 1,
+                "from": { "address": hex(tx.sender) },
+                "block": { "number": 1 },
+                "status": 1,
+                "gasUsed": MOCK_TX_GAS_USED,
+                "cumulativeGasUsed": MOCK_TX_GAS_USED * 2,
+                "logs": [{ "index": 2 }],
+            }})
+        );
+
+        let data = query(
+            &chain.schema,
+            &format!(
+                "{{ transaction(hash: \"{:#x}\") {{ hash }} }}",
+                H256::random()
+            ),
+        )
+        .await;
+        assert_eq!(data, json!({ "transaction": null }));
+    }
+
+    #[tokio::test]
+    async fn test_logs() {
+        let chain = mock_chain(false);
+
+        let data = query(
+            &chain.schema,
+            &format!(
+                "{{ logs(filter: {{ fromBlock: 1, addresses: [\"{:#x}\"] }}) {{ index transaction {{ hash }} }} }}",
+                chain.address
+            ),
+        )
+        .await;
+        assert_eq!(
+            data,
+            json!({ "logs": [
+                { "index": 0, "transaction": { "hash": hex(chain.txs[0].transaction.hash) } },
+                { "index": 2, "transaction": { "hash": hex(chain.txs[1].transaction.hash) } },
+            ]})
+        );
+
+        let data = query(
+            &chain.schema,
+            &format!(
+                "{{ logs(filter: {{ fromBlock: 1, addresses: [\"{:#x}\"], topics: [[\"{:#x}\"]] }}) {{ index }} }}",
+                chain.address, chain.topic
+            ),
+        )
+        .await;
+        assert_eq!(data, json!({ "logs": [{ "index": 2 }] }));
+
+        // The range is limited without the log index.
+        let logs_from_genesis = format!(
+            "{{ logs(filter: {{ fromBlock: 0, addresses: [\"{:#x}\"] }}) {{ index }} }}",
+            chain.address
+        );
+        let err = query_err(&chain.schema, &logs_from_genesis).await;
+        assert_eq!(
+            err,
+            RpcError::InvalidBlockRange(0, 2, MAX_BLOCK_RANGE).to_string()
+        );
+
+        let indexed = mock_chain(true);
+        let data = query(
+            &indexed.schema,
+            &format!(
+                "{{ logs(filter: {{ fromBlock: 0, addresses: [\"{:#x}\"] }}) {{ index }} }}",
+                indexed.address
+            ),
+        )
+        .await;
+        assert_eq!(data, json!({ "logs": [{ "index": 0 }, { "index": 2 }] }));
+    }
+
+    #[tokio::test]
+    async fn test_too_many_logs() {
+        let address = H160::random();
+        let adapter = MockAdapter::default();
+        adapter.push_block(vec![(mock_signed_tx(H160::random(), 0), vec![
+            mock_log(
+                address,
+                vec![]
+            );
+            MAX_LOG_NUM
+                + 1
+        ])]);
+        let schema = build_schema(Arc::new(adapter), u64::MAX, MAX_BLOCK_RANGE);
+
+        let err = query_err(&schema, "{ logs(filter: { fromBlock: 1 }) { index } }").await;
+        assert_eq!(err, RpcError::TooManyLogs(MAX_LOG_NUM).to_string());
+    }
+
+    #[tokio::test]
+    async fn test_call() {
+        let chain = mock_chain(false);
+
+        let data = query(
+            &chain.schema,
+            &format!(
+                "{{ call(data: {{ from: \"{:#x}\", to: \"{:#x}\", data: \"0x1234\" }}) {{ data gasUsed status }} }}",
+                H160::random(),
+                H160::random()
+            ),
+        )
+        .await;
+        assert_eq!(
+            data,
+            json!({ "call": { "data": "0x1234", "gasUsed": MOCK_TX_GAS_USED, "status": 1 } })
+        );
+
+        // The system contracts can't be called.
+        let err = query_err(
+            &chain.schema,
+            "{ call(data: { to: \"0xffffffffffffffffffffffffffffffffffffff01\" }) { status } }",
+        )
+        .await;
+        assert_eq!(err, RpcError::CallSystemContract.to_string());
+
+        let data = query(
+            &chain.schema,
+            &format!(
+                "{{ block(number: 1) {{ call(data: {{ to: \"{:#x}\", data: \"0xab\" }}) {{ data }} }} }}",
+                H160::random()
+            ),
+        )
+        .await;
+        assert_eq!(data, json!({ "block": { "call": { "data": "0xab" } } }));
+    }
+}
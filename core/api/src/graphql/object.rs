@@ -0,0 +1,877 @@
+use std::sync::Arc;
+
+use async_graphql::{Context as GraphQLContext, Error, InputObject, Json, Object, Result};
+use either::Either;
+
+use core_executor::{is_system_contract_address_format, next_block_base_fee_per_gas};
+use protocol::constants::{MAX_GAS_LIMIT, MAX_GAS_PRICE};
+use protocol::tokio::sync::OnceCell;
+use protocol::traits::{APIAdapter, Context};
+use protocol::types::{
+    BigEndianHash, Block as AxonBlock, ConsensusConfig, Hash, Hex, Log as AxonLog,
+    Metadata as AxonMetadata, Proof as AxonProof, Receipt, SignedTransaction, ValidatorExtend,
+    H160, H256, U256, U64,
+};
+
+use crate::graphql::scalar::{Address, BigInt, Bytes, Bytes32, Long};
+use crate::graphql::{adapter, protocol_err, rpc_err, web3};
+use crate::jsonrpc::web3_types::{BlockId, Web3CallRequest, Web3Transaction, EMPTY_UNCLE_HASH};
+use crate::jsonrpc::{error::RpcError, Web3RpcServer};
+
+/// A block with the transactions and the receipts in it loaded lazily. They
+/// are fetched in one batch on the first access and shared by all the fields
+/// resolved from the block, so a query of the blocks with the transactions,
+/// the receipts and the logs does not issue a request per transaction.
+#[derive(Clone)]
+pub struct Block(Arc<BlockData>);
+
+struct BlockData {
+    block:    AxonBlock,
+    hash:     Hash,
+    txs:      OnceCell<Vec<SignedTransaction>>,
+    receipts: OnceCell<Vec<Receipt>>,
+}
+
+impl Block {
+    pub fn new(block: AxonBlock) -> Self {
+        Block(Arc::new(BlockData {
+            hash: block.hash(),
+            block,
+            txs: OnceCell::new(),
+            receipts: OnceCell::new(),
+        }))
+    }
+
+    pub async fn load(adapter: &dyn APIAdapter, number: Option<u64>) -> Result<Option<Self>> {
+        Ok(adapter
+            .get_block_by_number(Context::new(), number)
+            .await
+            .map_err(protocol_err)?
+            .map(Block::new))
+    }
+
+    pub async fn load_by_hash(adapter: &dyn APIAdapter, hash: Hash) -> Result<Option<Self>> {
+        Ok(adapter
+            .get_block_by_hash(Context::new(), hash)
+            .await
+            .map_err(protocol_err)?
+            .map(Block::new))
+    }
+
+    pub fn block_number(&self) -> u64 {
+        self.0.block.header.number
+    }
+
+    async fn signed_txs(&self, adapter: &dyn APIAdapter) -> Result<&[SignedTransaction]> {
+        let block = &self.0.block;
+        let txs = self
+            .0
+            .txs
+            .get_or_try_init(|| async {
+                adapter
+                    .get_transactions_by_hashes(
+                        Context::new(),
+                        block.header.number,
+                        &block.tx_hashes,
+                    )
+                    .await
+                    .map_err(protocol_err)?
+                    .into_iter()
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| Error::new("missing transactions of the block"))
+            })
+            .await?;
+
+        Ok(txs.as_slice())
+    }
+
+    async fn receipts(&self, adapter: &dyn APIAdapter) -> Result<&[Receipt]> {
+        let block = &self.0.block;
+        let receipts = self
+            .0
+            .receipts
+            .get_or_try_init(|| async {
+                adapter
+                    .get_receipts_by_hashes(Context::new(), block.header.number, &block.tx_hashes)
+                    .await
+                    .map_err(protocol_err)?
+                    .into_iter()
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| Error::new("missing receipts of the block"))
+            })
+            .await?;
+
+        Ok(receipts.as_slice())
+    }
+
+    async fn transaction(
+        &self,
+        adapter: &dyn APIAdapter,
+        index: usize,
+    ) -> Result<Option<Transaction>> {
+        Ok(self
+            .signed_txs(adapter)
+            .await?
+            .get(index)
+            .map(|stx| Transaction::new(stx.clone(), Some((self.clone(), index)))))
+    }
+
+    /// Returns the logs in the block which match the filter. The index of a
+    /// log is its position in the block.
+    pub async fn filter_logs(
+        &self,
+        adapter: &dyn APIAdapter,
+        addresses: &[H160],
+        topics: &[Vec<H256>],
+    ) -> Result<Vec<Log>> {
+        let mut ret = Vec::new();
+        let mut index = 0;
+        for receipt in self.receipts(adapter).await?.iter() {
+            for log in receipt.logs.iter() {
+                if match_log(log, addresses, topics) {
+                    ret.push(Log {
+                        log:      log.clone(),
+                        index:    index as u64,
+                        tx_index: receipt.tx_index as usize,
+                        block:    self.clone(),
+                    });
+                }
+                index += 1;
+            }
+        }
+
+        Ok(ret)
+    }
+}
+
+#[Object]
+impl Block {
+    async fn number(&self) -> Long {
+        self.0.block.header.number.into()
+    }
+
+    async fn hash(&self) -> Bytes32 {
+        self.0.hash.into()
+    }
+
+    async fn parent(&self, ctx: &GraphQLContext<'_>) -> Result<Option<Block>> {
+        match self.block_number() {
+            0 => Ok(None),
+            n => Block::load(adapter(ctx), Some(n - 1)).await,
+        }
+    }
+
+    async fn nonce(&self) -> Bytes {
+        Bytes(Hex::encode([0u8; 8]))
+    }
+
+    async fn transactions_root(&self) -> Bytes32 {
+        self.0.block.header.transactions_root.into()
+    }
+
+    async fn transaction_count(&self) -> Long {
+        (self.0.block.tx_hashes.len() as u64).into()
+    }
+
+    async fn state_root(&self) -> Bytes32 {
+        self.0.block.header.state_root.into()
+    }
+
+    async fn receipts_root(&self) -> Bytes32 {
+        self.0.block.header.receipts_root.into()
+    }
+
+    /// The proposer of the block.
+    async fn miner(&self, block: Option<Long>) -> Account {
+        Account {
+            address: self.0.block.header.proposer,
+            number:  Some(block.map(|n| n.0).unwrap_or(self.block_number())),
+        }
+    }
+
+    async fn extra_data(&self) -> Bytes {
+        Bytes(Hex::encode(rlp::encode_list(
+            &self.0.block.header.extra_data,
+        )))
+    }
+
+    async fn gas_limit(&self) -> Long {
+        self.0.block.header.gas_limit.low_u64().into()
+    }
+
+    async fn gas_used(&self) -> Long {
+        self.0.block.header.gas_used.low_u64().into()
+    }
+
+    async fn base_fee_per_gas(&self) -> BigInt {
+        U256::from(self.0.block.header.base_fee_per_gas.low_u64()).into()
+    }
+
+    async fn next_base_fee_per_gas(&self, ctx: &GraphQLContext<'_>) -> Result<BigInt> {
+        let hardforks = adapter(ctx)
+            .hardfork_info(Context::new())
+            .await
+            .map_err(protocol_err)?;
+        Ok(
+            U256::from(next_block_base_fee_per_gas(&self.0.block.header, &hardforks).low_u64())
+                .into(),
+        )
+    }
+
+    async fn timestamp(&self) -> Long {
+        self.0.block.header.timestamp.into()
+    }
+
+    async fn logs_bloom(&self) -> Bytes {
+        Bytes(Hex::encode(self.0.block.header.log_bloom))
+    }
+
+    async fn difficulty(&self) -> BigInt {
+        U256::zero().into()
+    }
+
+    async fn ommer_count(&self) -> Long {
+        Long(0)
+    }
+
+    async fn ommers(&self) -> Vec<Block> {
+        Vec::new()
+    }
+
+    async fn ommer_hash(&self) -> Bytes32 {
+        EMPTY_UNCLE_HASH.into()
+    }
+
+    async fn transactions(&self, ctx: &GraphQLContext<'_>) -> Result<Vec<Transaction>> {
+        Ok(self
+            .signed_txs(adapter(ctx))
+            .await?
+            .iter()
+            .enumerate()
+            .map(|(index, stx)| Transaction::new(stx.clone(), Some((self.clone(), index))))
+            .collect())
+    }
+
+    async fn transaction_at(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        index: Long,
+    ) -> Result<Option<Transaction>> {
+        self.transaction(adapter(ctx), index.0 as usize).await
+    }
+
+    async fn logs(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        filter: BlockFilterCriteria,
+    ) -> Result<Vec<Log>> {
+        let (addresses, topics) = filter.into_parts();
+        self.filter_logs(adapter(ctx), &addresses, &topics).await
+    }
+
+    async fn account(&self, address: Address) -> Account {
+        Account {
+            address: address.0,
+            number:  Some(self.block_number()),
+        }
+    }
+
+    async fn call(&self, ctx: &GraphQLContext<'_>, data: CallData) -> Result<CallResult> {
+        call(ctx, data, Some(self.block_number())).await
+    }
+
+    async fn estimate_gas(&self, ctx: &GraphQLContext<'_>, data: CallData) -> Result<Long> {
+        estimate_gas(ctx, data, Some(self.block_number())).await
+    }
+
+    /// The consensus proof of the previous block, which is carried by this
+    /// block.
+    async fn proof(&self) -> Proof {
+        Proof(self.0.block.header.proof.clone())
+    }
+
+    /// The metadata of the epoch which the block belongs to.
+    async fn metadata(&self, ctx: &GraphQLContext<'_>) -> Result<Metadata> {
+        Metadata::load(adapter(ctx), Some(self.block_number())).await
+    }
+}
+
+pub struct Transaction {
+    stx:   SignedTransaction,
+    // The block containing the transaction and the index in it, or `None` if
+    // the transaction is still in the mempool.
+    block: Option<(Block, usize)>,
+}
+
+impl Transaction {
+    pub fn new(stx: SignedTransaction, block: Option<(Block, usize)>) -> Self {
+        Transaction { stx, block }
+    }
+
+    pub async fn load(adapter: &dyn APIAdapter, hash: Hash) -> Result<Option<Self>> {
+        let stx = match adapter
+            .get_transaction_by_hash(Context::new(), hash)
+            .await
+            .map_err(protocol_err)?
+        {
+            Some(stx) => stx,
+            None => return Ok(None),
+        };
+
+        let receipt = adapter
+            .get_receipt_by_tx_hash(Context::new(), hash)
+            .await
+            .map_err(protocol_err)?;
+        let block = match receipt {
+            Some(receipt) => Block::load(adapter, Some(receipt.block_number))
+                .await?
+                .map(|block| (block, receipt.tx_index as usize)),
+            None => None,
+        };
+
+        Ok(Some(Transaction::new(stx, block)))
+    }
+
+    async fn receipt<'a>(&'a self, adapter: &dyn APIAdapter) -> Result<Option<&'a Receipt>> {
+        match &self.block {
+            Some((block, index)) => Ok(block.receipts(adapter).await?.get(*index)),
+            None => Ok(None),
+        }
+    }
+
+    fn block_number(&self) -> Option<u64> {
+        self.block.as_ref().map(|(block, _)| block.block_number())
+    }
+
+    fn signature(&self) -> Web3Transaction {
+        Web3Transaction::from(self.stx.clone())
+    }
+}
+
+#[Object]
+impl Transaction {
+    async fn hash(&self) -> Bytes32 {
+        self.stx.transaction.hash.into()
+    }
+
+    async fn nonce(&self) -> Long {
+        self.stx.transaction.unsigned.nonce().low_u64().into()
+    }
+
+    async fn index(&self) -> Option<Long> {
+        self.block.as_ref().map(|(_, index)| (*index as u64).into())
+    }
+
+    async fn from(&self, block: Option<Long>) -> Account {
+        Account {
+            address: self.stx.sender,
+            number:  block.map(|n| n.0).or(self.block_number()),
+        }
+    }
+
+    async fn to(&self, block: Option<Long>) -> Option<Account> {
+        self.stx.get_to().map(|address| Account {
+            address,
+            number: block.map(|n| n.0).or(self.block_number()),
+        })
+    }
+
+    async fn value(&self) -> BigInt {
+        (*self.stx.transaction.unsigned.value()).into()
+    }
+
+    async fn gas_price(&self) -> BigInt {
+        U256::from(self.stx.transaction.unsigned.gas_price().low_u64()).into()
+    }
+
+    async fn max_fee_per_gas(&self) -> Option<BigInt> {
+        self.signature()
+            .max_fee_per_gas
+            .map(|fee| U256::from(fee.low_u64()).into())
+    }
+
+    async fn max_priority_fee_per_gas(&self) -> Option<BigInt> {
+        self.signature()
+            .max_priority_fee_per_gas
+            .map(|fee| U256::from(fee.low_u64()).into())
+    }
+
+    async fn effective_gas_price(&self) -> Option<BigInt> {
+        self.block
+            .as_ref()
+            .map(|_| U256::from(self.stx.transaction.unsigned.gas_price().low_u64()).into())
+    }
+
+    async fn gas(&self) -> Long {
+        self.stx.transaction.unsigned.gas_limit().low_u64().into()
+    }
+
+    async fn input_data(&self) -> Bytes {
+        Bytes(Hex::encode(self.stx.transaction.unsigned.data()))
+    }
+
+    async fn block(&self) -> Option<Block> {
+        self.block.as_ref().map(|(block, _)| block.clone())
+    }
+
+    async fn status(&self, ctx: &GraphQLContext<'_>) -> Result<Option<Long>> {
+        Ok(self
+            .receipt(adapter(ctx))
+            .await?
+            .map(|receipt| receipt.status().low_u64().into()))
+    }
+
+    async fn gas_used(&self, ctx: &GraphQLContext<'_>) -> Result<Option<Long>> {
+        Ok(self
+            .receipt(adapter(ctx))
+            .await?
+            .map(|receipt| receipt.used_gas.low_u64().into()))
+    }
+
+    async fn cumulative_gas_used(&self, ctx: &GraphQLContext<'_>) -> Result<Option<Long>> {
+        let (block, index) = match &self.block {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+        let gas = block
+            .receipts(adapter(ctx))
+            .await?
+            .iter()
+            .take(index + 1)
+            .map(|receipt| receipt.used_gas.low_u64())
+            .sum::<u64>();
+        Ok(Some(gas.into()))
+    }
+
+    async fn created_contract(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        block: Option<Long>,
+    ) -> Result<Option<Account>> {
+        let number = block.map(|n| n.0).or(self.block_number());
+        Ok(self
+            .receipt(adapter(ctx))
+            .await?
+            .and_then(|receipt| receipt.code_address)
+            .map(|address| Account {
+                address: address.into(),
+                number,
+            }))
+    }
+
+    async fn logs(&self, ctx: &GraphQLContext<'_>) -> Result<Option<Vec<Log>>> {
+        let (block, index) = match &self.block {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+        let logs = block.filter_logs(adapter(ctx), &[], &[]).await?;
+        Ok(Some(
+            logs.into_iter()
+                .filter(|log| log.tx_index == *index)
+                .collect(),
+        ))
+    }
+
+    /// The signature values, which are `null` for the signatures of the
+    /// interoperation transactions.
+    async fn r(&self) -> Option<BigInt> {
+        match self.signature().r {
+            Either::Left(r) => Some(r.into()),
+            Either::Right(_) => None,
+        }
+    }
+
+    async fn s(&self) -> Option<BigInt> {
+        match self.signature().s {
+            Either::Left(s) => Some(s.into()),
+            Either::Right(_) => None,
+        }
+    }
+
+    async fn v(&self) -> BigInt {
+        U256::from(self.signature().v.low_u64()).into()
+    }
+
+    #[graphql(name = "type")]
+    async fn type_(&self) -> Long {
+        self.stx.type_().into()
+    }
+
+    async fn raw(&self) -> Bytes {
+        Bytes(self.signature().raw)
+    }
+}
+
+pub struct Log {
+    log:      AxonLog,
+    index:    u64,
+    tx_index: usize,
+    block:    Block,
+}
+
+#[Object]
+impl Log {
+    async fn index(&self) -> Long {
+        self.index.into()
+    }
+
+    async fn account(&self, block: Option<Long>) -> Account {
+        Account {
+            address: self.log.address,
+            number:  Some(block.map(|n| n.0).unwrap_or(self.block.block_number())),
+        }
+    }
+
+    async fn topics(&self) -> Vec<Bytes32> {
+        self.log.topics.iter().copied().map(Into::into).collect()
+    }
+
+    async fn data(&self) -> Bytes {
+        Bytes(Hex::encode(&self.log.data))
+    }
+
+    async fn transaction(&self, ctx: &GraphQLContext<'_>) -> Result<Transaction> {
+        self.block
+            .transaction(adapter(ctx), self.tx_index)
+            .await?
+            .ok_or_else(|| Error::new("missing transaction of the log"))
+    }
+}
+
+/// An account at the state after the given block, or the latest block if it
+/// is `None`.
+pub struct Account {
+    address: H160,
+    number:  Option<u64>,
+}
+
+#[Object]
+impl Account {
+    async fn address(&self) -> Address {
+        self.address.into()
+    }
+
+    async fn balance(&self, ctx: &GraphQLContext<'_>) -> Result<BigInt> {
+        let account = adapter(ctx)
+            .get_account(Context::new(), self.address, self.number)
+            .await
+            .map_err(protocol_err)?;
+        Ok(account.balance.into())
+    }
+
+    async fn transaction_count(&self, ctx: &GraphQLContext<'_>) -> Result<Long> {
+        let account = adapter(ctx)
+            .get_account(Context::new(), self.address, self.number)
+            .await
+            .map_err(protocol_err)?;
+        Ok(account.nonce.low_u64().into())
+    }
+
+    async fn code(&self, ctx: &GraphQLContext<'_>) -> Result<Bytes> {
+        let adapter = adapter(ctx);
+        let account = adapter
+            .get_account(Context::new(), self.address, self.number)
+            .await
+            .map_err(protocol_err)?;
+        let code = adapter
+            .get_code_by_hash(Context::new(), &account.code_hash)
+            .await
+            .map_err(protocol_err)?;
+
+        Ok(Bytes(code.map(Hex::encode).unwrap_or_else(Hex::empty)))
+    }
+
+    async fn storage(&self, ctx: &GraphQLContext<'_>, slot: Bytes32) -> Result<Bytes32> {
+        if is_system_contract_address_format(&self.address) {
+            return Err(rpc_err(RpcError::CallSystemContract));
+        }
+
+        let adapter = adapter(ctx);
+        let header = adapter
+            .get_block_header_by_number(Context::new(), self.number)
+            .await
+            .map_err(protocol_err)?
+            .ok_or_else(|| rpc_err(RpcError::CannotFindBlock))?;
+        let value = adapter
+            .get_storage_at(
+                Context::new(),
+                self.address,
+                U256::from_big_endian(slot.0.as_bytes()),
+                header.state_root,
+            )
+            .await
+            .map_err(protocol_err)?;
+
+        Ok(H256::from_uint(&U256::from_big_endian(&value)).into())
+    }
+}
+
+/// The consensus proof of a block.
+pub struct Proof(AxonProof);
+
+#[Object]
+impl Proof {
+    async fn number(&self) -> Long {
+        self.0.number.into()
+    }
+
+    async fn round(&self) -> Long {
+        self.0.round.into()
+    }
+
+    async fn block_hash(&self) -> Bytes32 {
+        self.0.block_hash.into()
+    }
+
+    /// The aggregated BLS signature of the validators.
+    async fn signature(&self) -> Bytes {
+        Bytes(Hex::encode(&self.0.signature))
+    }
+
+    /// The bitmap of the validators who signed the block.
+    async fn bitmap(&self) -> Bytes {
+        Bytes(Hex::encode(&self.0.bitmap))
+    }
+}
+
+/// The metadata of an epoch.
+pub struct Metadata(AxonMetadata);
+
+impl Metadata {
+    pub async fn load(adapter: &dyn APIAdapter, number: Option<u64>) -> Result<Self> {
+        adapter
+            .get_metadata_by_number(Context::new(), number)
+            .await
+            .map(Metadata)
+            .map_err(protocol_err)
+    }
+}
+
+#[Object]
+impl Metadata {
+    async fn epoch(&self) -> Long {
+        self.0.epoch.into()
+    }
+
+    async fn start_number(&self) -> Long {
+        self.0.version.start.into()
+    }
+
+    async fn end_number(&self) -> Long {
+        self.0.version.end.into()
+    }
+
+    async fn verifiers(&self) -> Vec<Validator> {
+        self.0
+            .verifier_list
+            .iter()
+            .cloned()
+            .map(Validator)
+            .collect()
+    }
+
+    async fn consensus_config(&self) -> Json<ConsensusConfig> {
+        Json(self.0.consensus_config.clone())
+    }
+}
+
+pub struct Validator(ValidatorExtend);
+
+#[Object]
+impl Validator {
+    async fn address(&self) -> Address {
+        self.0.address.into()
+    }
+
+    async fn bls_pub_key(&self) -> Bytes {
+        Bytes(self.0.bls_pub_key.clone())
+    }
+
+    async fn pub_key(&self) -> Bytes {
+        Bytes(self.0.pub_key.clone())
+    }
+
+    async fn propose_weight(&self) -> Long {
+        (self.0.propose_weight as u64).into()
+    }
+
+    async fn vote_weight(&self) -> Long {
+        (self.0.vote_weight as u64).into()
+    }
+}
+
+#[derive(InputObject)]
+pub struct BlockFilterCriteria {
+    addresses: Option<Vec<Address>>,
+    /// Each position matches any of the topics in it, and an empty position
+    /// matches any topic.
+    topics:    Option<Vec<Vec<Bytes32>>>,
+}
+
+impl BlockFilterCriteria {
+    fn into_parts(self) -> (Vec<H160>, Vec<Vec<H256>>) {
+        into_parts(self.addresses, self.topics)
+    }
+}
+
+#[derive(InputObject)]
+pub struct FilterCriteria {
+    /// The latest block if it is `None`.
+    pub from_block: Option<Long>,
+    /// The latest block if it is `None`.
+    pub to_block:   Option<Long>,
+    addresses:      Option<Vec<Address>>,
+    topics:         Option<Vec<Vec<Bytes32>>>,
+}
+
+impl FilterCriteria {
+    pub fn into_parts(self) -> (Vec<H160>, Vec<Vec<H256>>) {
+        into_parts(self.addresses, self.topics)
+    }
+}
+
+fn into_parts(
+    addresses: Option<Vec<Address>>,
+    topics: Option<Vec<Vec<Bytes32>>>,
+) -> (Vec<H160>, Vec<Vec<H256>>) {
+    (
+        addresses
+            .unwrap_or_default()
+            .into_iter()
+            .map(|a| a.0)
+            .collect(),
+        topics
+            .unwrap_or_default()
+            .into_iter()
+            .take(4)
+            .map(|t| t.into_iter().map(|t| t.0).collect())
+            .collect(),
+    )
+}
+
+fn match_log(log: &AxonLog, addresses: &[H160], topics: &[Vec<H256>]) -> bool {
+    (addresses.is_empty() || addresses.contains(&log.address))
+        && topics.iter().enumerate().all(|(i, position)| {
+            position.is_empty()
+                || log
+                    .topics
+                    .get(i)
+                    .map(|topic| position.contains(topic))
+                    .unwrap_or(false)
+        })
+}
+
+#[derive(InputObject)]
+pub struct CallData {
+    from:                     Option<Address>,
+    to:                       Option<Address>,
+    gas:                      Option<Long>,
+    gas_price:                Option<BigInt>,
+    max_fee_per_gas:          Option<BigInt>,
+    max_priority_fee_per_gas: Option<BigInt>,
+    value:                    Option<BigInt>,
+    data:                     Option<Bytes>,
+}
+
+impl TryFrom<CallData> for Web3CallRequest {
+    type Error = Error;
+
+    fn try_from(data: CallData) -> Result<Self> {
+        let to_u64 = |v: Option<BigInt>| -> Result<Option<U64>> {
+            match v {
+                Some(v) if v.0 > U256::from(u64::MAX) => Err(Error::new("fee is too large")),
+                Some(v) => Ok(Some(v.0.low_u64().into())),
+                None => Ok(None),
+            }
+        };
+
+        Ok(Web3CallRequest {
+            transaction_type:         None,
+            from:                     data.from.map(|a| a.0),
+            to:                       data.to.map(|a| a.0),
+            gas_price:                to_u64(data.gas_price)?,
+            max_fee_per_gas:          to_u64(data.max_fee_per_gas)?,
+            gas:                      data.gas.map(|g| g.0.into()),
+            value:                    data.value.map(|v| v.0),
+            data:                     data.data.map(|d| d.0),
+            nonce:                    None,
+            access_list:              None,
+            chain_id:                 None,
+            max_priority_fee_per_gas: data.max_priority_fee_per_gas.map(|v| v.0),
+        })
+    }
+}
+
+pub struct CallResult {
+    data:     Hex,
+    gas_used: u64,
+    status:   u64,
+}
+
+#[Object]
+impl CallResult {
+    async fn data(&self) -> Bytes {
+        Bytes(self.data.clone())
+    }
+
+    async fn gas_used(&self) -> Long {
+        self.gas_used.into()
+    }
+
+    async fn status(&self) -> Long {
+        self.status.into()
+    }
+}
+
+/// Execute a call on the state after the given block. Unlike `eth_call`, a
+/// reverted call is not an error but a result with status 0.
+pub async fn call(
+    ctx: &GraphQLContext<'_>,
+    data: CallData,
+    number: Option<u64>,
+) -> Result<CallResult> {
+    let req = Web3CallRequest::try_from(data)?;
+    if req.gas.unwrap_or_default() > MAX_GAS_LIMIT.into() {
+        return Err(rpc_err(RpcError::GasLimitIsTooLarge));
+    }
+
+    if req.gas_price.unwrap_or_default() > MAX_GAS_PRICE {
+        return Err(rpc_err(RpcError::GasPriceIsTooLarge));
+    }
+
+    if req.to.as_ref().map(is_system_contract_address_format) == Some(true) {
+        return Err(rpc_err(RpcError::CallSystemContract));
+    }
+
+    let data = req
+        .data
+        .as_ref()
+        .map(|hex| hex.as_bytes())
+        .unwrap_or_default();
+    let resp = web3(ctx)
+        .call_evm(req, data, number, None, None)
+        .await
+        .map_err(protocol_err)?;
+
+    Ok(CallResult {
+        data:     Hex::encode(resp.ret),
+        gas_used: resp.gas_used,
+        status:   resp.exit_reason.is_succeed() as u64,
+    })
+}
+
+pub async fn estimate_gas(
+    ctx: &GraphQLContext<'_>,
+    data: CallData,
+    number: Option<u64>,
+) -> Result<Long> {
+    let block_id = number.map(|n| BlockId::Num(n.into()));
+    let gas = web3(ctx)
+        .estimate_gas(Web3CallRequest::try_from(data)?, block_id, None, None)
+        .await
+        .map_err(rpc_err)?;
+
+    Ok(gas.low_u64().into())
+}
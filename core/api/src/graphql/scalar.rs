@@ -0,0 +1,153 @@
+use async_graphql::{
+    from_value, to_value, InputValueError, InputValueResult, Scalar, ScalarType, Value,
+};
+
+use protocol::types::{Hex, H160, H256, U256};
+
+macro_rules! impl_hex_scalar {
+    ($(#[$meta: meta])* $name: ident, $inner: ty) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct $name(pub $inner);
+
+        #[Scalar]
+        impl ScalarType for $name {
+            fn parse(value: Value) -> InputValueResult<Self> {
+                from_value(value).map($name).map_err(InputValueError::custom)
+            }
+
+            fn to_value(&self) -> Value {
+                to_value(&self.0).unwrap_or(Value::Null)
+            }
+        }
+
+        impl From<$inner> for $name {
+            fn from(inner: $inner) -> Self {
+                $name(inner)
+            }
+        }
+    };
+}
+
+impl_hex_scalar!(
+    /// A 32 bytes binary string, represented as 0x-prefixed hexadecimal.
+    Bytes32,
+    H256
+);
+
+impl_hex_scalar!(
+    /// A 20 bytes Ethereum address, represented as 0x-prefixed hexadecimal.
+    Address,
+    H160
+);
+
+impl_hex_scalar!(
+    /// An arbitrary length binary string, represented as 0x-prefixed
+    /// hexadecimal.
+    Bytes,
+    Hex
+);
+
+/// A 256 bits integer, represented as 0x-prefixed hexadecimal in output. The
+/// input can be either hexadecimal or decimal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigInt(pub U256);
+
+#[Scalar]
+impl ScalarType for BigInt {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match value {
+            Value::Number(n) => n
+                .as_u64()
+                .map(|n| BigInt(n.into()))
+                .ok_or_else(|| InputValueError::custom("negative or fractional BigInt")),
+            Value::String(s) if s.starts_with("0x") => from_value(Value::String(s))
+                .map(BigInt)
+                .map_err(InputValueError::custom),
+            Value::String(s) => U256::from_dec_str(&s)
+                .map(BigInt)
+                .map_err(|e| InputValueError::custom(format!("{:?}", e))),
+            v => Err(InputValueError::expected_type(v)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        to_value(self.0).unwrap_or(Value::Null)
+    }
+}
+
+impl From<U256> for BigInt {
+    fn from(inner: U256) -> Self {
+        BigInt(inner)
+    }
+}
+
+/// A 64 bits unsigned integer. The input can be either a number or a
+/// 0x-prefixed hexadecimal string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Long(pub u64);
+
+#[Scalar]
+impl ScalarType for Long {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match value {
+            Value::Number(n) => n
+                .as_u64()
+                .map(Long)
+                .ok_or_else(|| InputValueError::custom("negative or fractional Long")),
+            Value::String(s) => s
+                .strip_prefix("0x")
+                .and_then(|s| u64::from_str_radix(s, 16).ok())
+                .map(Long)
+                .ok_or_else(|| InputValueError::custom("invalid hexadecimal Long")),
+            v => Err(InputValueError::expected_type(v)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Number(self.0.into())
+    }
+}
+
+impl From<u64> for Long {
+    fn from(inner: u64) -> Self {
+        Long(inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_long() {
+        assert_eq!(Long::parse(Value::from(16u64)).unwrap(), Long(16));
+        assert_eq!(Long::parse(Value::from("0x10")).unwrap(), Long(16));
+        assert!(Long::parse(Value::from("16")).is_err());
+        assert!(Long::parse(Value::from(-1)).is_err());
+        assert_eq!(Long(16).to_value(), Value::from(16u64));
+    }
+
+    #[test]
+    fn test_parse_big_int() {
+        assert_eq!(
+            BigInt::parse(Value::from("0x10")).unwrap(),
+            BigInt(16.into())
+        );
+        assert_eq!(BigInt::parse(Value::from("16")).unwrap(), BigInt(16.into()));
+        assert_eq!(
+            BigInt::parse(Value::from(16u64)).unwrap(),
+            BigInt(16.into())
+        );
+        assert_eq!(BigInt(16.into()).to_value(), Value::from("0x10"));
+    }
+
+    #[test]
+    fn test_parse_bytes32() {
+        let hash = H256::random();
+        let value = to_value(hash).unwrap();
+        assert_eq!(Bytes32::parse(value.clone()).unwrap(), Bytes32(hash));
+        assert_eq!(Bytes32(hash).to_value(), value);
+        assert!(Bytes32::parse(Value::from("0x10")).is_err());
+    }
+}
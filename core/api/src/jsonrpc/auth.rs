@@ -0,0 +1,350 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use hmac::{Hmac, Mac};
+use hyper::{header::AUTHORIZATION, HeaderMap, Request, Response, StatusCode};
+use jsonrpsee::server::Methods;
+use serde::Deserialize;
+use sha2::Sha256;
+use tower::{Layer, Service};
+
+use common_config_parser::types::ConfigApi;
+use protocol::codec::hex_decode;
+
+use crate::APIError;
+
+/// The header carrying a static API key, as an alternative to the
+/// `Authorization: Bearer <key>` header.
+pub const API_KEY_HEADER: &str = "x-api-key";
+/// The maximum difference in seconds between the `iat` claim of a JWT and the
+/// local time, which is the same as the engine API of geth.
+const JWT_IAT_LEEWAY: u64 = 60;
+/// The HMAC key used to digest the API keys before comparing them.
+const API_KEY_MAC_KEY: &[u8] = b"axon-api-key";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The credentials accepted by the RPC server. A request is authorized if it
+/// carries one of the static API keys, or a JWT signed with the HS256 secret.
+/// Every request is authorized if neither of them is configured.
+#[derive(Default)]
+pub struct Auth {
+    jwt_secret: Option<Vec<u8>>,
+    api_keys:   HashSet<String>,
+}
+
+impl Auth {
+    pub fn from_config(config: &ConfigApi) -> Result<Self, APIError> {
+        let jwt_secret = match config.jwt_secret_path {
+            Some(ref path) => {
+                let secret = std::fs::read_to_string(path).map_err(|e| {
+                    APIError::Auth(format!("read jwt secret {}: {}", path.display(), e))
+                })?;
+                let secret = hex_decode(secret.trim())
+                    .map_err(|e| APIError::Auth(format!("decode jwt secret: {}", e)))?;
+                if secret.is_empty() {
+                    return Err(APIError::Auth("jwt secret is empty".to_string()));
+                }
+                Some(secret)
+            }
+            None => None,
+        };
+        let api_keys = config
+            .api_keys
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        Ok(Auth {
+            jwt_secret,
+            api_keys,
+        })
+    }
+
+    pub fn with_api_key(key: &str) -> Self {
+        Auth {
+            jwt_secret: None,
+            api_keys:   HashSet::from([key.to_string()]),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.jwt_secret.is_none() && self.api_keys.is_empty()
+    }
+
+    pub fn api_keys(&self) -> &HashSet<String> {
+        &self.api_keys
+    }
+
+    pub fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        if self.is_open() {
+            return true;
+        }
+
+        let Some(token) = request_token(headers) else {
+            return false;
+        };
+
+        if self.is_api_key(token) {
+            return true;
+        }
+
+        match self.jwt_secret {
+            Some(ref secret) => verify_jwt(secret, token, unix_now()).is_some(),
+            None => false,
+        }
+    }
+
+    /// Compares the token with every API key in constant time. The digests
+    /// rather than the keys are compared, so that neither the content nor the
+    /// length of a key leaks through the time it takes.
+    fn is_api_key(&self, token: &str) -> bool {
+        let token_mac = api_key_mac(token);
+
+        self.api_keys.iter().fold(false, |found, key| {
+            let key_digest = api_key_mac(key).finalize().into_bytes();
+            token_mac.clone().verify_slice(&key_digest).is_ok() | found
+        })
+    }
+}
+
+fn api_key_mac(key: &str) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(API_KEY_MAC_KEY).expect("hmac accepts keys of any length");
+    mac.update(key.as_bytes());
+    mac
+}
+
+/// Returns the bearer token or the API key of the request.
+pub fn request_token(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    bearer
+        .or_else(|| {
+            headers
+                .get(API_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+        })
+        .map(str::trim)
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct JwtClaims {
+    iat: u64,
+    exp: Option<u64>,
+}
+
+fn verify_jwt(secret: &[u8], token: &str, now: u64) -> Option<()> {
+    let (message, signature) = token.rsplit_once('.')?;
+    let (header, claims) = message.split_once('.')?;
+
+    let header: JwtHeader = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header).ok()?).ok()?;
+    (header.alg == "HS256").then_some(())?;
+
+    let mut mac = HmacSha256::new_from_slice(secret).ok()?;
+    mac.update(message.as_bytes());
+    mac.verify_slice(&URL_SAFE_NO_PAD.decode(signature).ok()?)
+        .ok()?;
+
+    let claims: JwtClaims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims).ok()?).ok()?;
+    (claims.iat.abs_diff(now) <= JWT_IAT_LEEWAY).then_some(())?;
+    if let Some(exp) = claims.exp {
+        (exp > now).then_some(())?;
+    }
+
+    Some(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// A tower layer rejecting the unauthorized HTTP requests, including the
+/// WebSocket handshakes, with `401 Unauthorized`.
+#[derive(Clone)]
+pub struct AuthLayer {
+    auth: Arc<Auth>,
+}
+
+impl AuthLayer {
+    pub fn new(auth: Auth) -> Self {
+        AuthLayer {
+            auth: Arc::new(auth),
+        }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService {
+            inner,
+            auth: Arc::clone(&self.auth),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AuthService<S> {
+    inner: S,
+    auth:  Arc<Auth>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for AuthService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+    type Response = Response<ResBody>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        if self.auth.is_authorized(req.headers()) {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let mut resp = Response::new(ResBody::default());
+        *resp.status_mut() = StatusCode::UNAUTHORIZED;
+        Box::pin(async move { Ok(resp) })
+    }
+}
+
+/// The per-method access control. An entry of the lists is either a namespace
+/// such as `debug`, or a full method name such as `debug_traceTransaction`.
+/// A method is served if it matches the allowlist, or the allowlist is empty,
+/// and it does not match the denylist.
+#[derive(Default)]
+pub struct MethodFilter {
+    allowlist: HashSet<String>,
+    denylist:  HashSet<String>,
+}
+
+impl MethodFilter {
+    pub fn from_config(config: &ConfigApi) -> Self {
+        let collect = |list: &Option<Vec<String>>| list.iter().flatten().cloned().collect();
+
+        MethodFilter {
+            allowlist: collect(&config.method_allowlist),
+            denylist:  collect(&config.method_denylist),
+        }
+    }
+
+    pub fn is_allowed(&self, method: &str) -> bool {
+        let matches = |list: &HashSet<String>| {
+            list.contains(method)
+                || method
+                    .split_once('_')
+                    .map(|(namespace, _)| list.contains(namespace))
+                    .unwrap_or_default()
+        };
+
+        (self.allowlist.is_empty() || matches(&self.allowlist)) && !matches(&self.denylist)
+    }
+
+    /// Returns the allowed methods of the given ones, so that the others are
+    /// answered with `Method not found`.
+    pub fn restrict(&self, methods: impl Into<Methods>) -> Methods {
+        let methods = methods.into();
+        let mut allowed = Methods::new();
+
+        for name in methods.method_names().filter(|name| self.is_allowed(name)) {
+            let callback = methods.method(name).cloned().expect("listed method");
+            allowed
+                .verify_and_insert(name, callback)
+                .expect("method names are unique");
+        }
+
+        allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign_jwt(secret: &[u8], alg: &str, claims: &str) -> String {
+        let header = URL_SAFE_NO_PAD.encode(format!(r#"{{"alg":"{}","typ":"JWT"}}"#, alg));
+        let message = format!("{}.{}", header, URL_SAFE_NO_PAD.encode(claims));
+        let mut mac = HmacSha256::new_from_slice(secret).unwrap();
+        mac.update(message.as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        format!("{}.{}", message, signature)
+    }
+
+    #[test]
+    fn test_verify_jwt() {
+        let secret = [7u8; 32];
+        let now = 1_700_000_000;
+
+        let token = sign_jwt(&secret, "HS256", &format!(r#"{{"iat":{}}}"#, now));
+        assert!(verify_jwt(&secret, &token, now + JWT_IAT_LEEWAY).is_some());
+        assert!(verify_jwt(&secret, &token, now + JWT_IAT_LEEWAY + 1).is_none());
+        assert!(verify_jwt(&[8u8; 32], &token, now).is_none());
+
+        let token = sign_jwt(&secret, "none", &format!(r#"{{"iat":{}}}"#, now));
+        assert!(verify_jwt(&secret, &token, now).is_none());
+
+        let claims = format!(r#"{{"iat":{},"exp":{}}}"#, now, now);
+        let token = sign_jwt(&secret, "HS256", &claims);
+        assert!(verify_jwt(&secret, &token, now).is_none());
+    }
+
+    #[test]
+    fn test_admin_token() {
+        let auth = Auth::with_api_key("secret");
+        let headers = |name: &str, value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                hyper::header::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+            headers
+        };
+
+        assert!(auth.is_authorized(&headers("authorization", "Bearer secret")));
+        assert!(auth.is_authorized(&headers(API_KEY_HEADER, "secret")));
+        assert!(!auth.is_authorized(&headers("authorization", "Bearer other")));
+        assert!(!auth.is_authorized(&headers(API_KEY_HEADER, "secre")));
+        assert!(!auth.is_authorized(&headers("authorization", "secret")));
+        assert!(!auth.is_authorized(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_method_filter() {
+        let filter = MethodFilter {
+            allowlist: HashSet::from(["eth".to_string(), "debug_traceTransaction".to_string()]),
+            denylist:  HashSet::from(["eth_sendRawTransactionSync".to_string()]),
+        };
+
+        assert!(filter.is_allowed("eth_call"));
+        assert!(filter.is_allowed("debug_traceTransaction"));
+        assert!(!filter.is_allowed("debug_traceCall"));
+        assert!(!filter.is_allowed("eth_sendRawTransactionSync"));
+        assert!(!filter.is_allowed("pprof"));
+        assert!(MethodFilter::default().is_allowed("pprof"));
+    }
+}
@@ -1,6 +1,6 @@
 use jsonrpsee::types::{error::ErrorObject, ErrorObjectOwned};
 
-use protocol::types::{ExitReason, TxResp};
+use protocol::types::{ExitReason, TxResp, H256};
 use protocol::{codec::hex_encode, Display};
 
 use core_executor::decode_revert_msg;
@@ -51,6 +51,26 @@ pub enum RpcError {
     CannotFindFilterId(u64),
     #[display(fmt = "Not allow to call system contract address")]
     CallSystemContract,
+    #[display(fmt = "Cannot find the transaction")]
+    CannotFindTransaction,
+    #[display(fmt = "Invalid tracer {}", _0)]
+    InvalidTracer(String),
+    #[display(fmt = "Invalid timeout {}", _0)]
+    InvalidTimeout(String),
+    #[display(fmt = "Execution timeout")]
+    ExecutionTimeout,
+    #[display(fmt = "Gas required exceeds allowance {}", _0)]
+    GasRequiredExceedsAllowance(u64),
+    #[display(fmt = "Invalid state override {}", _0)]
+    InvalidStateOverride(String),
+    #[display(fmt = "Invalid simulation {}", _0)]
+    InvalidSimulation(String),
+    #[display(fmt = "Transaction {:#x} is not committed before timeout", _0)]
+    TransactionCommitTimeout(H256),
+    #[display(fmt = "Request rate limit exceeded")]
+    RateLimited,
+    #[display(fmt = "Query returned more than {} results", _0)]
+    TooManyLogs(usize),
 
     #[display(fmt = "EVM error {}", "decode_revert_msg(&_0.ret)")]
     Evm(TxResp),
@@ -88,6 +108,16 @@ impl RpcError {
             RpcError::InvalidFromBlockAndToBlockUnion => -40021,
             RpcError::CannotFindFilterId(_) => -40022,
             RpcError::CallSystemContract => -40023,
+            RpcError::CannotFindTransaction => -40024,
+            RpcError::InvalidTracer(_) => -40025,
+            RpcError::InvalidTimeout(_) => -40026,
+            RpcError::ExecutionTimeout => -40027,
+            RpcError::GasRequiredExceedsAllowance(_) => -40028,
+            RpcError::InvalidStateOverride(_) => -40029,
+            RpcError::InvalidSimulation(_) => -40030,
+            RpcError::TransactionCommitTimeout(_) => -40031,
+            RpcError::RateLimited => -40032,
+            RpcError::TooManyLogs(_) => -40033,
 
             RpcError::Evm(_) => -49998,
             RpcError::Internal(_) => -49999,
@@ -129,6 +159,18 @@ impl From<RpcError> for ErrorObjectOwned {
             }
             RpcError::CannotFindFilterId(_) => ErrorObject::owned(err_code, err, none_data),
             RpcError::CallSystemContract => ErrorObject::owned(err_code, err, none_data),
+            RpcError::CannotFindTransaction => ErrorObject::owned(err_code, err, none_data),
+            RpcError::InvalidTracer(_) => ErrorObject::owned(err_code, err, none_data),
+            RpcError::InvalidTimeout(_) => ErrorObject::owned(err_code, err, none_data),
+            RpcError::ExecutionTimeout => ErrorObject::owned(err_code, err, none_data),
+            RpcError::GasRequiredExceedsAllowance(_) => {
+                ErrorObject::owned(err_code, err, none_data)
+            }
+            RpcError::InvalidStateOverride(_) => ErrorObject::owned(err_code, err, none_data),
+            RpcError::InvalidSimulation(_) => ErrorObject::owned(err_code, err, none_data),
+            RpcError::TransactionCommitTimeout(_) => ErrorObject::owned(err_code, err, none_data),
+            RpcError::RateLimited => ErrorObject::owned(err_code, err, none_data),
+            RpcError::TooManyLogs(_) => ErrorObject::owned(err_code, err, none_data),
 
             RpcError::Evm(resp) => {
                 ErrorObject::owned(err_code, err.clone(), Some(vm_err(resp.clone())))
@@ -0,0 +1,213 @@
+use std::{sync::Arc, time::Duration};
+
+use jsonrpsee::core::RpcResult;
+
+use protocol::traits::{APIAdapter, Context};
+use protocol::{async_trait, lazy::CHAIN_ID};
+
+use crate::jsonrpc::web3_types::{NodeInfo, PeerInfo};
+use crate::jsonrpc::{error::RpcError, AdminRpcServer};
+
+/// The default ban duration of `admin_banPeer`, one day.
+const DEFAULT_BAN_DURATION: u64 = 24 * 60 * 60;
+
+pub struct AdminRpcImpl<Adapter> {
+    adapter: Arc<Adapter>,
+    version: String,
+}
+
+impl<Adapter: APIAdapter> AdminRpcImpl<Adapter> {
+    pub fn new(adapter: Arc<Adapter>, version: String) -> Self {
+        AdminRpcImpl { adapter, version }
+    }
+}
+
+#[async_trait]
+impl<Adapter: APIAdapter + 'static> AdminRpcServer for AdminRpcImpl<Adapter> {
+    async fn peers(&self) -> RpcResult<Vec<PeerInfo>> {
+        let peers = self
+            .adapter
+            .get_peers(Context::new())
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        Ok(peers.into_iter().map(Into::into).collect())
+    }
+
+    async fn node_info(&self) -> RpcResult<NodeInfo> {
+        let ctx = Context::new();
+        let info = self
+            .adapter
+            .get_network_node_info(ctx.clone())
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+        let latest = self
+            .adapter
+            .get_block_header_by_number(ctx, None)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .ok_or(RpcError::CannotGetLatestBlock)?;
+
+        Ok(NodeInfo {
+            version:        self.version.clone(),
+            peer_id:        info.peer_id,
+            listen_addrs:   info.listen_addrs,
+            chain_id:       (**CHAIN_ID.load()).into(),
+            latest_block:   latest.number.into(),
+            inbound_peers:  info.inbound,
+            outbound_peers: info.outbound,
+            banned_ips:     info.banned,
+        })
+    }
+
+    async fn add_peer(&self, multiaddr: String) -> RpcResult<bool> {
+        self.adapter
+            .add_peer(Context::new(), multiaddr)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        Ok(true)
+    }
+
+    async fn remove_peer(&self, peer_id: String) -> RpcResult<bool> {
+        let removed = self
+            .adapter
+            .remove_peer(Context::new(), peer_id)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        Ok(removed)
+    }
+
+    async fn ban_peer(
+        &self,
+        peer_id: String,
+        duration: Option<u64>,
+        reason: Option<String>,
+    ) -> RpcResult<Vec<String>> {
+        let duration = Duration::from_secs(duration.unwrap_or(DEFAULT_BAN_DURATION));
+        let reason = reason.unwrap_or_else(|| "ban from admin rpc".to_string());
+        let banned = self
+            .adapter
+            .ban_peer(Context::new(), peer_id, duration, reason)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        Ok(banned)
+    }
+
+    async fn unban(&self, ip_network: String) -> RpcResult<bool> {
+        self.adapter
+            .unban(Context::new(), ip_network)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::tokio;
+    use protocol::traits::{ConnectedPeer, PeerDirection};
+
+    use crate::tests::{MockAdapter, MOCK_PEER_ID};
+
+    use super::*;
+
+    const PEER_ID: &str = "QmaQPgFBbMxnEA6Ge5cX3ri9ZvhpbwQ8hGzeWvxF9Z7Bzt";
+
+    fn admin_rpc() -> (Arc<MockAdapter>, AdminRpcImpl<MockAdapter>) {
+        let adapter = Arc::new(MockAdapter::default());
+        let rpc = AdminRpcImpl::new(Arc::clone(&adapter), "0.1.0".to_string());
+        (adapter, rpc)
+    }
+
+    #[tokio::test]
+    async fn test_admin_peers() {
+        let (adapter, rpc) = admin_rpc();
+        adapter.push_peer(ConnectedPeer {
+            peer_id:      PEER_ID.to_string(),
+            multiaddr:    "/ip4/10.0.0.1/tcp/8001".to_string(),
+            direction:    PeerDirection::Inbound,
+            latency:      Some(Duration::from_micros(1500)),
+            is_consensus: true,
+        });
+
+        let peers = rpc.peers().await.unwrap();
+        assert_eq!(peers, vec![PeerInfo {
+            peer_id:      PEER_ID.to_string(),
+            multiaddr:    "/ip4/10.0.0.1/tcp/8001".to_string(),
+            direction:    "inbound".to_string(),
+            latency:      Some(1),
+            is_consensus: true,
+        }]);
+
+        let info = rpc.node_info().await.unwrap();
+        assert_eq!(info.version, "0.1.0");
+        assert_eq!(info.peer_id, MOCK_PEER_ID);
+        assert_eq!(info.latest_block, 0.into());
+        assert_eq!((info.inbound_peers, info.outbound_peers), (1, 0));
+        assert_eq!(info.banned_ips, 0);
+    }
+
+    #[tokio::test]
+    async fn test_admin_add_and_remove_peer() {
+        let (_adapter, rpc) = admin_rpc();
+        let multiaddr = format!("/ip4/10.0.0.1/tcp/8001/p2p/{}", PEER_ID);
+
+        assert!(rpc.add_peer(multiaddr.clone()).await.unwrap());
+        let peers = rpc.peers().await.unwrap();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].multiaddr, multiaddr);
+        assert_eq!(peers[0].direction, "outbound");
+        assert_eq!(peers[0].latency, None);
+
+        // The multiaddr without a peer id is rejected.
+        assert!(rpc
+            .add_peer("/ip4/10.0.0.2/tcp/8001".to_string())
+            .await
+            .is_err());
+
+        assert!(rpc.remove_peer(PEER_ID.to_string()).await.unwrap());
+        assert!(!rpc.remove_peer(PEER_ID.to_string()).await.unwrap());
+        assert!(rpc.peers().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_admin_ban_peer() {
+        let (adapter, rpc) = admin_rpc();
+        let multiaddr = format!("/ip4/10.0.0.1/tcp/8001/p2p/{}", PEER_ID);
+        rpc.add_peer(multiaddr.clone()).await.unwrap();
+
+        // The ban lasts one day by default.
+        let banned = rpc.ban_peer(PEER_ID.to_string(), None, None).await.unwrap();
+        assert_eq!(banned, vec!["10.0.0.1/32".to_string()]);
+        assert_eq!(adapter.bans(), vec![(
+            "10.0.0.1/32".to_string(),
+            Duration::from_secs(DEFAULT_BAN_DURATION),
+            "ban from admin rpc".to_string()
+        )]);
+        assert!(rpc.peers().await.unwrap().is_empty());
+        assert_eq!(rpc.node_info().await.unwrap().banned_ips, 1);
+
+        assert!(rpc.unban("10.0.0.1/32".to_string()).await.unwrap());
+        assert!(adapter.bans().is_empty());
+
+        rpc.add_peer(multiaddr).await.unwrap();
+        let banned = rpc
+            .ban_peer(PEER_ID.to_string(), Some(60), Some("spam".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(banned.len(), 1);
+        assert_eq!(adapter.bans(), vec![(
+            "10.0.0.1/32".to_string(),
+            Duration::from_secs(60),
+            "spam".to_string()
+        )]);
+
+        // A peer not connected has no address to ban.
+        let banned = rpc.ban_peer(PEER_ID.to_string(), None, None).await.unwrap();
+        assert!(banned.is_empty());
+    }
+}
@@ -10,7 +10,8 @@ use protocol::types::{
     Block, CkbRelatedInfo, HardforkInfoInner, Metadata, Proof, Proposal, H256, U256,
 };
 
-use crate::jsonrpc::web3_types::{BlockId, HardforkStatus};
+use crate::jsonrpc::r#impl::finalized_block_number;
+use crate::jsonrpc::web3_types::{BlockId, FinalizedBlock, HardforkStatus};
 use crate::jsonrpc::{error::RpcError, AxonRpcServer};
 
 pub struct AxonRpcImpl<Adapter> {
@@ -34,6 +35,12 @@ impl<Adapter: APIAdapter + 'static> AxonRpcServer for AxonRpcImpl<Adapter> {
                     .await
             }
             BlockId::Latest => self.adapter.get_block_by_number(Context::new(), None).await,
+            BlockId::Safe | BlockId::Finalized => {
+                let number = finalized_block_number(&*self.adapter).await?;
+                self.adapter
+                    .get_block_by_number(Context::new(), Some(number))
+                    .await
+            }
             _ => return Err(ErrorCode::InvalidRequest.into()),
         }
         .map_err(|e| RpcError::Internal(e.to_string()))?;
@@ -49,6 +56,43 @@ impl<Adapter: APIAdapter + 'static> AxonRpcServer for AxonRpcImpl<Adapter> {
         Ok(ret)
     }
 
+    async fn get_finalized_block(&self) -> RpcResult<FinalizedBlock> {
+        let proof = self
+            .adapter
+            .get_latest_proof(Context::new())
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+        let block = self
+            .adapter
+            .get_block_by_number(Context::new(), Some(proof.number))
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        if let Some(block) = block {
+            return Ok(FinalizedBlock { block, proof });
+        }
+
+        // The proof is saved right before its block, so fall back to the
+        // latest block whose proof is carried by the header of the next one.
+        let latest = self
+            .adapter
+            .get_block_by_number(Context::new(), None)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .ok_or(RpcError::CannotFindBlock)?;
+        let block = self
+            .adapter
+            .get_block_by_number(Context::new(), Some(latest.header.proof.number))
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .ok_or(RpcError::CannotFindBlock)?;
+
+        Ok(FinalizedBlock {
+            block,
+            proof: latest.header.proof,
+        })
+    }
+
     async fn get_metadata_by_number(&self, block_number: U256) -> RpcResult<Metadata> {
         let ret = self
             .adapter
@@ -0,0 +1,240 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{future::Future, sync::Arc, time::Duration};
+
+use jsonrpsee::core::RpcResult;
+
+use core_executor::is_system_contract_address_format;
+use protocol::constants::{MAX_GAS_LIMIT, MAX_GAS_PRICE};
+use protocol::traits::{APIAdapter, Context};
+use protocol::types::{Block, Proposal, TraceResult, Tracer, H256};
+use protocol::{async_trait, tokio::time::timeout, ProtocolResult};
+
+use crate::jsonrpc::r#impl::{finalized_block_number, web3::mock_header_by_call_req};
+use crate::jsonrpc::web3_types::{BlockId, BlockTraceResult, TraceConfig, Web3CallRequest};
+use crate::jsonrpc::{error::RpcError, DebugRpcServer};
+
+pub struct DebugRpcImpl<Adapter> {
+    adapter: Arc<Adapter>,
+}
+
+impl<Adapter: APIAdapter> DebugRpcImpl<Adapter> {
+    pub fn new(adapter: Arc<Adapter>) -> Self {
+        DebugRpcImpl { adapter }
+    }
+
+    async fn get_block_by_id(&self, block_id: BlockId) -> Result<Block, RpcError> {
+        match block_id {
+            BlockId::Hash(hash) => self.adapter.get_block_by_hash(Context::new(), hash).await,
+            BlockId::Safe | BlockId::Finalized => {
+                let number = finalized_block_number(&*self.adapter).await?;
+                self.adapter
+                    .get_block_by_number(Context::new(), Some(number))
+                    .await
+            }
+            id => {
+                self.adapter
+                    .get_block_by_number(Context::new(), id.into())
+                    .await
+            }
+        }
+        .map_err(|e| RpcError::Internal(e.to_string()))?
+        .ok_or(RpcError::CannotFindBlock)
+    }
+
+    /// Re-execute the first `trace_to` transactions of the block on the state
+    /// of its parent block, and trace the ones from `trace_from`.
+    async fn trace_block(
+        &self,
+        block: &Block,
+        trace_from: usize,
+        trace_to: usize,
+        config: TraceConfig,
+    ) -> Result<Vec<TraceResult>, RpcError> {
+        let (tracer, duration) = parse_trace_config(&config)?;
+
+        if block.header.number == 0 {
+            return Err(RpcError::Internal(
+                "genesis block is not traceable".to_string(),
+            ));
+        }
+
+        let parent = self
+            .adapter
+            .get_block_header_by_number(Context::new(), Some(block.header.number - 1))
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .ok_or(RpcError::CannotFindBlock)?;
+
+        let txs = self
+            .adapter
+            .get_transactions_by_hashes(
+                Context::new(),
+                block.header.number,
+                &block.tx_hashes[..trace_to],
+            )
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or(RpcError::CannotFindTransaction)?;
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        with_timeout(
+            duration,
+            &cancel,
+            self.adapter.trace_transactions(
+                Context::new(),
+                parent.state_root,
+                Proposal::new_without_state_root(&block.header),
+                txs,
+                trace_from,
+                tracer,
+                Arc::clone(&cancel),
+            ),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<Adapter: APIAdapter + 'static> DebugRpcServer for DebugRpcImpl<Adapter> {
+    async fn trace_transaction(
+        &self,
+        tx_hash: H256,
+        config: Option<TraceConfig>,
+    ) -> RpcResult<TraceResult> {
+        let receipt = self
+            .adapter
+            .get_receipt_by_tx_hash(Context::new(), tx_hash)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .ok_or(RpcError::CannotFindTransaction)?;
+
+        let block = self
+            .get_block_by_id(BlockId::Num(receipt.block_number.into()))
+            .await?;
+
+        let tx_index = receipt.tx_index as usize;
+        if block.tx_hashes.get(tx_index) != Some(&tx_hash) {
+            return Err(RpcError::CannotFindTransaction.into());
+        }
+
+        let trace = self
+            .trace_block(&block, tx_index, tx_index + 1, config.unwrap_or_default())
+            .await?
+            .pop()
+            .ok_or_else(|| RpcError::Internal("missing trace result".to_string()))?;
+
+        Ok(trace)
+    }
+
+    async fn trace_call(
+        &self,
+        req: Web3CallRequest,
+        block_id: Option<BlockId>,
+        config: Option<TraceConfig>,
+    ) -> RpcResult<TraceResult> {
+        if req.gas.unwrap_or_default() > MAX_GAS_LIMIT.into() {
+            return Err(RpcError::GasLimitIsTooLarge.into());
+        }
+
+        if req.gas_price.unwrap_or_default() > MAX_GAS_PRICE {
+            return Err(RpcError::GasPriceIsTooLarge.into());
+        }
+
+        if let Some(call_addr) = req.to {
+            if is_system_contract_address_format(&call_addr) {
+                return Err(RpcError::CallSystemContract.into());
+            }
+        }
+
+        let (tracer, duration) = parse_trace_config(&config.unwrap_or_default())?;
+        let header = self
+            .get_block_by_id(block_id.unwrap_or_default())
+            .await?
+            .header;
+        let mock_header = mock_header_by_call_req(header, &req, None);
+        let data = req
+            .data
+            .as_ref()
+            .map(|hex| hex.as_bytes().to_vec())
+            .unwrap_or_default();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let trace = with_timeout(
+            duration,
+            &cancel,
+            self.adapter.trace_call(
+                Context::new(),
+                req.from,
+                req.to,
+                req.gas_price,
+                req.gas,
+                req.value.unwrap_or_default(),
+                data,
+                mock_header.state_root,
+                Proposal::new_without_state_root(&mock_header),
+                tracer,
+                Arc::clone(&cancel),
+            ),
+        )
+        .await?;
+
+        Ok(trace)
+    }
+
+    async fn trace_block_by_number(
+        &self,
+        number: BlockId,
+        config: Option<TraceConfig>,
+    ) -> RpcResult<Vec<BlockTraceResult>> {
+        let block = self.get_block_by_id(number).await?;
+        let traces = self
+            .trace_block(&block, 0, block.tx_hashes.len(), config.unwrap_or_default())
+            .await?;
+
+        Ok(block
+            .tx_hashes
+            .into_iter()
+            .zip(traces)
+            .map(|(tx_hash, result)| BlockTraceResult { tx_hash, result })
+            .collect())
+    }
+
+    async fn trace_block_by_hash(
+        &self,
+        hash: H256,
+        config: Option<TraceConfig>,
+    ) -> RpcResult<Vec<BlockTraceResult>> {
+        self.trace_block_by_number(BlockId::Hash(hash), config)
+            .await
+    }
+}
+
+fn parse_trace_config(config: &TraceConfig) -> Result<(Tracer, Duration), RpcError> {
+    let tracer = config
+        .tracer()
+        .ok_or_else(|| RpcError::InvalidTracer(config.tracer.clone().unwrap_or_default()))?;
+    let duration = config
+        .timeout()
+        .ok_or_else(|| RpcError::InvalidTimeout(config.timeout.clone().unwrap_or_default()))?;
+
+    Ok((tracer, duration))
+}
+
+/// Wait for the trace until the deadline, the `cancel` flag is set to abort
+/// the execution running on the blocking thread pool when the deadline is
+/// reached.
+pub(crate) async fn with_timeout<T>(
+    duration: Duration,
+    cancel: &AtomicBool,
+    fut: impl Future<Output = ProtocolResult<T>>,
+) -> Result<T, RpcError> {
+    timeout(duration, fut)
+        .await
+        .map_err(|_| {
+            cancel.store(true, Ordering::Release);
+            RpcError::ExecutionTimeout
+        })?
+        .map_err(|e| RpcError::Internal(e.to_string()))
+}
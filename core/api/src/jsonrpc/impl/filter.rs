@@ -11,8 +11,9 @@ use protocol::traits::{APIAdapter, Context};
 use protocol::types::{BlockNumber, Hash, Receipt, H160, H256, U256, U64};
 use protocol::{async_trait, rand::prelude::*};
 
+use crate::jsonrpc::r#impl::{finalized_block_number, from_receipt_to_web3_log, MAX_LOG_NUM};
 use crate::jsonrpc::web3_types::{BlockId, FilterChanges, RawLoggerFilter, Web3Log};
-use crate::jsonrpc::{error::RpcError, r#impl::from_receipt_to_web3_log, Web3FilterServer};
+use crate::jsonrpc::{error::RpcError, Web3FilterServer};
 
 pub fn filter_module<Adapter>(
     adapter: Arc<Adapter>,
@@ -299,11 +300,20 @@ where
             .unwrap();
 
         let latest_number = latest_block.header.number;
+        let finalized_number = if [&filter.from_block, &filter.to_block]
+            .iter()
+            .any(|id| matches!(id, Some(BlockId::Safe | BlockId::Finalized)))
+        {
+            finalized_block_number(&*self.adapter).await?
+        } else {
+            latest_number
+        };
         let (start, end) = {
             let convert = |id: &BlockId| -> BlockNumber {
                 match id {
                     BlockId::Num(n) => n.low_u64(),
                     BlockId::Earliest => 0,
+                    BlockId::Safe | BlockId::Finalized => finalized_number,
                     _ => latest_number,
                 }
             };
@@ -328,11 +338,36 @@ where
         if start > latest_number {
             return Ok(Vec::new());
         }
-        if end.saturating_sub(start) > self.log_filter_max_block_range {
-            return Err(
-                RpcError::InvalidBlockRange(start, end, self.log_filter_max_block_range).into(),
-            );
-        }
+
+        // The block range is not limited if the log index can tell which
+        // blocks contain the matching logs, but the number of the logs is.
+        let (numbers, indexed) = match self
+            .adapter
+            .get_log_block_numbers(
+                Context::new(),
+                start,
+                end,
+                filter.address.as_deref().unwrap_or_default(),
+                topics,
+            )
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+        {
+            Some(numbers) => (numbers, true),
+            None => {
+                if end.saturating_sub(start) > self.log_filter_max_block_range {
+                    return Err(RpcError::InvalidBlockRange(
+                        start,
+                        end,
+                        self.log_filter_max_block_range,
+                    )
+                    .into());
+                }
+
+                ((start..=end).collect::<Vec<_>>(), false)
+            }
+        };
+
         let extend_logs = |logs: &mut Vec<Web3Log>, receipts: Vec<Option<Receipt>>| {
             for (index, receipt) in receipts.into_iter().flatten().enumerate() {
                 from_receipt_to_web3_log(
@@ -346,7 +381,7 @@ where
         };
 
         let mut visiter_last_block = false;
-        for n in start..=end {
+        for n in numbers {
             if n == latest_number {
                 visiter_last_block = true;
             } else {
@@ -380,6 +415,10 @@ where
             extend_logs(&mut all_logs, receipts);
         }
 
+        if indexed && all_logs.len() > MAX_LOG_NUM {
+            return Err(RpcError::TooManyLogs(MAX_LOG_NUM).into());
+        }
+
         if let Some(BlockId::Num(ref mut n)) = filter.from_block {
             *n = U64::from(end + 1)
         }
@@ -1,11 +1,20 @@
+mod admin;
 mod axon;
 mod ckb_light_client;
+mod debug;
 mod filter;
 mod node;
+mod txpool;
 mod web3;
 
+pub use admin::AdminRpcImpl;
 pub use axon::AxonRpcImpl;
 pub use ckb_light_client::CkbLightClientRpcImpl;
+pub use debug::DebugRpcImpl;
 pub use filter::filter_module;
 pub use node::NodeRpcImpl;
+pub use txpool::TxPoolRpcImpl;
 pub use web3::{from_receipt_to_web3_log, Web3RpcImpl};
+
+pub(crate) use debug::with_timeout;
+pub(crate) use web3::{finalized_block_number, MAX_LOG_NUM};
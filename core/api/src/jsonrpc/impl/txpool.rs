@@ -0,0 +1,99 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use jsonrpsee::core::RpcResult;
+
+use protocol::async_trait;
+use protocol::traits::{APIAdapter, Context};
+use protocol::types::{SignedTransaction, TransactionAction, H160};
+
+use crate::jsonrpc::web3_types::{TxPoolContent, TxPoolContentFrom, TxPoolStatus, Web3Transaction};
+use crate::jsonrpc::{error::RpcError, TxPoolRpcServer};
+
+pub struct TxPoolRpcImpl<Adapter> {
+    adapter: Arc<Adapter>,
+}
+
+impl<Adapter: APIAdapter> TxPoolRpcImpl<Adapter> {
+    pub fn new(adapter: Arc<Adapter>) -> Self {
+        TxPoolRpcImpl { adapter }
+    }
+
+    async fn grouped_content<T, F>(&self, sender: Option<H160>, f: F) -> RpcResult<TxPoolContent<T>>
+    where
+        F: Fn(SignedTransaction) -> T,
+    {
+        let content = self
+            .adapter
+            .get_mempool_content(Context::new(), sender)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        let group = |txs: Vec<SignedTransaction>| {
+            let mut ret: BTreeMap<H160, BTreeMap<u64, T>> = BTreeMap::new();
+            for tx in txs.into_iter() {
+                ret.entry(tx.sender)
+                    .or_default()
+                    .insert(tx.transaction.unsigned.nonce().low_u64(), f(tx));
+            }
+            ret
+        };
+
+        Ok(TxPoolContent {
+            pending: group(content.pending),
+            queued:  group(content.queued),
+        })
+    }
+}
+
+#[async_trait]
+impl<Adapter: APIAdapter + 'static> TxPoolRpcServer for TxPoolRpcImpl<Adapter> {
+    async fn status(&self) -> RpcResult<TxPoolStatus> {
+        let (pending, queued) = self
+            .adapter
+            .get_mempool_status(Context::new())
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        Ok(TxPoolStatus {
+            pending: (pending as u64).into(),
+            queued:  (queued as u64).into(),
+        })
+    }
+
+    async fn content(&self) -> RpcResult<TxPoolContent<Web3Transaction>> {
+        self.grouped_content(None, Web3Transaction::from).await
+    }
+
+    async fn content_from(&self, address: H160) -> RpcResult<TxPoolContentFrom<Web3Transaction>> {
+        let mut content = self
+            .grouped_content(Some(address), Web3Transaction::from)
+            .await?;
+
+        Ok(TxPoolContentFrom {
+            pending: content.pending.remove(&address).unwrap_or_default(),
+            queued:  content.queued.remove(&address).unwrap_or_default(),
+        })
+    }
+
+    async fn inspect(&self) -> RpcResult<TxPoolContent<String>> {
+        self.grouped_content(None, inspect_tx).await
+    }
+}
+
+/// Summarize a transaction as geth does, for example
+/// `0x...: 1 wei + 21000 gas × 1000000000 wei`.
+fn inspect_tx(stx: SignedTransaction) -> String {
+    let unsigned = &stx.transaction.unsigned;
+    let to = match unsigned.action() {
+        TransactionAction::Call(to) => format!("{:?}", to),
+        TransactionAction::Create => "contract creation".to_string(),
+    };
+
+    format!(
+        "{}: {} wei + {} gas × {} wei",
+        to,
+        unsigned.value(),
+        unsigned.gas_limit(),
+        unsigned.gas_price()
+    )
+}
@@ -1,79 +1,331 @@
-use std::{sync::Arc, time::Duration};
+use std::sync::{atomic::AtomicBool, Arc};
+use std::{future::Future, time::Duration};
 
-use jsonrpsee::core::RpcResult;
+use jsonrpsee::core::{RpcResult, SubscriptionResult};
+use jsonrpsee::server::{PendingSubscriptionSink, SubscriptionMessage};
+use parking_lot::Mutex;
 
 use common_apm::metrics_rpc;
-use core_executor::is_system_contract_address_format;
+use common_config_parser::types::spec::HardforkName;
+use core_executor::{
+    block_hardfork_enabled, decode_revert_msg, exit_error, is_system_contract_address_format,
+    next_block_base_fee_per_gas,
+};
 use protocol::constants::{
-    BASE_FEE_PER_GAS, MAX_FEE_HISTORY, MAX_GAS_LIMIT, MAX_GAS_PRICE, MEMPOOL_REFRESH_TIMEOUT,
+    MAX_FEE_HISTORY, MAX_GAS_LIMIT, MAX_GAS_PRICE, MEMPOOL_REFRESH_TIMEOUT,
     MIN_TRANSACTION_GAS_LIMIT,
 };
+use protocol::tokio::{
+    sync::broadcast::{error::RecvError, Receiver},
+    time::{sleep, timeout},
+};
 use protocol::traits::{APIAdapter, Context};
 use protocol::types::{
-    Block, BlockNumber, Bytes, EthAccountProof, Hash, Header, Hex, Proposal, Receipt,
-    SignedTransaction, TxResp, UnverifiedTransaction, H160, H256, U256, U64,
-};
-use protocol::{
-    async_trait, codec::ProtocolCodec, lazy::PROTOCOL_VERSION, tokio::time::sleep, ProtocolResult,
+    AccountOverride, BigEndianHash, Block, BlockNumber, Bytes, EthAccountProof, ExitError,
+    ExitReason, HardforkInfo, Hash, Header, Hex, Proposal, Receipt, SignedTransaction,
+    SimulateBlock, SimulateCall, StateOverride, TxResp, UnverifiedTransaction, H160, H256, U256,
+    U64,
 };
+use protocol::{async_trait, codec::ProtocolCodec, lazy::PROTOCOL_VERSION, ProtocolResult};
 
+use crate::jsonrpc::r#impl::with_timeout;
 use crate::jsonrpc::web3_types::{
     BlockCount, BlockId, FeeHistoryEmpty, FeeHistoryWithReward, FeeHistoryWithoutReward,
-    RichTransactionOrHash, Web3Block, Web3CallRequest, Web3FeeHistory, Web3Filter, Web3Log,
-    Web3Receipt, Web3Transaction,
+    RichTransactionOrHash, Web3AccessList, Web3Block, Web3BlockOverrides, Web3CallError,
+    Web3CallRequest, Web3FeeHistory, Web3Filter, Web3Log, Web3Receipt, Web3SimulatePayload,
+    Web3SimulatedBlock, Web3SimulatedCall, Web3Transaction,
 };
 use crate::jsonrpc::{error::RpcError, Web3RpcServer};
 use crate::APIError;
 
 pub(crate) const MAX_LOG_NUM: usize = 10000;
-
-pub struct Web3RpcImpl<Adapter> {
+/// The gas price oracle samples the lowest effective priority fees of the
+/// transactions in the recent blocks, and suggests the percentile of them.
+const GAS_PRICE_ORACLE_BLOCKS: u64 = 20;
+const GAS_PRICE_ORACLE_SAMPLES_PER_BLOCK: usize = 3;
+const GAS_PRICE_ORACLE_PERCENTILE: usize = 60;
+/// The limits of the blocks and the calls in a simulation.
+const MAX_SIMULATE_BLOCKS: usize = 256;
+const MAX_SIMULATE_CALLS: usize = 1000;
+/// The error codes of the simulated calls, which are the same as geth.
+const SIMULATE_REVERTED_CODE: i32 = 3;
+const SIMULATE_VM_ERROR_CODE: i32 = -32015;
+/// The time limit of a simulation, which is the same as the default EVM
+/// timeout of geth.
+const SIMULATE_TIMEOUT: Duration = Duration::from_secs(5);
+/// The default and the maximum time in milliseconds to wait for a transaction
+/// to be committed in `eth_sendRawTransactionSync`.
+const SEND_RAW_TX_SYNC_DEFAULT_TIMEOUT: u64 = 10_000;
+const SEND_RAW_TX_SYNC_MAX_TIMEOUT: u64 = 60_000;
+
+pub struct Web3RpcImpl<Adapter: ?Sized> {
     adapter:                    Arc<Adapter>,
     max_gas_cap:                U64,
     log_filter_max_block_range: u64,
+    /// The suggested priority fee per gas with the hash of the head block it
+    /// is sampled at.
+    gas_price_oracle_cache:     Mutex<Option<(Hash, U64)>>,
+    /// The cumulative gas used of the receipts with the hash of the block they
+    /// are in.
+    cumulative_gas_cache:       Mutex<Option<(Hash, Arc<Vec<U64>>)>>,
 }
 
-impl<Adapter: APIAdapter> Web3RpcImpl<Adapter> {
+impl<Adapter: APIAdapter + ?Sized> Web3RpcImpl<Adapter> {
     pub fn new(adapter: Arc<Adapter>, max_gas_cap: u64, log_filter_max_block_range: u64) -> Self {
         Self {
             adapter,
             max_gas_cap: max_gas_cap.into(),
             log_filter_max_block_range,
+            gas_price_oracle_cache: Mutex::new(None),
+            cumulative_gas_cache: Mutex::new(None),
         }
     }
 
+    /// Returns the gas used by the receipt and all the receipts before it in
+    /// the same block. The cumulative gas used of a block is computed once and
+    /// cached for its other receipts.
+    async fn cumulative_gas_used(&self, receipt: &Receipt) -> Result<U64, RpcError> {
+        let cached = self
+            .cumulative_gas_cache
+            .lock()
+            .as_ref()
+            .filter(|(hash, _)| *hash == receipt.block_hash)
+            .map(|(_, gas)| Arc::clone(gas));
+        let cumulative_gas = match cached {
+            Some(gas) => gas,
+            None => {
+                let ctx = Context::new();
+                let block = self
+                    .adapter
+                    .get_block_by_number(ctx.clone(), Some(receipt.block_number))
+                    .await
+                    .map_err(|e| RpcError::Internal(e.to_string()))?
+                    .ok_or(RpcError::CannotFindBlock)?;
+                let receipts = self
+                    .adapter
+                    .get_receipts_by_hashes(ctx, receipt.block_number, &block.tx_hashes)
+                    .await
+                    .map_err(|e| RpcError::Internal(e.to_string()))?
+                    .into_iter()
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(RpcError::CannotFindTransaction)?;
+                self.cache_cumulative_gas_used(block.hash(), &receipts)
+            }
+        };
+
+        Ok(cumulative_gas
+            .get(receipt.tx_index as usize)
+            .copied()
+            .unwrap_or(receipt.used_gas))
+    }
+
+    fn cache_cumulative_gas_used(&self, block_hash: Hash, receipts: &[Receipt]) -> Arc<Vec<U64>> {
+        let gas = receipts
+            .iter()
+            .scan(U64::zero(), |cumulative, receipt| {
+                *cumulative = cumulative.saturating_add(receipt.used_gas);
+                Some(*cumulative)
+            })
+            .collect::<Vec<_>>();
+        let gas = Arc::new(gas);
+        *self.cumulative_gas_cache.lock() = Some((block_hash, Arc::clone(&gas)));
+        gas
+    }
+
+    /// Resolves the block id to a block number, `None` stands for the latest
+    /// block. The pending block is resolved to the latest one as well, so the
+    /// callers serving it should handle it before.
     async fn get_block_number_by_id(
         &self,
         block_id: Option<BlockId>,
     ) -> Result<Option<BlockNumber>, RpcError> {
-        match block_id {
-            Some(BlockId::Hash(ref hash)) => Ok(self
+        match block_id.unwrap_or_default() {
+            BlockId::Num(num) => Ok(Some(num.low_u64())),
+            BlockId::Earliest => Ok(Some(0)),
+            BlockId::Hash(hash) => self
+                .adapter
+                .get_block_number_by_hash(Context::new(), hash)
+                .await
+                .map_err(|e| RpcError::Internal(e.to_string())),
+            BlockId::Safe | BlockId::Finalized => {
+                finalized_block_number(&*self.adapter).await.map(Some)
+            }
+            BlockId::Latest | BlockId::Pending => Ok(None),
+        }
+    }
+
+    fn decode_raw_transaction(&self, tx: &Hex) -> Result<SignedTransaction, RpcError> {
+        let utx = UnverifiedTransaction::decode(&tx.as_bytes())
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        let gas_price = utx.unsigned.gas_price();
+
+        if gas_price == U64::zero() {
+            return Err(RpcError::GasPriceIsZero);
+        }
+
+        if gas_price > MAX_GAS_PRICE {
+            return Err(RpcError::GasPriceIsTooLarge);
+        }
+
+        let gas_limit = *utx.unsigned.gas_limit();
+
+        if gas_limit < MIN_TRANSACTION_GAS_LIMIT.into() {
+            return Err(RpcError::GasLimitIsTooLow);
+        }
+
+        if gas_limit > self.max_gas_cap {
+            return Err(RpcError::GasLimitIsTooLarge);
+        }
+
+        utx.check_hash()
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        SignedTransaction::from_unverified(utx).map_err(|e| RpcError::Internal(e.to_string()))
+    }
+
+    async fn submit_transaction(&self, stx: SignedTransaction) -> Result<(), RpcError> {
+        self.adapter
+            .insert_signed_txs(Context::new(), stx)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))
+    }
+
+    /// Waits until the block containing the transaction is committed, then
+    /// returns the receipt of the transaction. Blocks are final once they are
+    /// committed, so the receipt never changes afterwards.
+    async fn wait_for_commit(
+        &self,
+        stx: SignedTransaction,
+        commits: Receiver<Block>,
+        duration: Duration,
+    ) -> Result<Web3Receipt, RpcError> {
+        let hash = stx.transaction.hash;
+        timeout(duration, self.wait_for_block(hash, commits))
+            .await
+            .map_err(|_| RpcError::TransactionCommitTimeout(hash))??;
+
+        // The receipts are saved before the block, so it must be found here.
+        let receipt = self
+            .committed_receipt(hash)
+            .await?
+            .ok_or(RpcError::CannotFindTransaction)?;
+
+        Ok(Web3Receipt::new(receipt, stx))
+    }
+
+    async fn wait_for_block(
+        &self,
+        hash: H256,
+        mut commits: Receiver<Block>,
+    ) -> Result<(), RpcError> {
+        loop {
+            match commits.recv().await {
+                Ok(block) if block.tx_hashes.contains(&hash) => return Ok(()),
+                Ok(_) => continue,
+                // Some commits are missed, so look up the receipt instead.
+                Err(RecvError::Lagged(_)) => {
+                    if self.committed_receipt(hash).await?.is_some() {
+                        return Ok(());
+                    }
+                }
+                Err(RecvError::Closed) => {
+                    return Err(RpcError::Internal("Commit notifier is closed".to_string()))
+                }
+            }
+        }
+    }
+
+    async fn committed_receipt(&self, hash: H256) -> Result<Option<Receipt>, RpcError> {
+        self.adapter
+            .get_receipt_by_tx_hash(Context::new(), hash)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))
+    }
+
+    /// Returns the header to execute a call on and the state overrides of the
+    /// call. The pending state is the latest one with the changes of the
+    /// pending transactions applied as overrides.
+    async fn call_context(
+        &self,
+        block_id: Option<BlockId>,
+        state_override: Option<StateOverride>,
+    ) -> Result<(Header, Option<StateOverride>), RpcError> {
+        if let Some(BlockId::Pending) = block_id {
+            let pending = self
                 .adapter
-                .get_block_number_by_hash(Context::new(), *hash)
+                .get_pending_block(Context::new())
                 .await
-                .map_err(|e| RpcError::Internal(e.to_string()))?),
-            _ => Ok(block_id.unwrap_or_default().into()),
+                .map_err(|e| RpcError::Internal(e.to_string()))?;
+            let mut pending_override = pending.state_override.clone();
+            merge_state_override(&mut pending_override, state_override.unwrap_or_default());
+            return Ok((pending.block.header.clone(), Some(pending_override)));
         }
+
+        let number = self.get_block_number_by_id(block_id).await?;
+        let header = self
+            .adapter
+            .get_block_header_by_number(Context::new(), number)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .ok_or(RpcError::CannotFindBlock)?;
+        Ok((header, state_override))
     }
 
-    async fn call_evm(
+    /// Returns the number of the block to read the state of an account from,
+    /// and the override of the account in the pending state.
+    async fn account_context(
+        &self,
+        address: H160,
+        block_id: Option<BlockId>,
+    ) -> Result<(Option<BlockNumber>, Option<AccountOverride>), RpcError> {
+        if let Some(BlockId::Pending) = block_id {
+            let pending = self
+                .adapter
+                .get_pending_block(Context::new())
+                .await
+                .map_err(|e| RpcError::Internal(e.to_string()))?;
+            return Ok((
+                Some(pending.block.header.number - 1),
+                pending.state_override.get(&address).cloned(),
+            ));
+        }
+
+        Ok((self.get_block_number_by_id(block_id).await?, None))
+    }
+
+    pub(crate) async fn call_evm(
         &self,
         req: Web3CallRequest,
         data: Bytes,
         number: Option<u64>,
-        estimate: bool,
+        state_override: Option<&StateOverride>,
+        block_overrides: Option<&Web3BlockOverrides>,
     ) -> ProtocolResult<TxResp> {
-        if req.from.is_none() && req.to.is_none() {
-            return Err(APIError::RequestPayload("from and to are both None".to_string()).into());
-        }
-
         let header = self
             .adapter
             .get_block_header_by_number(Context::new(), number)
             .await?
             .ok_or_else(|| APIError::Storage(format!("Cannot get {:?} header", number)))?;
 
-        let mock_header = mock_header_by_call_req(header, &req);
+        let mock_header = mock_header_by_call_req(header, &req, block_overrides);
+
+        self.call_evm_with_header(&req, data, req.gas, false, &mock_header, state_override)
+            .await
+    }
+
+    async fn call_evm_with_header(
+        &self,
+        req: &Web3CallRequest,
+        data: Bytes,
+        gas_limit: Option<U64>,
+        estimate: bool,
+        mock_header: &Header,
+        state_override: Option<&StateOverride>,
+    ) -> ProtocolResult<TxResp> {
+        if req.from.is_none() && req.to.is_none() {
+            return Err(APIError::RequestPayload("from and to are both None".to_string()).into());
+        }
 
         self.adapter
             .evm_call(
@@ -81,16 +333,53 @@ impl<Adapter: APIAdapter> Web3RpcImpl<Adapter> {
                 req.from,
                 req.to,
                 req.gas_price,
-                req.gas,
+                gas_limit,
                 req.value.unwrap_or_default(),
                 data.to_vec(),
                 estimate,
+                state_override.cloned(),
                 mock_header.state_root,
-                Proposal::new_without_state_root(&mock_header),
+                Proposal::new_without_state_root(mock_header),
             )
             .await
     }
 
+    /// Estimate the gas limit of the call, the upper bound is limited by the
+    /// `max_gas_cap` and the block gas limit.
+    async fn estimate_gas_limit(
+        &self,
+        req: Web3CallRequest,
+        data: Bytes,
+        header: Header,
+        state_override: Option<&StateOverride>,
+        block_overrides: Option<&Web3BlockOverrides>,
+    ) -> Result<u64, RpcError> {
+        let block_gas_limit = block_overrides
+            .and_then(|b| b.gas_limit)
+            .unwrap_or(header.gas_limit)
+            .low_u64();
+        let mock_header = mock_header_by_call_req(header, &req, block_overrides);
+
+        let hi = req
+            .gas
+            .map(|gas| gas.low_u64())
+            .unwrap_or(block_gas_limit)
+            .min(block_gas_limit)
+            .min(self.max_gas_cap.low_u64());
+
+        binary_search_gas(hi, |gas| {
+            self.call_evm_with_header(
+                &req,
+                data.clone(),
+                Some(gas.into()),
+                false,
+                &mock_header,
+                state_override,
+            )
+        })
+        .await
+    }
+
     async fn calculate_rewards(
         &self,
         block_number: u64,
@@ -132,6 +421,76 @@ impl<Adapter: APIAdapter> Web3RpcImpl<Adapter> {
         Ok(())
     }
 
+    /// Suggest a priority fee per gas like the gas price oracle of geth. The
+    /// blocks without transactions are skipped, and zero is suggested if there
+    /// is no transaction in the recent blocks. The suggestion is cached until
+    /// the head block changes.
+    async fn suggest_priority_fee_per_gas(&self, latest: &Header) -> Result<U64, RpcError> {
+        let head = latest.hash();
+        if let Some((hash, fee)) = *self.gas_price_oracle_cache.lock() {
+            if hash == head {
+                return Ok(fee);
+            }
+        }
+
+        let oldest = latest
+            .number
+            .saturating_sub(GAS_PRICE_ORACLE_BLOCKS.saturating_sub(1));
+        let mut samples = Vec::new();
+
+        for number in oldest..=latest.number {
+            let block = match self
+                .adapter
+                .get_block_by_number(Context::new(), Some(number))
+                .await
+            {
+                Ok(Some(block)) if !block.tx_hashes.is_empty() => block,
+                _ => continue,
+            };
+
+            let mut fees = self
+                .adapter
+                .get_transactions_by_hashes(Context::new(), number, &block.tx_hashes)
+                .await
+                .map_err(|e| RpcError::Internal(e.to_string()))?
+                .iter()
+                .flatten()
+                .map(|tx| {
+                    tx.transaction
+                        .unsigned
+                        .effective_priority_fee_per_gas(block.header.base_fee_per_gas)
+                })
+                .collect::<Vec<_>>();
+            fees.sort_unstable();
+            samples.extend(fees.into_iter().take(GAS_PRICE_ORACLE_SAMPLES_PER_BLOCK));
+        }
+
+        let fee = if samples.is_empty() {
+            U64::zero()
+        } else {
+            samples.sort_unstable();
+            samples[(samples.len() - 1) * GAS_PRICE_ORACLE_PERCENTILE / 100]
+        };
+
+        *self.gas_price_oracle_cache.lock() = Some((head, fee));
+        Ok(fee)
+    }
+
+    async fn hardfork_info(&self) -> Result<HardforkInfo, RpcError> {
+        self.adapter
+            .hardfork_info(Context::new())
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))
+    }
+
+    async fn latest_header(&self) -> Result<Header, RpcError> {
+        self.adapter
+            .get_block_header_by_number(Context::new(), None)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .ok_or(RpcError::CannotGetLatestBlock)
+    }
+
     async fn inner_fee_history(
         &self,
         height: Option<u64>,
@@ -153,6 +512,7 @@ impl<Adapter: APIAdapter> Web3RpcImpl<Adapter> {
         let mut bash_fee_per_gases: Vec<U64> = Vec::new();
         let mut gas_used_ratios: Vec<f64> = Vec::new();
         let mut reward: Vec<Vec<U64>> = Vec::new();
+        let hardforks = self.hardfork_info().await?;
 
         for i in oldest_block_number..=latest_block_number {
             let block = match self
@@ -167,7 +527,7 @@ impl<Adapter: APIAdapter> Web3RpcImpl<Adapter> {
             let gas_used_ratio = calculate_gas_used_ratio(&block);
             gas_used_ratios.push(gas_used_ratio);
             bash_fee_per_gases.push(block.header.base_fee_per_gas);
-            bash_fee_per_gases.push(next_block_base_fee_per_gas());
+            bash_fee_per_gases.push(next_block_base_fee_per_gas(&block.header, &hardforks));
 
             if let Some(reward_percentiles) = reward_percentiles.clone() {
                 let txs = block.tx_hashes;
@@ -192,49 +552,68 @@ impl<Adapter: APIAdapter> Web3RpcImpl<Adapter> {
 }
 
 #[async_trait]
-impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
+impl<Adapter: APIAdapter + ?Sized + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
     #[metrics_rpc("eth_sendRawTransaction")]
     async fn send_raw_transaction(&self, tx: Hex) -> RpcResult<H256> {
-        let utx = UnverifiedTransaction::decode(&tx.as_bytes())
-            .map_err(|e| RpcError::Internal(e.to_string()))?;
+        let stx = self.decode_raw_transaction(&tx)?;
+        let hash = stx.transaction.hash;
 
-        let gas_price = utx.unsigned.gas_price();
+        self.submit_transaction(stx).await?;
 
-        if gas_price == U64::zero() {
-            return Err(RpcError::GasPriceIsZero.into());
-        }
+        // TODO `eth_getTransactionCount(..., "pending")` should be synchronous with
+        // `eth_sendRawTransaction`. Temporary solution for axonweb3/axon#1544.
+        sleep(Duration::from_millis(MEMPOOL_REFRESH_TIMEOUT)).await;
 
-        if gas_price > MAX_GAS_PRICE {
-            return Err(RpcError::GasPriceIsTooLarge.into());
-        }
+        Ok(hash)
+    }
 
-        let gas_limit = *utx.unsigned.gas_limit();
+    #[metrics_rpc("eth_sendRawTransactionSync")]
+    async fn send_raw_transaction_sync(
+        &self,
+        tx: Hex,
+        timeout_ms: Option<u64>,
+    ) -> RpcResult<Web3Receipt> {
+        let duration = commit_timeout(timeout_ms)?;
+        let stx = self.decode_raw_transaction(&tx)?;
+        // Subscribe before the insertion so that the commit can not be missed.
+        let commits = self.adapter.subscribe_committed_blocks(Context::new());
+        self.submit_transaction(stx.clone()).await?;
+
+        let receipt = self.wait_for_commit(stx, commits, duration).await?;
+
+        Ok(receipt)
+    }
 
-        if gas_limit < MIN_TRANSACTION_GAS_LIMIT.into() {
-            return Err(RpcError::GasLimitIsTooLow.into());
+    async fn send_raw_transaction_subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        tx: Hex,
+        timeout_ms: Option<u64>,
+    ) -> SubscriptionResult {
+        let checked = commit_timeout(timeout_ms)
+            .and_then(|duration| Ok((duration, self.decode_raw_transaction(&tx)?)));
+        let (duration, stx) = match checked {
+            Ok(checked) => checked,
+            Err(e) => {
+                pending.reject(e).await;
+                return Ok(());
+            }
+        };
+        let commits = self.adapter.subscribe_committed_blocks(Context::new());
+        if let Err(e) = self.submit_transaction(stx.clone()).await {
+            pending.reject(e).await;
+            return Ok(());
         }
 
-        if gas_limit > self.max_gas_cap {
-            return Err(RpcError::GasLimitIsTooLarge.into());
+        let sink = pending.accept().await?;
+        match self.wait_for_commit(stx, commits, duration).await {
+            Ok(receipt) => sink.send(SubscriptionMessage::from_json(&receipt)?).await?,
+            // Close the subscription with an error notification, so the
+            // subscriber is not left waiting for a receipt never sent.
+            Err(e) => return Err(e.to_string().into()),
         }
 
-        utx.check_hash()
-            .map_err(|e| RpcError::Internal(e.to_string()))?;
-
-        let stx = SignedTransaction::from_unverified(utx)
-            .map_err(|e| RpcError::Internal(e.to_string()))?;
-        let hash = stx.transaction.hash;
-
-        self.adapter
-            .insert_signed_txs(Context::new(), stx)
-            .await
-            .map_err(|e| RpcError::Internal(e.to_string()))?;
-
-        // TODO `eth_getTransactionCount(..., "pending")` should be synchronous with
-        // `eth_sendRawTransaction`. Temporary solution for axonweb3/axon#1544.
-        sleep(Duration::from_millis(MEMPOOL_REFRESH_TIMEOUT)).await;
-
-        Ok(hash)
+        Ok(())
     }
 
     #[metrics_rpc("eth_getTransactionByHash")]
@@ -267,11 +646,27 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
         number: BlockId,
         show_rich_tx: bool,
     ) -> RpcResult<Option<Web3Block>> {
-        let block = self
-            .adapter
-            .get_block_by_number(Context::new(), number.into())
-            .await
-            .map_err(|e| RpcError::Internal(e.to_string()))?;
+        // The transactions of the pending block are not stored yet.
+        let (block, pending) = match number {
+            BlockId::Pending => {
+                let pending = self
+                    .adapter
+                    .get_pending_block(Context::new())
+                    .await
+                    .map_err(|e| RpcError::Internal(e.to_string()))?;
+                (Some(pending.block.clone()), Some(pending))
+            }
+            id => (
+                self.adapter
+                    .get_block_by_number(
+                        Context::new(),
+                        self.get_block_number_by_id(Some(id)).await?,
+                    )
+                    .await
+                    .map_err(|e| RpcError::Internal(e.to_string()))?,
+                None,
+            ),
+        };
 
         match block {
             Some(b) => {
@@ -282,12 +677,15 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
                 if show_rich_tx {
                     let mut txs = Vec::with_capacity(capacity);
                     for (idx, tx) in ret.transactions.iter().enumerate() {
-                        let tx = self
-                            .adapter
-                            .get_transaction_by_hash(Context::new(), tx.get_hash())
-                            .await
-                            .map_err(|e| RpcError::Internal(e.to_string()))?
-                            .unwrap();
+                        let tx = match pending.as_ref() {
+                            Some(pending) => pending.txs[idx].clone(),
+                            None => self
+                                .adapter
+                                .get_transaction_by_hash(Context::new(), tx.get_hash())
+                                .await
+                                .map_err(|e| RpcError::Internal(e.to_string()))?
+                                .unwrap(),
+                        };
 
                         txs.push(RichTransactionOrHash::Rich(
                             Web3Transaction::from(tx)
@@ -364,32 +762,20 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
                     .get_pending_tx_count(Context::new(), address)
                     .await
                     .map_err(|e| RpcError::Internal(e.to_string()))?;
-                Ok(self
-                    .adapter
+                self.adapter
                     .get_account(Context::new(), address, block_number)
                     .await
                     .map(|account| account.nonce + pending_tx_count)
-                    .unwrap_or_default())
+                    .map_err(|e| RpcError::Internal(e.to_string()).into())
             }
-            BlockId::Hash(ref hash) => {
-                let number = self
-                    .adapter
-                    .get_block_number_by_hash(Context::new(), *hash)
-                    .await
-                    .map_err(|e| RpcError::Internal(e.to_string()))?;
-                Ok(self
-                    .adapter
+            b => {
+                let number = self.get_block_number_by_id(Some(b)).await?;
+                self.adapter
                     .get_account(Context::new(), address, number)
                     .await
                     .map(|account| account.nonce)
-                    .unwrap_or_default())
+                    .map_err(|e| RpcError::Internal(e.to_string()).into())
             }
-            b => Ok(self
-                .adapter
-                .get_account(Context::new(), address, b.into())
-                .await
-                .map(|account| account.nonce)
-                .unwrap_or_default()),
         }
     }
 
@@ -405,17 +791,26 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
 
     #[metrics_rpc("eth_getBalance")]
     async fn get_balance(&self, address: H160, block_id: Option<BlockId>) -> RpcResult<U256> {
-        let number = self.get_block_number_by_id(block_id).await?;
+        let (number, account_override) = self.account_context(address, block_id).await?;
+        if let Some(balance) = account_override.and_then(|account| account.balance) {
+            return Ok(balance);
+        }
 
-        Ok(self
-            .adapter
+        self.adapter
             .get_account(Context::new(), address, number)
             .await
-            .map_or(U256::zero(), |account| account.balance))
+            .map(|account| account.balance)
+            .map_err(|e| RpcError::Internal(e.to_string()).into())
     }
 
     #[metrics_rpc("eth_call")]
-    async fn call(&self, req: Web3CallRequest, block_id: Option<BlockId>) -> RpcResult<Hex> {
+    async fn call(
+        &self,
+        req: Web3CallRequest,
+        block_id: Option<BlockId>,
+        state_override: Option<StateOverride>,
+        block_overrides: Option<Web3BlockOverrides>,
+    ) -> RpcResult<Hex> {
         if req.gas.unwrap_or_default() > MAX_GAS_LIMIT.into() {
             return Err(RpcError::GasLimitIsTooLarge.into());
         }
@@ -430,7 +825,10 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
             }
         }
 
-        let number = self.get_block_number_by_id(block_id).await?;
+        check_state_override(state_override.as_ref())?;
+
+        let (header, state_override) = self.call_context(block_id, state_override).await?;
+        let mock_header = mock_header_by_call_req(header, &req, block_overrides.as_ref());
 
         let data_bytes = req
             .data
@@ -438,7 +836,14 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
             .map(|hex| hex.as_bytes())
             .unwrap_or_default();
         let resp = self
-            .call_evm(req, data_bytes, number, false)
+            .call_evm_with_header(
+                &req,
+                data_bytes,
+                req.gas,
+                false,
+                &mock_header,
+                state_override.as_ref(),
+            )
             .await
             .map_err(|e| RpcError::Internal(e.to_string()))?;
 
@@ -451,7 +856,13 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
     }
 
     #[metrics_rpc("eth_estimateGas")]
-    async fn estimate_gas(&self, req: Web3CallRequest, number: Option<BlockId>) -> RpcResult<U256> {
+    async fn estimate_gas(
+        &self,
+        req: Web3CallRequest,
+        number: Option<BlockId>,
+        state_override: Option<StateOverride>,
+        block_overrides: Option<Web3BlockOverrides>,
+    ) -> RpcResult<U256> {
         if let Some(gas_limit) = req.gas.as_ref() {
             if gas_limit < &(MIN_TRANSACTION_GAS_LIMIT.into()) {
                 return Err(RpcError::GasLimitIsTooLow.into());
@@ -472,30 +883,206 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
             }
         }
 
-        let num = match number {
-            Some(BlockId::Num(n)) => Some(n.low_u64()),
-            _ => None,
-        };
+        check_state_override(state_override.as_ref())?;
+
+        let (header, state_override) = self.call_context(number, state_override).await?;
         let data_bytes = req
             .data
             .as_ref()
             .map(|hex| hex.as_bytes())
             .unwrap_or_default();
-        let resp = self
-            .call_evm(req, data_bytes, num, true)
+        let gas = self
+            .estimate_gas_limit(
+                req,
+                data_bytes,
+                header,
+                state_override.as_ref(),
+                block_overrides.as_ref(),
+            )
+            .await?;
+
+        Ok(gas.into())
+    }
+
+    #[metrics_rpc("eth_simulateV1")]
+    async fn simulate_v1(
+        &self,
+        payload: Web3SimulatePayload,
+        number: Option<BlockId>,
+    ) -> RpcResult<Vec<Web3SimulatedBlock>> {
+        let block_state_calls = payload.block_state_calls;
+        if block_state_calls.len() > MAX_SIMULATE_BLOCKS {
+            return Err(RpcError::InvalidSimulation(format!(
+                "too many blocks, the limit is {}",
+                MAX_SIMULATE_BLOCKS
+            ))
+            .into());
+        }
+
+        if block_state_calls
+            .iter()
+            .map(|b| b.calls.len())
+            .sum::<usize>()
+            > MAX_SIMULATE_CALLS
+        {
+            return Err(RpcError::InvalidSimulation(format!(
+                "too many calls, the limit is {}",
+                MAX_SIMULATE_CALLS
+            ))
+            .into());
+        }
+
+        let number = self.get_block_number_by_id(number).await?;
+        let base = self
+            .adapter
+            .get_block_header_by_number(Context::new(), number)
             .await
-            .map_err(|e| RpcError::Internal(e.to_string()))?;
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .ok_or(RpcError::CannotFindBlock)?;
 
-        if resp.exit_reason.is_succeed() {
-            return Ok(resp.gas_used.into());
+        // The simulated blocks are built on top of the base block one by one.
+        let mut headers: Vec<Header> = Vec::with_capacity(block_state_calls.len());
+        let mut blocks = Vec::with_capacity(block_state_calls.len());
+        for block_state_call in block_state_calls.into_iter() {
+            check_state_override(block_state_call.state_overrides.as_ref())?;
+
+            let parent = headers.last().unwrap_or(&base);
+            let mut header = parent.clone();
+            header.prev_hash = parent.hash();
+            header.number = parent.number + 1;
+            header.timestamp = parent.timestamp + 1;
+            if let Some(overrides) = block_state_call.block_overrides.as_ref() {
+                apply_block_overrides(&mut header, overrides);
+            }
+
+            if header.number <= parent.number || header.timestamp <= parent.timestamp {
+                return Err(RpcError::InvalidSimulation(format!(
+                    "block {} is not after its parent",
+                    header.number
+                ))
+                .into());
+            }
+
+            let mut calls = Vec::with_capacity(block_state_call.calls.len());
+            for req in block_state_call.calls.into_iter() {
+                if req.to.as_ref().map(is_system_contract_address_format) == Some(true) {
+                    return Err(RpcError::CallSystemContract.into());
+                }
+
+                calls.push(SimulateCall {
+                    from:      req.from.unwrap_or_default(),
+                    to:        req.to,
+                    gas_limit: req
+                        .gas
+                        .unwrap_or(header.gas_limit)
+                        .min(self.max_gas_cap)
+                        .low_u64(),
+                    gas_price: req.gas_price.unwrap_or_else(U64::one),
+                    value:     req.value.unwrap_or_default(),
+                    data:      req
+                        .data
+                        .map(|hex| hex.as_bytes().to_vec())
+                        .unwrap_or_default(),
+                });
+            }
+
+            blocks.push(SimulateBlock {
+                proposal: Proposal::new_without_state_root(&header),
+                state_override: block_state_call.state_overrides,
+                calls,
+            });
+            headers.push(header);
         }
 
-        Err(RpcError::Evm(resp).into())
+        let cancel = Arc::new(AtomicBool::new(false));
+        let resps = with_timeout(
+            SIMULATE_TIMEOUT,
+            &cancel,
+            self.adapter.simulate_blocks(
+                Context::new(),
+                base.state_root,
+                blocks,
+                Arc::clone(&cancel),
+            ),
+        )
+        .await?;
+
+        Ok(headers
+            .into_iter()
+            .zip(resps)
+            .map(|(header, resps)| simulated_block(header, resps))
+            .collect())
+    }
+
+    #[metrics_rpc("eth_createAccessList")]
+    async fn create_access_list(
+        &self,
+        req: Web3CallRequest,
+        number: Option<BlockId>,
+    ) -> RpcResult<Web3AccessList> {
+        if req.gas.unwrap_or_default() > MAX_GAS_LIMIT.into() {
+            return Err(RpcError::GasLimitIsTooLarge.into());
+        }
+
+        if req.gas_price.unwrap_or_default() > MAX_GAS_PRICE {
+            return Err(RpcError::GasPriceIsTooLarge.into());
+        }
+
+        if let Some(call_addr) = req.to {
+            if is_system_contract_address_format(&call_addr) {
+                return Err(RpcError::CallSystemContract.into());
+            }
+        }
+
+        let number = self.get_block_number_by_id(number).await?;
+        let header = self
+            .adapter
+            .get_block_header_by_number(Context::new(), number)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+            .ok_or(RpcError::CannotFindBlock)?;
+        let mock_header = mock_header_by_call_req(header, &req, None);
+        let data = req
+            .data
+            .as_ref()
+            .map(|hex| hex.as_bytes().to_vec())
+            .unwrap_or_default();
+
+        let (access_list, resp) = self
+            .adapter
+            .create_access_list(
+                Context::new(),
+                req.from,
+                req.to,
+                req.gas_price,
+                req.gas,
+                req.value.unwrap_or_default(),
+                data,
+                req.access_list.clone().unwrap_or_default(),
+                mock_header.state_root,
+                Proposal::new_without_state_root(&mock_header),
+            )
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        let error = match resp.exit_reason {
+            ExitReason::Revert(_) => Some(decode_revert_msg(&resp.ret)),
+            ref reason => exit_error(reason),
+        };
+
+        Ok(Web3AccessList {
+            access_list,
+            gas_used: resp.gas_used.into(),
+            error,
+        })
     }
 
     #[metrics_rpc("eth_getCode")]
     async fn get_code(&self, address: H160, block_id: Option<BlockId>) -> RpcResult<Hex> {
-        let number = self.get_block_number_by_id(block_id).await?;
+        let (number, account_override) = self.account_context(address, block_id).await?;
+        if let Some(code) = account_override.and_then(|account| account.code) {
+            return Ok(code);
+        }
 
         let account = self
             .adapter
@@ -519,7 +1106,10 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
     async fn get_block_transaction_count_by_number(&self, number: BlockId) -> RpcResult<U256> {
         let block = self
             .adapter
-            .get_block_by_number(Context::new(), number.into())
+            .get_block_by_number(
+                Context::new(),
+                self.get_block_number_by_id(Some(number)).await?,
+            )
             .await
             .map_err(|e| RpcError::Internal(e.to_string()))?;
         let count = match block {
@@ -545,13 +1135,74 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
                 .await
                 .map_err(|e| RpcError::Internal(e.to_string()))?
             {
-                return Ok(Some(Web3Receipt::new(receipt, stx)));
+                let cumulative_gas_used = self.cumulative_gas_used(&receipt).await?;
+                let mut web3_receipt = Web3Receipt::new(receipt, stx);
+                web3_receipt.cumulative_gas_used = cumulative_gas_used;
+                return Ok(Some(web3_receipt));
             }
         }
 
         Ok(None)
     }
 
+    #[metrics_rpc("eth_getBlockReceipts")]
+    async fn get_block_receipts(&self, block_id: BlockId) -> RpcResult<Option<Vec<Web3Receipt>>> {
+        let ctx = Context::new();
+        let number = match block_id {
+            BlockId::Hash(hash) => match self
+                .adapter
+                .get_block_number_by_hash(ctx.clone(), hash)
+                .await
+                .map_err(|e| RpcError::Internal(e.to_string()))?
+            {
+                Some(number) => Some(number),
+                None => return Ok(None),
+            },
+            id => self.get_block_number_by_id(Some(id)).await?,
+        };
+
+        let block = match self
+            .adapter
+            .get_block_by_number(ctx.clone(), number)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?
+        {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+        let receipts = self
+            .adapter
+            .get_receipts_by_hashes(ctx.clone(), block.header.number, &block.tx_hashes)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+        let stxs = self
+            .adapter
+            .get_transactions_by_hashes(ctx, block.header.number, &block.tx_hashes)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+
+        let receipts = receipts
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or(RpcError::CannotFindTransaction)?;
+        let cumulative_gas = self.cache_cumulative_gas_used(block.hash(), &receipts);
+
+        let mut ret = Vec::with_capacity(receipts.len());
+        for ((receipt, stx), gas) in receipts
+            .into_iter()
+            .zip(stxs.into_iter())
+            .zip(cumulative_gas.iter())
+        {
+            let stx = stx.ok_or(RpcError::CannotFindTransaction)?;
+            let mut web3_receipt = Web3Receipt::new(receipt, stx);
+            web3_receipt.cumulative_gas_used = *gas;
+            ret.push(web3_receipt);
+        }
+
+        Ok(Some(ret))
+    }
+
     #[metrics_rpc("net_peerCount")]
     async fn peer_count(&self) -> RpcResult<U256> {
         self.adapter
@@ -562,7 +1213,29 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
 
     #[metrics_rpc("eth_gasPrice")]
     async fn gas_price(&self) -> RpcResult<U256> {
-        Ok(U256::from(8u64))
+        let latest = self.latest_header().await?;
+        let hardforks = self.hardfork_info().await?;
+        if !block_hardfork_enabled(&hardforks, latest.number + 1, HardforkName::Apus) {
+            return Ok(U256::from(8u64));
+        }
+
+        let priority_fee = self.suggest_priority_fee_per_gas(&latest).await?;
+        Ok(
+            U256::from(next_block_base_fee_per_gas(&latest, &hardforks).low_u64())
+                + U256::from(priority_fee.low_u64()),
+        )
+    }
+
+    #[metrics_rpc("eth_maxPriorityFeePerGas")]
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
+        let latest = self.latest_header().await?;
+        let hardforks = self.hardfork_info().await?;
+        if !block_hardfork_enabled(&hardforks, latest.number + 1, HardforkName::Apus) {
+            return Ok(U256::from(8u64));
+        }
+
+        let priority_fee = self.suggest_priority_fee_per_gas(&latest).await?;
+        Ok(U256::from(priority_fee.low_u64()))
     }
 
     #[metrics_rpc("eth_getLogs")]
@@ -583,7 +1256,7 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
             Block(Block),
         }
 
-        async fn get_logs<T: APIAdapter>(
+        async fn get_logs<T: APIAdapter + ?Sized>(
             adapter: &T,
             position: BlockPosition,
             topics: &[Option<Vec<Option<H256>>>],
@@ -689,11 +1362,20 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
                     .map_err(|e| RpcError::Internal(e.to_string()))?
                     .unwrap();
                 let latest_number = latest_block.header.number;
+                let finalized_number = if [&filter.from_block, &filter.to_block]
+                    .iter()
+                    .any(|id| matches!(id, Some(BlockId::Safe | BlockId::Finalized)))
+                {
+                    finalized_block_number(&*self.adapter).await?
+                } else {
+                    latest_number
+                };
                 let (start, end) = {
                     let convert = |id: BlockId| -> BlockNumber {
                         match id {
                             BlockId::Num(n) => n.low_u64(),
                             BlockId::Earliest => 0,
+                            BlockId::Safe | BlockId::Finalized => finalized_number,
                             _ => latest_number,
                         }
                     };
@@ -711,17 +1393,37 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
                     return Err(RpcError::InvalidFromBlockNumber(start).into());
                 }
 
-                if end.saturating_sub(start) > self.log_filter_max_block_range {
-                    return Err(RpcError::InvalidBlockRange(
+                // The block range is not limited if the log index can tell which
+                // blocks contain the matching logs, but the number of the logs is.
+                let (numbers, indexed) = match self
+                    .adapter
+                    .get_log_block_numbers(
+                        Context::new(),
                         start,
                         end,
-                        self.log_filter_max_block_range,
+                        address_filter.as_deref().unwrap_or_default(),
+                        &topics,
                     )
-                    .into());
-                }
+                    .await
+                    .map_err(|e| RpcError::Internal(e.to_string()))?
+                {
+                    Some(numbers) => (numbers, true),
+                    None => {
+                        if end.saturating_sub(start) > self.log_filter_max_block_range {
+                            return Err(RpcError::InvalidBlockRange(
+                                start,
+                                end,
+                                self.log_filter_max_block_range,
+                            )
+                            .into());
+                        }
+
+                        ((start..=end).collect(), false)
+                    }
+                };
 
                 let mut visiter_last_block = false;
-                for n in start..=end {
+                for n in numbers {
                     if n == latest_number {
                         visiter_last_block = true;
                     } else {
@@ -736,12 +1438,12 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
                         .await?;
 
                         if early_return {
-                            return Ok(all_logs);
+                            break;
                         }
                     }
                 }
 
-                if visiter_last_block {
+                if visiter_last_block && !early_return {
                     get_logs(
                         &*self.adapter,
                         BlockPosition::Block(latest_block),
@@ -752,6 +1454,10 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
                     )
                     .await?;
                 }
+
+                if early_return && indexed {
+                    return Err(RpcError::TooManyLogs(MAX_LOG_NUM).into());
+                }
             }
         }
         Ok(all_logs)
@@ -781,6 +1487,12 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
                 gas_used_ratio: None,
             }));
         }
+        let newest_block = match newest_block {
+            BlockId::Safe | BlockId::Finalized => {
+                BlockId::Num(finalized_block_number(&*self.adapter).await?.into())
+            }
+            id => id,
+        };
         match newest_block {
             BlockId::Num(number) => {
                 let (oldest_block_number, bash_fee_per_gases, gas_used_ratios, reward) = self
@@ -845,9 +1557,10 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
                     .await
                     .map_err(|e| RpcError::Internal(e.to_string()))?
                     .unwrap();
+                let hardforks = self.hardfork_info().await?;
                 let base_fee_per_gas = vec![
                     first_block.header.base_fee_per_gas,
-                    next_block_base_fee_per_gas(),
+                    next_block_base_fee_per_gas(&first_block.header, &hardforks),
                 ];
                 let gas_used_ratio = vec![calculate_gas_used_ratio(&first_block)];
 
@@ -954,7 +1667,10 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
 
         let block = self
             .adapter
-            .get_block_by_number(Context::new(), number.into())
+            .get_block_by_number(
+                Context::new(),
+                self.get_block_number_by_id(Some(number)).await?,
+            )
             .await
             .map_err(|e| RpcError::Internal(e.to_string()))?;
 
@@ -977,7 +1693,22 @@ impl<Adapter: APIAdapter + 'static> Web3RpcServer for Web3RpcImpl<Adapter> {
             return Err(RpcError::CallSystemContract.into());
         }
 
-        let number = self.get_block_number_by_id(block_id).await?;
+        let (number, account_override) = self.account_context(address, block_id).await?;
+        if let Some(account) = account_override {
+            let index = H256::from_uint(&position);
+            if let Some(value) = account
+                .state
+                .as_ref()
+                .or(account.state_diff.as_ref())
+                .and_then(|storage| storage.get(&index))
+            {
+                return Ok(Hex::encode(value));
+            }
+
+            if account.state.is_some() {
+                return Ok(Hex::encode(H256::default()));
+            }
+        }
 
         let header = self
             .adapter
@@ -1089,13 +1820,60 @@ fn calculate_effective_priority_fees_index(
         .saturating_sub(1)
 }
 
-// Get the base fee per gas for the next block.
-fn next_block_base_fee_per_gas() -> U64 {
-    BASE_FEE_PER_GAS.into()
+/// The state and the storage slots of an account can not be replaced at the
+/// same time.
+fn check_state_override(state_override: Option<&StateOverride>) -> Result<(), RpcError> {
+    for (address, account) in state_override.into_iter().flatten() {
+        if account.state.is_some() && account.state_diff.is_some() {
+            return Err(RpcError::InvalidStateOverride(format!(
+                "both state and stateDiff are set for {:?}",
+                address
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply the state overrides of a call on top of the ones of the pending state.
+fn merge_state_override(base: &mut StateOverride, state_override: StateOverride) {
+    for (address, item) in state_override.into_iter() {
+        let account = base.entry(address).or_default();
+        if item.balance.is_some() {
+            account.balance = item.balance;
+        }
+
+        if item.nonce.is_some() {
+            account.nonce = item.nonce;
+        }
+
+        if item.code.is_some() {
+            account.code = item.code;
+        }
+
+        if let Some(state) = item.state {
+            account.state = Some(state);
+            account.state_diff = None;
+        }
+
+        if let Some(state_diff) = item.state_diff {
+            match account.state.as_mut() {
+                Some(state) => state.extend(state_diff),
+                None => account
+                    .state_diff
+                    .get_or_insert_with(Default::default)
+                    .extend(state_diff),
+            }
+        }
+    }
 }
 
-fn mock_header_by_call_req(latest_header: Header, call_req: &Web3CallRequest) -> Header {
-    Header {
+pub(crate) fn mock_header_by_call_req(
+    latest_header: Header,
+    call_req: &Web3CallRequest,
+    block_overrides: Option<&Web3BlockOverrides>,
+) -> Header {
+    let mut header = Header {
         version:                  latest_header.version,
         prev_hash:                latest_header.prev_hash,
         proposer:                 latest_header.proposer,
@@ -1125,7 +1903,173 @@ fn mock_header_by_call_req(latest_header: Header, call_req: &Web3CallRequest) ->
             .as_ref()
             .map(U64::low_u64)
             .unwrap_or(latest_header.chain_id),
+    };
+
+    if let Some(overrides) = block_overrides {
+        apply_block_overrides(&mut header, overrides);
+    }
+
+    header
+}
+
+fn apply_block_overrides(header: &mut Header, overrides: &Web3BlockOverrides) {
+    if let Some(number) = overrides.number {
+        header.number = number.low_u64();
+    }
+    if let Some(time) = overrides.time {
+        header.timestamp = time.low_u64();
+    }
+    if let Some(gas_limit) = overrides.gas_limit {
+        header.gas_limit = gas_limit;
+    }
+    if let Some(fee_recipient) = overrides.fee_recipient {
+        header.proposer = fee_recipient;
+    }
+    if let Some(base_fee) = overrides.base_fee_per_gas {
+        header.base_fee_per_gas = base_fee;
+    }
+}
+
+fn simulated_block(header: Header, resps: Vec<TxResp>) -> Web3SimulatedBlock {
+    let mut log_index = 0usize;
+    let calls = resps
+        .into_iter()
+        .enumerate()
+        .map(|(tx_index, resp)| {
+            let logs = resp
+                .logs
+                .iter()
+                .map(|log| {
+                    log_index += 1;
+                    Web3Log {
+                        address:           log.address,
+                        topics:            log.topics.clone(),
+                        data:              Hex::encode(&log.data),
+                        block_hash:        None,
+                        block_number:      Some(header.number.into()),
+                        transaction_hash:  None,
+                        transaction_index: Some(tx_index.into()),
+                        log_index:         Some((log_index - 1).into()),
+                        removed:           false,
+                    }
+                })
+                .collect();
+
+            let error = match resp.exit_reason {
+                ExitReason::Succeed(_) => None,
+                ExitReason::Revert(_) => Some(Web3CallError {
+                    code:    SIMULATE_REVERTED_CODE,
+                    message: decode_revert_msg(&resp.ret),
+                    data:    Some(Hex::encode(&resp.ret)),
+                }),
+                ref reason => exit_error(reason).map(|err| Web3CallError {
+                    code:    SIMULATE_VM_ERROR_CODE,
+                    message: err,
+                    data:    None,
+                }),
+            };
+
+            Web3SimulatedCall {
+                return_data: Hex::encode(&resp.ret),
+                logs,
+                gas_used: resp.gas_used.into(),
+                status: (error.is_none() as u64).into(),
+                error,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Web3SimulatedBlock {
+        number: header.number.into(),
+        timestamp: header.timestamp.into(),
+        gas_limit: header.gas_limit,
+        gas_used: calls
+            .iter()
+            .fold(U64::zero(), |acc, call| acc.saturating_add(call.gas_used)),
+        fee_recipient: header.proposer,
+        base_fee_per_gas: header.base_fee_per_gas,
+        calls,
+    }
+}
+
+/// Returns the time to wait for a sent transaction to be committed, which is
+/// given in milliseconds.
+fn commit_timeout(timeout_ms: Option<u64>) -> Result<Duration, RpcError> {
+    let timeout_ms = timeout_ms.unwrap_or(SEND_RAW_TX_SYNC_DEFAULT_TIMEOUT);
+    if timeout_ms == 0 || timeout_ms > SEND_RAW_TX_SYNC_MAX_TIMEOUT {
+        return Err(RpcError::InvalidTimeout(format!(
+            "{}ms, which should be in (0, {}]",
+            timeout_ms, SEND_RAW_TX_SYNC_MAX_TIMEOUT
+        )));
+    }
+
+    Ok(Duration::from_millis(timeout_ms))
+}
+
+/// Returns the number of the latest block with a stored aggregated proof,
+/// which the `safe` and `finalized` tags resolve to. The proof is saved right
+/// before its block, so the number is capped by the latest block.
+pub(crate) async fn finalized_block_number<Adapter: APIAdapter + ?Sized>(
+    adapter: &Adapter,
+) -> Result<BlockNumber, RpcError> {
+    let proof = adapter
+        .get_latest_proof(Context::new())
+        .await
+        .map_err(|e| RpcError::Internal(e.to_string()))?;
+    let latest = adapter
+        .get_block_header_by_number(Context::new(), None)
+        .await
+        .map_err(|e| RpcError::Internal(e.to_string()))?
+        .ok_or(RpcError::CannotFindBlock)?;
+
+    Ok(proof.number.min(latest.number))
+}
+
+/// Binary search the lowest gas limit not larger than `hi` with which the call
+/// succeeds. The `call` function executes the call with the given gas limit.
+async fn binary_search_gas<F, Fut>(mut hi: u64, call: F) -> Result<u64, RpcError>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = ProtocolResult<TxResp>>,
+{
+    let resp = call(hi)
+        .await
+        .map_err(|e| RpcError::Internal(e.to_string()))?;
+    if !resp.exit_reason.is_succeed() {
+        return Err(match resp.exit_reason {
+            ExitReason::Error(ExitError::OutOfGas) => RpcError::GasRequiredExceedsAllowance(hi),
+            _ => RpcError::Evm(resp),
+        });
+    }
+
+    // The gas limit can not be lower than the gas used, which is also enough
+    // for most calls, so it is tried first to reduce the number of executions.
+    let gas_used = resp.gas_used.max(MIN_TRANSACTION_GAS_LIMIT);
+    let mut lo = gas_used - 1;
+    if gas_used < hi {
+        let resp = call(gas_used)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+        if resp.exit_reason.is_succeed() {
+            hi = gas_used;
+        } else {
+            lo = gas_used;
+        }
     }
+
+    while lo + 1 < hi {
+        let mid = lo + (hi - lo) / 2;
+        let resp = call(mid)
+            .await
+            .map_err(|e| RpcError::Internal(e.to_string()))?;
+        if resp.exit_reason.is_succeed() {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    Ok(hi)
 }
 
 pub fn from_receipt_to_web3_log(
@@ -1171,3 +2115,271 @@ pub fn from_receipt_to_web3_log(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::future::{ready, Ready};
+
+    use protocol::tokio;
+    use protocol::types::{ExitRevert, ExitSucceed, Proof};
+
+    use crate::tests::{mock_log, mock_raw_tx, mock_signed_tx, MockAdapter, MOCK_TX_GAS_USED};
+
+    use super::*;
+
+    /// Mock a call which requires `required` gas to succeed and uses `used`
+    /// gas, the required gas may be larger than the used one since at most
+    /// 63/64 of the remaining gas is forwarded to a sub call.
+    fn mock_call(
+        required: u64,
+        used: u64,
+        revert: bool,
+    ) -> impl Fn(u64) -> Ready<ProtocolResult<TxResp>> {
+        move |gas| {
+            let exit_reason = if gas < required {
+                ExitReason::Error(ExitError::OutOfGas)
+            } else if revert {
+                ExitReason::Revert(ExitRevert::Reverted)
+            } else {
+                ExitReason::Succeed(ExitSucceed::Stopped)
+            };
+            ready(Ok(TxResp {
+                exit_reason,
+                gas_used: used,
+                ..Default::default()
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_binary_search_gas() {
+        // A transfer uses the minimum gas.
+        let gas = binary_search_gas(MAX_GAS_LIMIT, mock_call(21000, 21000, false)).await;
+        assert_eq!(gas.unwrap(), MIN_TRANSACTION_GAS_LIMIT);
+
+        // The required gas is larger than the gas used.
+        let gas = binary_search_gas(MAX_GAS_LIMIT, mock_call(101_000, 100_000, false)).await;
+        assert_eq!(gas.unwrap(), 101_000);
+
+        // The gas used is enough.
+        for used in 21_000..21_010 {
+            let gas = binary_search_gas(MAX_GAS_LIMIT, mock_call(used, used, false)).await;
+            assert_eq!(gas.unwrap(), used);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_binary_search_gas_cap() {
+        // The call requires exactly the cap.
+        let gas = binary_search_gas(50_000, mock_call(50_000, 50_000, false)).await;
+        assert_eq!(gas.unwrap(), 50_000);
+
+        let gas = binary_search_gas(50_000, mock_call(50_000, 45_000, false)).await;
+        assert_eq!(gas.unwrap(), 50_000);
+
+        // The call requires more than the cap.
+        let gas = binary_search_gas(50_000, mock_call(50_001, 50_001, false)).await;
+        assert!(matches!(
+            gas,
+            Err(RpcError::GasRequiredExceedsAllowance(50_000))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_binary_search_gas_revert() {
+        let gas = binary_search_gas(MAX_GAS_LIMIT, mock_call(21000, 30_000, true)).await;
+        assert!(matches!(gas, Err(RpcError::Evm(resp)) if resp.exit_reason.is_revert()));
+    }
+
+    #[tokio::test]
+    async fn test_get_block_receipts() {
+        let adapter = MockAdapter::default();
+        let sender = H160::random();
+        let log = mock_log(H160::random(), vec![H256::random()]);
+        let block = adapter.push_block(vec![
+            (mock_signed_tx(sender, 0), vec![log.clone(), log.clone()]),
+            (mock_signed_tx(sender, 1), Vec::new()),
+            (mock_signed_tx(sender, 2), vec![log]),
+        ]);
+        let adapter = Arc::new(adapter);
+        let rpc = Web3RpcImpl::new(Arc::clone(&adapter), u64::MAX, 1);
+
+        let receipts = rpc
+            .get_block_receipts(BlockId::Num(1.into()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(receipts.len(), 3);
+
+        let log_indexes = receipts
+            .iter()
+            .flat_map(|receipt| receipt.logs.iter().map(|log| log.log_index))
+            .collect::<Vec<_>>();
+        assert_eq!(log_indexes, vec![0.into(), 1.into(), 2.into()]);
+
+        for (i, receipt) in receipts.iter().enumerate() {
+            let cumulative_gas_used = U64::from(MOCK_TX_GAS_USED * (i as u64 + 1));
+            assert_eq!(receipt.gas_used, U64::from(MOCK_TX_GAS_USED));
+            assert_eq!(receipt.cumulative_gas_used, cumulative_gas_used);
+
+            // A single receipt agrees with the receipts of the block.
+            let single = rpc
+                .get_transaction_receipt(receipt.transaction_hash)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(single, *receipt);
+
+            // So does a single receipt queried before the receipts of the block.
+            let single = Web3RpcImpl::new(Arc::clone(&adapter), u64::MAX, 1)
+                .get_transaction_receipt(receipt.transaction_hash)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(single, *receipt);
+        }
+
+        let by_hash = rpc
+            .get_block_receipts(BlockId::Hash(block.hash()))
+            .await
+            .unwrap();
+        assert_eq!(by_hash, Some(receipts));
+        assert_eq!(
+            rpc.get_block_receipts(BlockId::Num(2.into()))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_block_number_by_safe_id() {
+        let adapter = Arc::new(MockAdapter::default());
+        for _ in 0..3 {
+            adapter.push_block(Vec::new());
+        }
+        let rpc = &Web3RpcImpl::new(Arc::clone(&adapter), u64::MAX, 1);
+        let number = move |id: BlockId| async move {
+            rpc.get_block_by_number(id, false)
+                .await
+                .unwrap()
+                .map(|block| block.number.low_u64())
+        };
+
+        // The latest proof is the one of the parent of the latest block.
+        assert_eq!(number(BlockId::Latest).await, Some(3));
+        assert_eq!(number(BlockId::Safe).await, Some(2));
+        assert_eq!(number(BlockId::Finalized).await, Some(2));
+        assert_eq!(number(BlockId::Earliest).await, Some(0));
+        assert_eq!(number(BlockId::Num(1.into())).await, Some(1));
+
+        adapter.set_latest_proof(Proof {
+            number: 1,
+            ..Default::default()
+        });
+        assert_eq!(number(BlockId::Safe).await, Some(1));
+        assert_eq!(number(BlockId::Finalized).await, Some(1));
+        let count = rpc
+            .get_block_transaction_count_by_number(BlockId::Finalized)
+            .await;
+        assert_eq!(count.unwrap(), U256::zero());
+
+        // The proof saved before its block is capped by the latest block.
+        adapter.set_latest_proof(Proof {
+            number: 4,
+            ..Default::default()
+        });
+        assert_eq!(number(BlockId::Safe).await, Some(3));
+        assert_eq!(number(BlockId::Finalized).await, Some(3));
+    }
+
+    /// Commits a block containing the transaction once it is in the mempool.
+    async fn commit_once_sent(adapter: &MockAdapter, stx: &SignedTransaction) {
+        let hash = stx.transaction.hash;
+        while !adapter
+            .mempool()
+            .iter()
+            .any(|tx| tx.transaction.hash == hash)
+        {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let block = adapter.push_block(vec![(stx.clone(), Vec::new())]);
+        adapter.committed_blocks.send(block).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_transaction_sync() {
+        let adapter = Arc::new(MockAdapter::default());
+        let rpc = Web3RpcImpl::new(Arc::clone(&adapter), u64::MAX, 1);
+
+        let (stx, raw) = mock_raw_tx(0);
+        let (receipt, _) = tokio::join!(
+            rpc.send_raw_transaction_sync(raw, Some(5000)),
+            commit_once_sent(&adapter, &stx)
+        );
+        let receipt = receipt.unwrap();
+        assert_eq!(receipt.transaction_hash, stx.transaction.hash);
+        assert_eq!(receipt.block_number, U256::one());
+        assert_eq!(receipt.from, stx.sender);
+
+        // The transaction is sent but never committed.
+        let (stx, raw) = mock_raw_tx(1);
+        let err = rpc.send_raw_transaction_sync(raw, Some(100)).await;
+        assert_eq!(
+            err.unwrap_err(),
+            RpcError::TransactionCommitTimeout(stx.transaction.hash).into()
+        );
+        assert!(adapter.mempool().contains(&stx));
+
+        // The transaction is not sent with an invalid timeout.
+        let (stx, raw) = mock_raw_tx(2);
+        assert!(rpc.send_raw_transaction_sync(raw, Some(0)).await.is_err());
+        assert!(!adapter.mempool().contains(&stx));
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_transaction_subscribe() {
+        let adapter = Arc::new(MockAdapter::default());
+        let module = Web3RpcImpl::new(Arc::clone(&adapter), u64::MAX, 1).into_rpc();
+        let request = |raw: &Hex, timeout_ms: u64| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_sendRawTransactionSubscribe",
+                "params": [raw, timeout_ms],
+            })
+            .to_string()
+        };
+
+        let (stx, raw) = mock_raw_tx(0);
+        let (resp, _) = tokio::join!(
+            module.raw_json_request(&request(&raw, 5000), 1),
+            commit_once_sent(&adapter, &stx)
+        );
+        let (resp, mut notifs) = resp.unwrap();
+        assert!(resp.is_success());
+        let notif: serde_json::Value = serde_json::from_str(&notifs.recv().await.unwrap()).unwrap();
+        assert_eq!(notif["method"], "eth_transactionReceipt");
+        let receipt: Web3Receipt =
+            serde_json::from_value(notif["params"]["result"].clone()).unwrap();
+        assert_eq!(receipt.transaction_hash, stx.transaction.hash);
+
+        // The subscription is closed with an error once the timeout expires.
+        let (stx, raw) = mock_raw_tx(1);
+        let (resp, mut notifs) = module
+            .raw_json_request(&request(&raw, 100), 1)
+            .await
+            .unwrap();
+        assert!(resp.is_success());
+        let notif: serde_json::Value = serde_json::from_str(&notifs.recv().await.unwrap()).unwrap();
+        let err = RpcError::TransactionCommitTimeout(stx.transaction.hash).to_string();
+        assert_eq!(notif["params"]["error"], err);
+
+        // The subscription is rejected with an invalid timeout.
+        let (stx, raw) = mock_raw_tx(2);
+        let (resp, _) = module.raw_json_request(&request(&raw, 0), 1).await.unwrap();
+        assert!(!resp.is_success());
+        assert!(!adapter.mempool().contains(&stx));
+    }
+}
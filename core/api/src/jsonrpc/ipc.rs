@@ -0,0 +1,449 @@
+use std::fs::{DirBuilder, Permissions};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use jsonrpsee::server::helpers::batch_response_error;
+use jsonrpsee::server::{
+    BatchRequestConfig, BoundedSubscriptions, ConnectionId, IdProvider, MethodCallback,
+    MethodResponse, MethodSink, Methods, SubscriptionState,
+};
+use jsonrpsee::types::error::{
+    reject_too_big_batch_request, reject_too_big_request, reject_too_many_subscriptions, ErrorCode,
+    BATCHES_NOT_SUPPORTED_CODE, BATCHES_NOT_SUPPORTED_MSG,
+};
+use jsonrpsee::types::{ErrorObject, Id, Params, Request};
+use serde::de::IgnoredAny;
+
+use protocol::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use protocol::tokio::net::{UnixListener, UnixStream};
+use protocol::tokio::sync::mpsc::{channel, Receiver, Sender};
+use protocol::tokio::task::{JoinHandle, JoinSet};
+use protocol::tokio::{self, select};
+
+use crate::jsonrpc::ws_subscription::HexIdProvider;
+use crate::APIError;
+
+/// The same as `geth.ipc`, only the owner can connect.
+pub const DEFAULT_IPC_PERMISSIONS: u32 = 0o600;
+/// The capacity of the messages buffered for every connection and every
+/// subscription.
+const MESSAGE_BUFFER_SIZE: usize = 1024;
+const READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Stops the IPC server and removes the socket file when dropped.
+pub struct IpcServerHandle {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl Drop for IpcServerHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// The calls of a connection share its id and its subscription limit, the
+/// same as the ones of a WebSocket connection.
+struct Connection {
+    id:            ConnectionId,
+    methods:       Methods,
+    batch_config:  BatchRequestConfig,
+    subscriptions: BoundedSubscriptions,
+    id_provider:   Arc<dyn IdProvider>,
+}
+
+/// Serves the methods, including the subscriptions, over a Unix domain
+/// socket. The same as `geth.ipc`, the requests and the responses are JSON
+/// values written back to back, and every message written by the server ends
+/// with a newline.
+pub fn start_ipc_server(
+    path: &Path,
+    permissions: u32,
+    methods: Methods,
+    max_request_size: u32,
+    batch_config: BatchRequestConfig,
+    max_subscriptions_per_connection: u32,
+) -> Result<IpcServerHandle, APIError> {
+    let ipc_err = |e: std::io::Error| APIError::IpcServer(format!("{}: {}", path.display(), e));
+
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    std::fs::create_dir_all(dir).map_err(ipc_err)?;
+
+    // Remove the socket left by a previous run, but never a regular file.
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path).map_err(ipc_err)?,
+        Ok(_) => {
+            return Err(APIError::IpcServer(format!(
+                "{} exists and is not a socket",
+                path.display()
+            )))
+        }
+        Err(_) => (),
+    }
+
+    // The socket is bound in a private directory and moved in place once its
+    // permissions are set, so it is never reachable with the mode given by
+    // the umask.
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let private_dir = dir.join(format!(".{}.{}", file_name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&private_dir);
+    DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)
+        .map_err(ipc_err)?;
+
+    let bind = || -> std::io::Result<UnixListener> {
+        let private_path = private_dir.join("ipc");
+        let listener = UnixListener::bind(&private_path)?;
+        std::fs::set_permissions(&private_path, Permissions::from_mode(permissions))?;
+        std::fs::rename(&private_path, path)?;
+        Ok(listener)
+    };
+    let listener = bind();
+    let _ = std::fs::remove_dir_all(&private_dir);
+    let listener = listener.map_err(ipc_err)?;
+
+    let id_provider: Arc<dyn IdProvider> = Arc::new(HexIdProvider::default());
+    let task = tokio::spawn(async move {
+        let mut id: ConnectionId = 0;
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let conn = Connection {
+                        id,
+                        methods: methods.clone(),
+                        batch_config,
+                        subscriptions: BoundedSubscriptions::new(max_subscriptions_per_connection),
+                        id_provider: Arc::clone(&id_provider),
+                    };
+                    tokio::spawn(serve_connection(stream, Arc::new(conn), max_request_size));
+                    id = id.wrapping_add(1);
+                }
+                Err(e) => log::warn!("[ipc] accept connection error: {}", e),
+            }
+        }
+    });
+
+    Ok(IpcServerHandle {
+        path: path.to_path_buf(),
+        task,
+    })
+}
+
+async fn serve_connection(stream: UnixStream, conn: Arc<Connection>, max_request_size: u32) {
+    let (mut reader, mut writer) = stream.into_split();
+    let (tx, mut rx) = channel::<String>(MESSAGE_BUFFER_SIZE);
+
+    tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            if writer.write_all(msg.as_bytes()).await.is_err()
+                || writer.write_all(b"\n").await.is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    // The calls and the subscriptions of the connection, which are cancelled
+    // once it is closed.
+    let mut calls = JoinSet::new();
+    let mut buf = Vec::new();
+    let mut chunk = vec![0u8; READ_BUFFER_SIZE];
+
+    loop {
+        let len = select! {
+            read = reader.read(&mut chunk) => match read {
+                // Answer the calls sent before the client shuts down its writing.
+                Ok(0) | Err(_) => break,
+                Ok(len) => len,
+            },
+            Some(_) = calls.join_next(), if !calls.is_empty() => continue,
+        };
+        buf.extend_from_slice(&chunk[..len]);
+
+        let mut consumed = 0;
+        let mut values = serde_json::Deserializer::from_slice(&buf).into_iter::<IgnoredAny>();
+        loop {
+            match values.next() {
+                Some(Ok(_)) => {
+                    let end = values.byte_offset();
+                    calls.spawn(handle_request(
+                        Arc::clone(&conn),
+                        buf[consumed..end].to_vec(),
+                        tx.clone(),
+                    ));
+                    consumed = end;
+                }
+                Some(Err(e)) if e.is_eof() => break,
+                Some(Err(_)) => {
+                    // The stream can not be resynchronized after a malformed value.
+                    let _ = tx.send(error_response(ErrorCode::ParseError)).await;
+                    return;
+                }
+                None => break,
+            }
+        }
+        buf.drain(..consumed);
+
+        if buf.len() > max_request_size as usize {
+            let err = reject_too_big_request(max_request_size);
+            let _ = tx.send(MethodResponse::error(Id::Null, err).result).await;
+            return;
+        }
+    }
+
+    while calls.join_next().await.is_some() {}
+}
+
+async fn handle_request(conn: Arc<Connection>, request: Vec<u8>, tx: Sender<String>) {
+    let Ok(request) = std::str::from_utf8(&request) else {
+        let _ = tx.send(error_response(ErrorCode::ParseError)).await;
+        return;
+    };
+    let request = request.trim();
+
+    if !request.starts_with('[') {
+        let (resp, notifications) = call(&conn, request).await;
+        if let (Ok(()), Some(notifications)) = (tx.send(resp).await, notifications) {
+            forward(notifications, tx).await;
+        }
+        return;
+    }
+
+    let batch = match serde_json::from_str::<Vec<serde_json::Value>>(request) {
+        Ok(batch) if !batch.is_empty() => batch,
+        _ => {
+            let _ = tx.send(error_response(ErrorCode::InvalidRequest)).await;
+            return;
+        }
+    };
+
+    let err = match conn.batch_config {
+        BatchRequestConfig::Disabled => Some(ErrorObject::borrowed(
+            BATCHES_NOT_SUPPORTED_CODE,
+            BATCHES_NOT_SUPPORTED_MSG,
+            None,
+        )),
+        BatchRequestConfig::Limit(limit) if batch.len() > limit as usize => {
+            Some(reject_too_big_batch_request(limit as usize))
+        }
+        _ => None,
+    };
+    if let Some(err) = err {
+        let _ = tx.send(batch_response_error(Id::Null, err)).await;
+        return;
+    }
+
+    let mut resps = Vec::with_capacity(batch.len());
+    let mut subscriptions = Vec::new();
+    for request in batch {
+        let (resp, notifications) = call(&conn, &request.to_string()).await;
+        resps.push(resp);
+        subscriptions.push(notifications);
+    }
+
+    if tx.send(format!("[{}]", resps.join(","))).await.is_ok() {
+        let mut forwards = JoinSet::new();
+        for notifications in subscriptions.into_iter().flatten() {
+            forwards.spawn(forward(notifications, tx.clone()));
+        }
+        while forwards.join_next().await.is_some() {}
+    }
+}
+
+/// Returns the response, and the notifications of the call if it is a
+/// subscription.
+async fn call(conn: &Connection, request: &str) -> (String, Option<Receiver<String>>) {
+    let Ok(req) = serde_json::from_str::<Request>(request) else {
+        return (error_response(ErrorCode::InvalidRequest), None);
+    };
+    let id = req.id;
+    let params = Params::new(req.params.map(|params| params.get()));
+
+    let resp = match conn.methods.method(&req.method) {
+        None => MethodResponse::error(id, ErrorObject::from(ErrorCode::MethodNotFound)),
+        Some(MethodCallback::Sync(callback)) => callback(id, params, usize::MAX),
+        Some(MethodCallback::Async(callback)) => {
+            callback(id.into_owned(), params.into_owned(), conn.id, usize::MAX).await
+        }
+        Some(MethodCallback::Subscription(callback)) => {
+            let Some(subscription_permit) = conn.subscriptions.acquire() else {
+                let err = reject_too_many_subscriptions(conn.subscriptions.max());
+                return (MethodResponse::error(id, err).result, None);
+            };
+            let state = SubscriptionState {
+                conn_id: conn.id,
+                id_provider: &*conn.id_provider,
+                subscription_permit,
+            };
+
+            let (tx, mut notifications) = channel(MESSAGE_BUFFER_SIZE);
+            let resp = match callback(id, params, MethodSink::new(tx), state).await {
+                Ok(resp) => resp,
+                Err(id) => MethodResponse::error(id, ErrorObject::from(ErrorCode::InternalError)),
+            };
+
+            // The response is sent to the sink as well, before the notifications.
+            let _ = notifications.recv().await;
+            return (resp.result, Some(notifications));
+        }
+        Some(MethodCallback::Unsubscription(callback)) => callback(id, params, conn.id, usize::MAX),
+    };
+
+    (resp.result, None)
+}
+
+async fn forward(mut notifications: Receiver<String>, tx: Sender<String>) {
+    while let Some(notification) = notifications.recv().await {
+        if tx.send(notification).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn error_response(code: ErrorCode) -> String {
+    MethodResponse::error(Id::Null, ErrorObject::from(code)).result
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpsee::core::SubscriptionResult;
+    use jsonrpsee::types::error::{TOO_BIG_BATCH_REQUEST_CODE, TOO_MANY_SUBSCRIPTIONS_CODE};
+    use jsonrpsee::types::ResponsePayload;
+    use jsonrpsee::{RpcModule, SubscriptionMessage};
+    use protocol::tokio::io::{AsyncBufReadExt, BufReader};
+    use serde_json::Value;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ipc_server() {
+        let path = std::env::temp_dir().join(format!("axon-{}.ipc", std::process::id()));
+        let mut module = RpcModule::new(());
+        module
+            .register_method("web3_clientVersion", |_, _| "axon")
+            .unwrap();
+
+        let handle = start_ipc_server(
+            &path,
+            DEFAULT_IPC_PERMISSIONS,
+            module.into(),
+            1024,
+            BatchRequestConfig::Unlimited,
+            1,
+        )
+        .unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, DEFAULT_IPC_PERMISSIONS);
+
+        // A single request and a batch written back to back, split in the middle.
+        let mut stream = BufReader::new(UnixStream::connect(&path).await.unwrap());
+        let requests = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"web3_clientVersion"}"#,
+            r#"[{"jsonrpc":"2.0","id":2,"method":"web3_clientVersion"}]"#
+        );
+        let (head, tail) = requests.split_at(70);
+        stream.get_mut().write_all(head.as_bytes()).await.unwrap();
+        stream.get_mut().write_all(tail.as_bytes()).await.unwrap();
+
+        let mut resps = Vec::new();
+        for _ in 0..2 {
+            let mut line = String::new();
+            stream.read_line(&mut line).await.unwrap();
+            resps.push(line);
+        }
+        resps.sort();
+        assert_eq!(resps, vec![
+            "[{\"jsonrpc\":\"2.0\",\"result\":\"axon\",\"id\":2}]\n",
+            "{\"jsonrpc\":\"2.0\",\"result\":\"axon\",\"id\":1}\n",
+        ]);
+
+        drop(handle);
+        assert!(!path.exists());
+    }
+
+    async fn request(stream: &mut BufReader<UnixStream>, request: &str) -> Value {
+        stream
+            .get_mut()
+            .write_all(request.as_bytes())
+            .await
+            .unwrap();
+        next_message(stream).await
+    }
+
+    async fn next_message(stream: &mut BufReader<UnixStream>) -> Value {
+        let mut line = String::new();
+        stream.read_line(&mut line).await.unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ipc_subscription() {
+        let path = std::env::temp_dir().join(format!("axon-sub-{}.ipc", std::process::id()));
+        let mut module = RpcModule::new(());
+        module
+            .register_subscription(
+                "eth_subscribe",
+                "eth_subscription",
+                "eth_unsubscribe",
+                |_, pending, _| async move {
+                    let sink = pending.accept().await?;
+                    sink.send(SubscriptionMessage::from_json(&"0x1")?).await?;
+                    sink.closed().await;
+                    SubscriptionResult::Ok(())
+                },
+            )
+            .unwrap();
+        let mut methods: Methods = module.into();
+        methods
+            .verify_and_insert(
+                "conn_id",
+                MethodCallback::Async(Arc::new(|id, _, conn_id, _| {
+                    Box::pin(async move {
+                        MethodResponse::response(id, ResponsePayload::result(conn_id), usize::MAX)
+                    })
+                })),
+            )
+            .unwrap();
+
+        let _handle = start_ipc_server(
+            &path,
+            DEFAULT_IPC_PERMISSIONS,
+            methods,
+            1024,
+            BatchRequestConfig::Limit(1),
+            1,
+        )
+        .unwrap();
+        let mut stream = BufReader::new(UnixStream::connect(&path).await.unwrap());
+        let mut other = BufReader::new(UnixStream::connect(&path).await.unwrap());
+
+        let subscribe = r#"{"jsonrpc":"2.0","id":1,"method":"eth_subscribe","params":[]}"#;
+        let resp = request(&mut stream, subscribe).await;
+        let notification = next_message(&mut stream).await;
+        assert_eq!(notification["method"], "eth_subscription");
+        assert_eq!(notification["params"]["subscription"], resp["result"]);
+        assert_eq!(notification["params"]["result"], "0x1");
+
+        // The subscriptions are limited per connection.
+        let resp = request(&mut stream, subscribe).await;
+        assert_eq!(resp["error"]["code"], TOO_MANY_SUBSCRIPTIONS_CODE);
+        let resp = request(&mut other, subscribe).await;
+        assert!(resp["result"].is_string());
+        next_message(&mut other).await;
+
+        let batch = concat!(
+            r#"[{"jsonrpc":"2.0","id":2,"method":"conn_id"},"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"conn_id"}]"#
+        );
+        let resp = request(&mut stream, batch).await;
+        assert_eq!(resp["error"]["code"], TOO_BIG_BATCH_REQUEST_CODE);
+
+        let conn_id = r#"{"jsonrpc":"2.0","id":4,"method":"conn_id"}"#;
+        let first = request(&mut stream, conn_id).await;
+        let second = request(&mut other, conn_id).await;
+        assert_ne!(first["result"], second["result"]);
+    }
+}
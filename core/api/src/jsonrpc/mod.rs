@@ -1,27 +1,37 @@
-mod error;
-mod r#impl;
+pub(crate) mod auth;
+pub(crate) mod error;
+pub(crate) mod r#impl;
+mod ipc;
+pub(crate) mod rate_limit;
 pub mod web3_types;
 mod ws_subscription;
 
 use std::{collections::HashMap, sync::Arc};
 
 use ckb_jsonrpc_types::{CellInfo, HeaderView as CkbHeaderView, OutPoint};
-use hyper::{header::CONTENT_TYPE, Method};
+use hyper::header::{HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use hyper::Method;
+use jsonrpsee::core::{RpcResult, SubscriptionResult};
+use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::server::{ServerBuilder, ServerHandle};
-use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use tower_http::cors::{Any as CorsAny, CorsLayer};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
-use common_config_parser::types::{spec::HardforkName, Config};
+use common_config_parser::types::{spec::HardforkName, Config, ConfigApi};
 use protocol::traits::APIAdapter;
 use protocol::types::{
-    Block, CkbRelatedInfo, EthAccountProof, Hash, Hex, Metadata, Proof, Proposal, H160, H256, U256,
+    Block, CkbRelatedInfo, EthAccountProof, Hash, Hex, Metadata, Proof, Proposal, StateOverride,
+    TraceResult, H160, H256, U256,
 };
 use protocol::ProtocolResult;
 
+use crate::jsonrpc::auth::{Auth, AuthLayer, MethodFilter, API_KEY_HEADER};
+use crate::jsonrpc::ipc::{start_ipc_server, IpcServerHandle, DEFAULT_IPC_PERMISSIONS};
+use crate::jsonrpc::rate_limit::{RateLimitLayer, RateLimiter, RemoteAddrLogger};
 use crate::jsonrpc::web3_types::{
-    BlockCount, BlockId, FilterChanges, HardforkStatus, RawLoggerFilter, Web3Block,
-    Web3CallRequest, Web3FeeHistory, Web3Filter, Web3Log, Web3Receipt, Web3SyncStatus,
-    Web3Transaction,
+    BlockCount, BlockId, BlockTraceResult, FilterChanges, FinalizedBlock, HardforkStatus, NodeInfo,
+    PeerInfo, RawLoggerFilter, TraceConfig, TxPoolContent, TxPoolContentFrom, TxPoolStatus,
+    Web3AccessList, Web3Block, Web3BlockOverrides, Web3CallRequest, Web3FeeHistory, Web3Filter,
+    Web3Log, Web3Receipt, Web3SimulatePayload, Web3SimulatedBlock, Web3SyncStatus, Web3Transaction,
 };
 use crate::jsonrpc::ws_subscription::{ws_subscription_module, HexIdProvider};
 use crate::APIError;
@@ -32,6 +42,29 @@ pub trait Web3Rpc {
     #[method(name = "eth_sendRawTransaction")]
     async fn send_raw_transaction(&self, tx: Hex) -> RpcResult<H256>;
 
+    /// Sends signed transaction, waiting until it is committed or the timeout
+    /// in milliseconds expires, and returns its receipt.
+    #[method(name = "eth_sendRawTransactionSync")]
+    async fn send_raw_transaction_sync(
+        &self,
+        tx: Hex,
+        timeout_ms: Option<u64>,
+    ) -> RpcResult<Web3Receipt>;
+
+    /// The WebSocket variant of `eth_sendRawTransactionSync`, which notifies
+    /// the receipt once the transaction is committed, or closes with an error
+    /// notification once the timeout in milliseconds expires.
+    #[subscription(
+        name = "eth_sendRawTransactionSubscribe" => "eth_transactionReceipt",
+        unsubscribe = "eth_sendRawTransactionUnsubscribe",
+        item = Web3Receipt
+    )]
+    async fn send_raw_transaction_subscribe(
+        &self,
+        tx: Hex,
+        timeout_ms: Option<u64>,
+    ) -> SubscriptionResult;
+
     /// Get transaction by its hash.
     #[method(name = "eth_getTransactionByHash")]
     async fn get_transaction_by_hash(&self, hash: H256) -> RpcResult<Option<Web3Transaction>>;
@@ -67,11 +100,43 @@ pub trait Web3Rpc {
     #[method(name = "eth_getBalance")]
     async fn get_balance(&self, address: H160, number: Option<BlockId>) -> RpcResult<U256>;
 
+    /// Executes a call without creating a transaction. The state and the block
+    /// context can be overridden, which is only applied to this call.
     #[method(name = "eth_call")]
-    async fn call(&self, req: Web3CallRequest, number: Option<BlockId>) -> RpcResult<Hex>;
+    async fn call(
+        &self,
+        req: Web3CallRequest,
+        number: Option<BlockId>,
+        state_override: Option<StateOverride>,
+        block_overrides: Option<Web3BlockOverrides>,
+    ) -> RpcResult<Hex>;
 
     #[method(name = "eth_estimateGas")]
-    async fn estimate_gas(&self, req: Web3CallRequest, number: Option<BlockId>) -> RpcResult<U256>;
+    async fn estimate_gas(
+        &self,
+        req: Web3CallRequest,
+        number: Option<BlockId>,
+        state_override: Option<StateOverride>,
+        block_overrides: Option<Web3BlockOverrides>,
+    ) -> RpcResult<U256>;
+
+    /// Simulates the calls of the blocks in sequence on top of the given
+    /// block, the state changes are carried between the calls and the blocks.
+    #[method(name = "eth_simulateV1")]
+    async fn simulate_v1(
+        &self,
+        payload: Web3SimulatePayload,
+        number: Option<BlockId>,
+    ) -> RpcResult<Vec<Web3SimulatedBlock>>;
+
+    /// Generates an access list of the call, returns it with the gas used when
+    /// the list is applied.
+    #[method(name = "eth_createAccessList")]
+    async fn create_access_list(
+        &self,
+        req: Web3CallRequest,
+        number: Option<BlockId>,
+    ) -> RpcResult<Web3AccessList>;
 
     #[method(name = "eth_getCode")]
     async fn get_code(&self, address: H160, number: Option<BlockId>) -> RpcResult<Hex>;
@@ -79,9 +144,17 @@ pub trait Web3Rpc {
     #[method(name = "eth_getTransactionReceipt")]
     async fn get_transaction_receipt(&self, hash: H256) -> RpcResult<Option<Web3Receipt>>;
 
+    /// Returns all the receipts of the block, or `None` if the block is not
+    /// found.
+    #[method(name = "eth_getBlockReceipts")]
+    async fn get_block_receipts(&self, number: BlockId) -> RpcResult<Option<Vec<Web3Receipt>>>;
+
     #[method(name = "eth_gasPrice")]
     async fn gas_price(&self) -> RpcResult<U256>;
 
+    #[method(name = "eth_maxPriorityFeePerGas")]
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256>;
+
     #[method(name = "eth_getLogs")]
     async fn get_logs(&self, filter: Web3Filter) -> RpcResult<Vec<Web3Log>>;
 
@@ -221,6 +294,11 @@ pub trait AxonRpc {
     #[method(name = "axon_getProofById")]
     async fn get_proof_by_id(&self, block_id: BlockId) -> RpcResult<Option<Proof>>;
 
+    /// Returns the latest block with a stored proof, together with the
+    /// aggregated BLS signature of the validators on it.
+    #[method(name = "axon_getFinalizedBlock")]
+    async fn get_finalized_block(&self) -> RpcResult<FinalizedBlock>;
+
     #[method(name = "axon_getMetadataByNumber")]
     async fn get_metadata_by_number(&self, block_number: U256) -> RpcResult<Metadata>;
 
@@ -250,12 +328,135 @@ pub trait CkbLightClientRpc {
     ) -> RpcResult<Option<CellInfo>>;
 }
 
+#[rpc(server)]
+pub trait DebugRpc {
+    /// Re-executes the transaction on the state of its parent block and returns
+    /// the trace of it.
+    #[method(name = "debug_traceTransaction")]
+    async fn trace_transaction(
+        &self,
+        tx_hash: H256,
+        config: Option<TraceConfig>,
+    ) -> RpcResult<TraceResult>;
+
+    /// Traces a call on the state of the given block without creating a
+    /// transaction.
+    #[method(name = "debug_traceCall")]
+    async fn trace_call(
+        &self,
+        req: Web3CallRequest,
+        block_id: Option<BlockId>,
+        config: Option<TraceConfig>,
+    ) -> RpcResult<TraceResult>;
+
+    /// Re-executes all the transactions of the block in order and returns the
+    /// trace of each transaction.
+    #[method(name = "debug_traceBlockByNumber")]
+    async fn trace_block_by_number(
+        &self,
+        number: BlockId,
+        config: Option<TraceConfig>,
+    ) -> RpcResult<Vec<BlockTraceResult>>;
+
+    #[method(name = "debug_traceBlockByHash")]
+    async fn trace_block_by_hash(
+        &self,
+        hash: H256,
+        config: Option<TraceConfig>,
+    ) -> RpcResult<Vec<BlockTraceResult>>;
+}
+
+#[rpc(server)]
+pub trait TxPoolRpc {
+    /// Returns the numbers of the pending and the queued transactions in the
+    /// mempool.
+    #[method(name = "txpool_status")]
+    async fn status(&self) -> RpcResult<TxPoolStatus>;
+
+    /// Returns the pending and the queued transactions in the mempool grouped
+    /// by sender and nonce.
+    #[method(name = "txpool_content")]
+    async fn content(&self) -> RpcResult<TxPoolContent<Web3Transaction>>;
+
+    /// Returns the pending and the queued transactions sent by the address.
+    #[method(name = "txpool_contentFrom")]
+    async fn content_from(&self, address: H160) -> RpcResult<TxPoolContentFrom<Web3Transaction>>;
+
+    /// Returns a textual summary of the transactions in the mempool.
+    #[method(name = "txpool_inspect")]
+    async fn inspect(&self) -> RpcResult<TxPoolContent<String>>;
+}
+
+/// The node management methods, which are only served on the admin listening
+/// address with the admin auth token.
+#[rpc(server)]
+pub trait AdminRpc {
+    /// Returns the connected peers.
+    #[method(name = "admin_peers")]
+    async fn peers(&self) -> RpcResult<Vec<PeerInfo>>;
+
+    #[method(name = "admin_nodeInfo")]
+    async fn node_info(&self) -> RpcResult<NodeInfo>;
+
+    /// Dials the multiaddr, which must end with `/p2p/<peer id>`.
+    #[method(name = "admin_addPeer")]
+    async fn add_peer(&self, multiaddr: String) -> RpcResult<bool>;
+
+    /// Disconnects the peer, returns false if it is not connected.
+    #[method(name = "admin_removePeer")]
+    async fn remove_peer(&self, peer_id: String) -> RpcResult<bool>;
+
+    /// Bans the addresses of the peer for the duration in seconds, one day by
+    /// default, and returns the banned ip networks.
+    #[method(name = "admin_banPeer")]
+    async fn ban_peer(
+        &self,
+        peer_id: String,
+        duration: Option<u64>,
+        reason: Option<String>,
+    ) -> RpcResult<Vec<String>>;
+
+    /// Unbans the ip network returned by `admin_banPeer`.
+    #[method(name = "admin_unban")]
+    async fn unban(&self, ip_network: String) -> RpcResult<bool>;
+}
+
 pub async fn run_jsonrpc_server<Adapter: APIAdapter + 'static>(
     version: String,
     config: Config,
     adapter: Arc<Adapter>,
-) -> ProtocolResult<(Option<ServerHandle>, Option<ServerHandle>)> {
-    let mut ret = (None, None);
+) -> ProtocolResult<(
+    Option<ServerHandle>,
+    Option<ServerHandle>,
+    Option<ServerHandle>,
+    Option<IpcServerHandle>,
+)> {
+    let mut ret = (None, None, None, None);
+    let ipc_path = config
+        .rpc
+        .ipc_path
+        .as_ref()
+        .map(|path| config.data_path.join(path));
+
+    if let Some(addr) = config.rpc.admin_listening_address {
+        let token = config.rpc.admin_auth_token.as_deref().ok_or_else(|| {
+            APIError::AdminServer("admin_auth_token is required to serve admin rpc".to_string())
+        })?;
+        let admin_rpc = r#impl::AdminRpcImpl::new(Arc::clone(&adapter), version.clone()).into_rpc();
+        let middleware =
+            tower::ServiceBuilder::new().layer(AuthLayer::new(Auth::with_api_key(token)));
+
+        let server = ServerBuilder::new()
+            .http_only()
+            .max_request_body_size(config.rpc.max_payload_size)
+            .max_response_body_size(config.rpc.max_payload_size)
+            .set_middleware(middleware)
+            .build(addr)
+            .await
+            .map_err(|e| APIError::AdminServer(e.to_string()))?;
+
+        ret.2 = Some(server.start(admin_rpc));
+    }
 
     let mut rpc = r#impl::Web3RpcImpl::new(
         Arc::clone(&adapter),
@@ -270,47 +471,120 @@ pub async fn run_jsonrpc_server<Adapter: APIAdapter + 'static>(
         r#impl::filter_module(Arc::clone(&adapter), config.web3.log_filter_max_block_range)
             .into_rpc();
     let ckb_light_client_rpc = r#impl::CkbLightClientRpcImpl::new(Arc::clone(&adapter)).into_rpc();
+    let debug_rpc = r#impl::DebugRpcImpl::new(Arc::clone(&adapter)).into_rpc();
+    let txpool_rpc = r#impl::TxPoolRpcImpl::new(Arc::clone(&adapter)).into_rpc();
 
     rpc.merge(node_rpc).unwrap();
     rpc.merge(axon_rpc).unwrap();
     rpc.merge(filter).unwrap();
     rpc.merge(ckb_light_client_rpc).unwrap();
+    rpc.merge(debug_rpc).unwrap();
+    rpc.merge(txpool_rpc).unwrap();
+
+    let auth = Auth::from_config(&config.rpc)?;
+    let rate_limiter = Arc::new(RateLimiter::new(
+        config.rpc.rate_limit.as_ref(),
+        auth.api_keys().clone(),
+        config.rpc.max_payload_size,
+    ));
+    let rate_limit = RateLimitLayer::new(Arc::clone(&rate_limiter));
+    let auth = AuthLayer::new(auth);
+    let method_filter = MethodFilter::from_config(&config.rpc);
 
     if let Some(addr) = config.rpc.http_listening_address {
-        let cors = CorsLayer::new()
-            .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-            .allow_origin(CorsAny)
-            .allow_headers([CONTENT_TYPE]);
-        let middleware = tower::ServiceBuilder::new().layer(cors);
+        let middleware = tower::ServiceBuilder::new()
+            .layer(cors_layer(&config.rpc)?)
+            .layer(auth.clone())
+            .layer(rate_limit.clone());
+
+        let methods = method_filter.restrict(rpc.clone());
 
         let server = ServerBuilder::new()
             .http_only()
             .max_request_body_size(config.rpc.max_payload_size)
             .max_response_body_size(config.rpc.max_payload_size)
             .max_connections(config.rpc.maxconn)
+            .set_batch_request_config(rate_limiter.batch_request_config())
+            .set_logger(RemoteAddrLogger::default())
             .set_middleware(middleware)
             .build(addr)
             .await
             .map_err(|e| APIError::HttpServer(e.to_string()))?;
 
-        ret.0 = Some(server.start(rpc.clone()));
+        ret.0 = Some(server.start(methods));
+    }
+
+    if config.rpc.ws_listening_address.is_some() || ipc_path.is_some() {
+        rpc.merge(ws_subscription_module(adapter).await).unwrap();
     }
 
     if let Some(addr) = config.rpc.ws_listening_address {
+        let middleware = tower::ServiceBuilder::new().layer(auth).layer(rate_limit);
+
         let server = ServerBuilder::new()
             .ws_only()
             .max_request_body_size(config.rpc.max_payload_size)
             .max_request_body_size(config.rpc.max_payload_size)
             .max_connections(config.rpc.maxconn)
+            .set_batch_request_config(rate_limiter.batch_request_config())
+            .max_subscriptions_per_connection(rate_limiter.max_subscriptions_per_connection())
             .set_id_provider(HexIdProvider::default())
+            .set_logger(RemoteAddrLogger::default())
+            .set_middleware(middleware)
             .build(addr)
             .await
             .map_err(|e| APIError::WebSocketServer(e.to_string()))?;
 
-        rpc.merge(ws_subscription_module(adapter).await).unwrap();
+        let methods = rate_limiter.limit_connections(method_filter.restrict(rpc.clone()));
+        ret.1 = Some(server.start(methods))
+    }
 
-        ret.1 = Some(server.start(rpc))
+    // The access to the socket is controlled by its file mode, so the method
+    // lists do not apply to it, the same as `geth.ipc`.
+    if let Some(path) = ipc_path {
+        let permissions = config
+            .rpc
+            .ipc_permissions
+            .unwrap_or(DEFAULT_IPC_PERMISSIONS);
+        let handle = start_ipc_server(
+            &path,
+            permissions,
+            rpc.into(),
+            config.rpc.max_payload_size,
+            rate_limiter.batch_request_config(),
+            rate_limiter.max_subscriptions_per_connection(),
+        )?;
+        ret.3 = Some(handle);
     }
 
     Ok(ret)
 }
+
+/// The CORS policy of the HTTP servers.
+pub(crate) fn cors_layer(config: &ConfigApi) -> Result<CorsLayer, APIError> {
+    Ok(CorsLayer::new()
+        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_origin(cors_origin(config.cors_origins.as_deref())?)
+        .allow_headers([
+            CONTENT_TYPE,
+            AUTHORIZATION,
+            HeaderName::from_static(API_KEY_HEADER),
+        ]))
+}
+
+/// Allows any origin if the origins are not configured or contain `*`.
+fn cors_origin(origins: Option<&[String]>) -> Result<AllowOrigin, APIError> {
+    match origins {
+        Some(origins) if !origins.iter().any(|origin| origin == "*") => {
+            let origins = origins
+                .iter()
+                .map(|origin| {
+                    HeaderValue::from_str(origin)
+                        .map_err(|_| APIError::Auth(format!("invalid cors origin {}", origin)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(AllowOrigin::list(origins))
+        }
+        _ => Ok(AllowOrigin::any()),
+    }
+}
@@ -0,0 +1,666 @@
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::hash::Hash;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use hyper::body::HttpBody;
+use hyper::header::{HeaderValue, CONTENT_TYPE, UPGRADE};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use jsonrpsee::server::logger::{
+    HttpRequest, Logger, MethodKind, SuccessOrError, TransportProtocol,
+};
+use jsonrpsee::server::{
+    AsyncMethod, BatchRequestConfig, ConnectionId, MaxResponseSize, MethodCallback, MethodResponse,
+    Methods,
+};
+use jsonrpsee::types::{ErrorObjectOwned, Id, Params};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use tower::{Layer, Service};
+
+use common_apm::metrics::api::{
+    API_REQUEST_COST_COUNTER_VEC_STATIC, API_THROTTLED_REQUEST_COUNTER_VEC_STATIC,
+};
+use common_config_parser::types::{ConfigRateLimit, ConfigTokenBucket};
+
+use crate::jsonrpc::{auth::request_token, error::RpcError};
+
+/// The default of jsonrpsee.
+const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: u32 = 1024;
+/// The full buckets are the same as the absent ones, so they are dropped at
+/// this interval.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+/// The cost of the expensive methods or namespaces, the other methods cost 1.
+const DEFAULT_METHOD_WEIGHTS: &[(&str, u64)] = &[
+    ("eth_call", 10),
+    ("eth_estimateGas", 10),
+    ("eth_createAccessList", 10),
+    ("eth_simulateV1", 20),
+    ("eth_getLogs", 20),
+    ("eth_getProof", 10),
+    ("eth_getBlockReceipts", 5),
+    ("eth_feeHistory", 5),
+    ("eth_sendRawTransactionSync", 5),
+    ("debug", 20),
+];
+
+struct TokenBucket {
+    tokens:     f64,
+    updated_at: Instant,
+}
+
+struct Buckets<K> {
+    capacity:          f64,
+    refill_per_second: f64,
+    state:             Mutex<BucketsState<K>>,
+}
+
+struct BucketsState<K> {
+    buckets:  HashMap<K, TokenBucket>,
+    swept_at: Instant,
+}
+
+impl<K: Hash + Eq> Buckets<K> {
+    fn new(config: &ConfigTokenBucket) -> Self {
+        Buckets {
+            capacity:          config.capacity as f64,
+            refill_per_second: config.refill_per_second as f64,
+            state:             Mutex::new(BucketsState {
+                buckets:  HashMap::new(),
+                swept_at: Instant::now(),
+            }),
+        }
+    }
+
+    fn refill(&self, bucket: &mut TokenBucket, now: Instant) {
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.refill_per_second).min(self.capacity);
+        bucket.updated_at = now;
+    }
+
+    fn try_acquire(&self, key: K, cost: u64, now: Instant) -> bool {
+        let mut state = self.state.lock();
+
+        if now.saturating_duration_since(state.swept_at) >= SWEEP_INTERVAL {
+            state.buckets.retain(|_, bucket| {
+                self.refill(bucket, now);
+                bucket.tokens < self.capacity
+            });
+            state.swept_at = now;
+        }
+
+        let bucket = state.buckets.entry(key).or_insert(TokenBucket {
+            tokens:     self.capacity,
+            updated_at: now,
+        });
+        self.refill(bucket, now);
+
+        let cost = cost as f64;
+        if bucket.tokens < cost {
+            return false;
+        }
+
+        bucket.tokens -= cost;
+        true
+    }
+}
+
+thread_local! {
+    /// The remote address of the WebSocket call being dispatched, set by the
+    /// [`RemoteAddrLogger`] right before the server invokes the method
+    /// callback on the same thread.
+    static CALLER: Cell<Option<IpAddr>> = Cell::new(None);
+}
+
+/// The token buckets charging the weighted cost of the requests. An HTTP
+/// request is charged to its API key if it carries one of the static keys,
+/// or to its IP otherwise, so that the JWTs, which can be issued at will,
+/// share the budget of the IP. The WebSocket handshakes are charged the same
+/// way, while the calls on a connection are charged to its IP.
+pub struct RateLimiter {
+    ip: Option<Buckets<IpAddr>>,
+    api_key: Option<Buckets<String>>,
+    weights: HashMap<String, u64>,
+    api_keys: HashSet<String>,
+    max_request_body_size: usize,
+    max_batch_size: Option<u32>,
+    max_subscriptions_per_connection: u32,
+}
+
+impl RateLimiter {
+    /// Only the configured static API keys are charged to the API key
+    /// buckets, or any client could get a fresh budget with a random key.
+    pub fn new(
+        config: Option<&ConfigRateLimit>,
+        api_keys: HashSet<String>,
+        max_request_body_size: u32,
+    ) -> Self {
+        let mut weights = DEFAULT_METHOD_WEIGHTS
+            .iter()
+            .map(|(method, weight)| (method.to_string(), *weight))
+            .collect::<HashMap<_, _>>();
+        if let Some(config) = config {
+            weights.extend(config.method_weights.clone());
+        }
+
+        RateLimiter {
+            ip: config.and_then(|c| c.ip.as_ref()).map(Buckets::new),
+            api_key: config.and_then(|c| c.api_key.as_ref()).map(Buckets::new),
+            weights,
+            api_keys,
+            max_request_body_size: max_request_body_size as usize,
+            max_batch_size: config.and_then(|c| c.max_batch_size),
+            max_subscriptions_per_connection: config
+                .and_then(|c| c.max_subscriptions_per_connection)
+                .unwrap_or(DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION),
+        }
+    }
+
+    pub fn batch_request_config(&self) -> BatchRequestConfig {
+        match self.max_batch_size {
+            Some(size) => BatchRequestConfig::Limit(size),
+            None => BatchRequestConfig::Unlimited,
+        }
+    }
+
+    pub fn max_subscriptions_per_connection(&self) -> u32 {
+        self.max_subscriptions_per_connection
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.ip.is_some() || (self.api_key.is_some() && !self.api_keys.is_empty())
+    }
+
+    fn weight(&self, method: &str) -> u64 {
+        self.weights
+            .get(method)
+            .or_else(|| {
+                method
+                    .split_once('_')
+                    .and_then(|(namespace, _)| self.weights.get(namespace))
+            })
+            .copied()
+            .unwrap_or(1)
+    }
+
+    /// Returns the total weight of the calls of a single or a batch request.
+    /// A malformed request costs 1, and is rejected by the server later.
+    fn request_cost(&self, body: &[u8]) -> u64 {
+        #[derive(Deserialize)]
+        struct Call {
+            method: Option<String>,
+        }
+
+        let cost = |call: Call| call.method.map(|m| self.weight(&m)).unwrap_or(1);
+
+        match body.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'[') => serde_json::from_slice::<Vec<Call>>(body)
+                .map(|calls| calls.into_iter().map(cost).fold(0, u64::saturating_add))
+                .unwrap_or(1),
+            _ => serde_json::from_slice::<Call>(body).map(cost).unwrap_or(1),
+        }
+    }
+
+    fn acquire_request(
+        &self,
+        ip: Option<IpAddr>,
+        api_key: Option<String>,
+        cost: u64,
+        is_ws: bool,
+    ) -> bool {
+        let now = Instant::now();
+
+        let acquired = match (api_key, &self.api_key, ip, &self.ip) {
+            (Some(key), Some(buckets), ..) if self.api_keys.contains(&key) => {
+                let acquired = buckets.try_acquire(key, cost, now);
+                if !acquired {
+                    API_THROTTLED_REQUEST_COUNTER_VEC_STATIC.api_key.inc();
+                }
+                acquired
+            }
+            (_, _, Some(ip), Some(buckets)) => {
+                let acquired = buckets.try_acquire(ip, cost, now);
+                if !acquired {
+                    API_THROTTLED_REQUEST_COUNTER_VEC_STATIC.ip.inc();
+                }
+                acquired
+            }
+            _ => true,
+        };
+
+        if acquired {
+            if is_ws {
+                API_REQUEST_COST_COUNTER_VEC_STATIC.ws.inc_by(cost as f64);
+            } else {
+                API_REQUEST_COST_COUNTER_VEC_STATIC.http.inc_by(cost as f64);
+            }
+        }
+
+        acquired
+    }
+
+    fn acquire_call(&self, ip: Option<IpAddr>, cost: u64) -> bool {
+        let (Some(ip), Some(buckets)) = (ip, &self.ip) else {
+            return true;
+        };
+
+        if !buckets.try_acquire(ip, cost, Instant::now()) {
+            API_THROTTLED_REQUEST_COUNTER_VEC_STATIC.ip.inc();
+            return false;
+        }
+
+        API_REQUEST_COST_COUNTER_VEC_STATIC.ws.inc_by(cost as f64);
+        true
+    }
+
+    /// Wraps the asynchronous methods and the subscriptions to charge the
+    /// calls on every connection to its IP. It is only meant for the
+    /// WebSocket server logging with the [`RemoteAddrLogger`], since the HTTP
+    /// requests are charged as a whole.
+    pub fn limit_connections(self: &Arc<Self>, methods: Methods) -> Methods {
+        if self.ip.is_none() {
+            return methods;
+        }
+
+        let mut limited = Methods::new();
+        for name in methods.method_names() {
+            let callback = match methods.method(name).cloned().expect("listed method") {
+                MethodCallback::Async(method) => {
+                    let limiter = Arc::clone(self);
+                    let cost = self.weight(name);
+                    let method: AsyncMethod<'static> = Arc::new(
+                        move |id: Id<'static>,
+                              params: Params<'static>,
+                              conn_id: ConnectionId,
+                              max_response_size: MaxResponseSize|
+                              -> Pin<Box<dyn Future<Output = MethodResponse> + Send>> {
+                            if limiter.acquire_call(CALLER.with(Cell::take), cost) {
+                                return method(id, params, conn_id, max_response_size);
+                            }
+
+                            let err = ErrorObjectOwned::from(RpcError::RateLimited);
+                            Box::pin(async move { MethodResponse::error(id, err) })
+                        },
+                    );
+                    MethodCallback::Async(method)
+                }
+                MethodCallback::Subscription(method) => {
+                    let limiter = Arc::clone(self);
+                    let cost = self.weight(name);
+                    MethodCallback::Subscription(Arc::new(move |id, params, sink, state| {
+                        if limiter.acquire_call(CALLER.with(Cell::take), cost) {
+                            return method(id, params, sink, state);
+                        }
+
+                        // The server expects the response to a subscription call in
+                        // the sink, the same as a rejected subscription.
+                        let err = ErrorObjectOwned::from(RpcError::RateLimited);
+                        let resp = MethodResponse::error(id, err);
+                        Box::pin(async move {
+                            let _ = sink.send(resp.result.clone()).await;
+                            Ok(resp)
+                        })
+                    }))
+                }
+                callback => callback,
+            };
+
+            limited
+                .verify_and_insert(name, callback)
+                .expect("method names are unique");
+        }
+
+        limited
+    }
+}
+
+/// The slot for the remote address of a request, which is only known to the
+/// server and filled by the [`RemoteAddrLogger`].
+#[derive(Clone, Default)]
+struct RemoteAddr(Arc<Mutex<Option<IpAddr>>>);
+
+/// A logger recording the remote address of every HTTP request and WebSocket
+/// handshake for the [`RateLimitService`], since the address is not exposed
+/// to the middlewares, and of every WebSocket call for the methods wrapped by
+/// [`RateLimiter::limit_connections`], since the calls only know the
+/// connection id.
+#[derive(Default)]
+pub struct RemoteAddrLogger {
+    ip: Mutex<Option<IpAddr>>,
+}
+
+/// The server clones the logger for every connection before it connects, and
+/// the one of a WebSocket connection again for its task after the handshake,
+/// so a clone carries the address recorded so far.
+impl Clone for RemoteAddrLogger {
+    fn clone(&self) -> Self {
+        RemoteAddrLogger {
+            ip: Mutex::new(*self.ip.lock()),
+        }
+    }
+}
+
+impl Logger for RemoteAddrLogger {
+    type Instant = ();
+
+    fn on_connect(&self, remote_addr: SocketAddr, request: &HttpRequest, _t: TransportProtocol) {
+        *self.ip.lock() = Some(remote_addr.ip());
+        record_remote_addr(request, remote_addr.ip());
+    }
+
+    fn on_request(&self, _t: TransportProtocol) -> Self::Instant {}
+
+    fn on_call(&self, _: &str, _: Params, _: MethodKind, t: TransportProtocol) {
+        if let TransportProtocol::WebSocket = t {
+            CALLER.with(|caller| caller.set(*self.ip.lock()));
+        }
+    }
+
+    fn on_result(&self, _: &str, _: SuccessOrError, _: Self::Instant, _t: TransportProtocol) {}
+
+    fn on_response(&self, _: &str, _: Self::Instant, _t: TransportProtocol) {}
+
+    fn on_disconnect(&self, _: SocketAddr, _t: TransportProtocol) {}
+}
+
+/// Records the remote address of the request for the [`RateLimitService`],
+/// for the servers which know the address of the connection.
+pub fn record_remote_addr<B>(request: &Request<B>, ip: IpAddr) {
+    if let Some(slot) = request.extensions().get::<RemoteAddr>() {
+        *slot.0.lock() = Some(ip);
+    }
+}
+
+/// A tower layer rejecting the requests over the budget with
+/// `429 Too Many Requests`. It must be the innermost layer, so that the
+/// [`RemoteAddrLogger`] or [`record_remote_addr`] has recorded the remote
+/// address once the server service is called, and before the returned future
+/// is polled.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitLayer {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        RateLimitLayer { limiter }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: Arc::clone(&self.limiter),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner:   S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Error: From<hyper::Error> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+    type Response = Response<Body>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if !self.limiter.is_enabled() {
+            return Box::pin(self.inner.call(req));
+        }
+
+        // The service polled ready is the one to call.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiter = Arc::clone(&self.limiter);
+
+        Box::pin(async move {
+            let is_ws = req.headers().contains_key(UPGRADE);
+            let (mut req, cost) = if req.method() == Method::POST && !is_ws {
+                let (parts, body) = req.into_parts();
+                let Some(body) = read_body(body, limiter.max_request_body_size).await? else {
+                    return Ok(status_response(StatusCode::PAYLOAD_TOO_LARGE));
+                };
+                let cost = limiter.request_cost(&body);
+                (Request::from_parts(parts, Body::from(body)), cost)
+            } else {
+                (req, 1)
+            };
+
+            let api_key = request_token(req.headers()).map(str::to_string);
+            let remote_addr = RemoteAddr::default();
+            req.extensions_mut().insert(remote_addr.clone());
+
+            // Nothing is processed until the future is polled.
+            let resp = inner.call(req);
+            let ip = *remote_addr.0.lock();
+
+            if !limiter.acquire_request(ip, api_key, cost, is_ws) {
+                return Ok(throttled_response());
+            }
+
+            resp.await
+        })
+    }
+}
+
+/// Returns `None` if the body is larger than the limit.
+async fn read_body(mut body: Body, limit: usize) -> Result<Option<Vec<u8>>, hyper::Error> {
+    let mut buf = Vec::new();
+
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if buf.len() + chunk.len() > limit {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk);
+    }
+
+    Ok(Some(buf))
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut resp = Response::new(Body::empty());
+    *resp.status_mut() = status;
+    resp
+}
+
+fn throttled_response() -> Response<Body> {
+    let err = ErrorObjectOwned::from(RpcError::RateLimited);
+    let body = MethodResponse::error(Id::Null, err).result;
+
+    let mut resp = Response::new(Body::from(body));
+    *resp.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+    resp.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpsee::{core::SubscriptionResult, RpcModule};
+    use protocol::tokio;
+
+    use super::*;
+
+    fn limit(capacity: u64, refill_per_second: u64) -> ConfigRateLimit {
+        ConfigRateLimit {
+            ip: Some(ConfigTokenBucket {
+                capacity,
+                refill_per_second,
+            }),
+            api_key: None,
+            method_weights: HashMap::from([("eth_getLogs".to_string(), 50)]),
+            max_batch_size: None,
+            max_subscriptions_per_connection: None,
+        }
+    }
+
+    #[test]
+    fn test_request_cost() {
+        let limiter = RateLimiter::new(Some(&limit(100, 10)), HashSet::new(), 1024);
+
+        let single = br#"{"jsonrpc":"2.0","id":1,"method":"eth_blockNumber","params":[]}"#;
+        assert_eq!(limiter.request_cost(single), 1);
+
+        let batch = br#" [
+            {"jsonrpc":"2.0","id":1,"method":"eth_call","params":[]},
+            {"jsonrpc":"2.0","id":2,"method":"eth_getLogs","params":[]},
+            {"jsonrpc":"2.0","id":3,"method":"debug_traceTransaction","params":[]}
+        ]"#;
+        assert_eq!(limiter.request_cost(batch), 10 + 50 + 20);
+        assert_eq!(limiter.request_cost(b"not json"), 1);
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let buckets = Buckets::new(&ConfigTokenBucket {
+            capacity:          10,
+            refill_per_second: 5,
+        });
+        let ip: IpAddr = [127, 0, 0, 1].into();
+        let now = Instant::now();
+
+        assert!(buckets.try_acquire(ip, 10, now));
+        assert!(!buckets.try_acquire(ip, 1, now));
+        assert!(buckets.try_acquire([127, 0, 0, 2].into(), 1, now));
+        assert!(buckets.try_acquire(ip, 5, now + Duration::from_secs(1)));
+        assert!(!buckets.try_acquire(ip, 11, now + Duration::from_secs(10)));
+        assert!(buckets.try_acquire(ip, 10, now + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_api_key_budget() {
+        let mut config = limit(1, 0);
+        config.api_key = Some(ConfigTokenBucket {
+            capacity:          100,
+            refill_per_second: 0,
+        });
+        let ip = Some([127, 0, 0, 1].into());
+
+        let open = RateLimiter::new(Some(&config), HashSet::new(), 1024);
+        assert!(open.acquire_request(ip, Some("key".to_string()), 1, false));
+        assert!(!open.acquire_request(ip, Some("key".to_string()), 1, false));
+
+        let keyed = RateLimiter::new(Some(&config), HashSet::from(["key".to_string()]), 1024);
+        assert!(keyed.acquire_request(ip, Some("key".to_string()), 50, false));
+        assert!(keyed.acquire_request(ip, Some("jwt".to_string()), 1, false));
+        assert!(!keyed.acquire_request(ip, None, 1, false));
+        assert!(!keyed.acquire_request(ip, Some("jwt".to_string()), 1, false));
+        assert!(keyed.acquire_request(ip, Some("key".to_string()), 50, false));
+    }
+
+    #[test]
+    fn test_remote_addr_logger() {
+        let logger = RemoteAddrLogger::default();
+        let conn = logger.clone();
+        let remote_addr = RemoteAddr::default();
+        let mut req = Request::new(Body::empty());
+        req.extensions_mut().insert(remote_addr.clone());
+
+        let ip: IpAddr = [127, 0, 0, 1].into();
+        conn.on_connect((ip, 8000).into(), &req, TransportProtocol::WebSocket);
+        assert_eq!(*remote_addr.0.lock(), Some(ip));
+        assert_eq!(*logger.ip.lock(), None);
+
+        let params = Params::new(None);
+        conn.clone().on_call(
+            "eth_blockNumber",
+            params.clone(),
+            MethodKind::MethodCall,
+            TransportProtocol::WebSocket,
+        );
+        assert_eq!(CALLER.with(Cell::take), Some(ip));
+
+        conn.on_call(
+            "eth_blockNumber",
+            params,
+            MethodKind::MethodCall,
+            TransportProtocol::Http,
+        );
+        assert_eq!(CALLER.with(Cell::take), None);
+    }
+
+    #[tokio::test]
+    async fn test_limit_connections() {
+        let limiter = Arc::new(RateLimiter::new(Some(&limit(3, 0)), HashSet::new(), 1024));
+        let mut module = RpcModule::new(());
+        module
+            .register_async_method("eth_blockNumber", |_, _| async { 1u64 })
+            .unwrap();
+        module
+            .register_subscription(
+                "eth_subscribe",
+                "eth_subscription",
+                "eth_unsubscribe",
+                |_, pending, _| async move {
+                    pending.accept().await?;
+                    SubscriptionResult::Ok(())
+                },
+            )
+            .unwrap();
+        let methods = limiter.limit_connections(module.into());
+
+        let logger = RemoteAddrLogger::default();
+        let req = Request::new(Body::empty());
+        logger.on_connect(
+            ([127, 0, 0, 1], 8000).into(),
+            &req,
+            TransportProtocol::WebSocket,
+        );
+
+        let call = r#"{"jsonrpc":"2.0","id":1,"method":"eth_blockNumber","params":[]}"#;
+        let subscribe = r#"{"jsonrpc":"2.0","id":2,"method":"eth_subscribe","params":[]}"#;
+        for (body, success) in [
+            (call, true),
+            (subscribe, true),
+            (call, true),
+            (subscribe, false),
+        ] {
+            logger.on_call(
+                "",
+                Params::new(None),
+                MethodKind::MethodCall,
+                TransportProtocol::WebSocket,
+            );
+            let (resp, _) = methods.raw_json_request(body, 1).await.unwrap();
+            assert_eq!(resp.is_success(), success, "{}", body);
+        }
+
+        // Another connection from the same IP shares the budget.
+        let other = RemoteAddrLogger::default();
+        other.on_connect(
+            ([127, 0, 0, 1], 8001).into(),
+            &req,
+            TransportProtocol::WebSocket,
+        );
+        other.on_call(
+            "",
+            Params::new(None),
+            MethodKind::MethodCall,
+            TransportProtocol::WebSocket,
+        );
+        let (resp, _) = methods.raw_json_request(call, 1).await.unwrap();
+        assert!(!resp.is_success());
+    }
+}
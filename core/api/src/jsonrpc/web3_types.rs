@@ -1,13 +1,18 @@
-use std::{fmt, str::FromStr};
+use std::{collections::BTreeMap, fmt, str::FromStr, time::Duration};
 
 use either::Either;
 use serde::de::{Error, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use strum::IntoEnumIterator;
+
+use common_config_parser::types::spec::HardforkName;
 
 use protocol::codec::ProtocolCodec;
+use protocol::traits::{ConnectedPeer, PeerDirection};
 use protocol::types::{
-    AccessList, Block, Bloom, Bytes, Hash, Header, Hex, Public, Receipt, SignedTransaction, H160,
-    H256, H64, MAX_PRIORITY_FEE_PER_GAS, U256, U64,
+    AccessList, Block, Bloom, Bytes, CallTracerConfig, Hash, Header, Hex, Proof, Public, Receipt,
+    SignedTransaction, StateOverride, StructLoggerConfig, TraceResult, Tracer, H160, H256, H64,
+    MAX_PRIORITY_FEE_PER_GAS, U256, U64,
 };
 
 pub const EMPTY_UNCLE_HASH: H256 = H256([
@@ -15,6 +20,8 @@ pub const EMPTY_UNCLE_HASH: H256 = H256([
     0xd3, 0x12, 0x45, 0x1b, 0x94, 0x8a, 0x74, 0x13, 0xf0, 0xa1, 0x42, 0xfd, 0x40, 0xd4, 0x93, 0x47,
 ]);
 
+pub const DEFAULT_TRACE_TIMEOUT: Duration = Duration::from_secs(5);
+
 use core_consensus::SyncStatus as InnerSyncStatus;
 
 #[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -206,7 +213,7 @@ impl Web3Receipt {
                 block_hash:        receipt.block_hash,
                 transaction_hash:  receipt.tx_hash,
                 transaction_index: Some(receipt.tx_index.into()),
-                log_index:         idx.into(),
+                log_index:         (receipt.log_index as usize + idx).into(),
                 removed:           false,
             })
             .collect::<Vec<_>>();
@@ -322,6 +329,104 @@ pub struct Web3CallRequest {
     pub max_priority_fee_per_gas: Option<U256>,
 }
 
+/// The overrides of the block context which an RPC call is executed in.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Web3BlockOverrides {
+    pub number:           Option<U64>,
+    pub time:             Option<U64>,
+    pub gas_limit:        Option<U64>,
+    pub fee_recipient:    Option<H160>,
+    pub base_fee_per_gas: Option<U64>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Web3SimulatePayload {
+    pub block_state_calls: Vec<Web3BlockStateCall>,
+}
+
+/// The calls simulated in a block, with the overrides applied before them.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Web3BlockStateCall {
+    pub block_overrides: Option<Web3BlockOverrides>,
+    pub state_overrides: Option<StateOverride>,
+    #[serde(default)]
+    pub calls:           Vec<Web3CallRequest>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3SimulatedBlock {
+    pub number:           U64,
+    pub timestamp:        U64,
+    pub gas_limit:        U64,
+    pub gas_used:         U64,
+    pub fee_recipient:    H160,
+    pub base_fee_per_gas: U64,
+    pub calls:            Vec<Web3SimulatedCall>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3SimulatedCall {
+    pub return_data: Hex,
+    pub logs:        Vec<Web3Log>,
+    pub gas_used:    U64,
+    pub status:      U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error:       Option<Web3CallError>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Web3CallError {
+    pub code:    i32,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data:    Option<Hex>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3AccessList {
+    pub access_list: AccessList,
+    pub gas_used:    U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error:       Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct TxPoolStatus {
+    pub pending: U64,
+    pub queued:  U64,
+}
+
+/// The transactions in the mempool grouped by sender and nonce, the output of
+/// `txpool_content` and `txpool_inspect`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TxPoolContent<T> {
+    pub pending: BTreeMap<H160, BTreeMap<u64, T>>,
+    pub queued:  BTreeMap<H160, BTreeMap<u64, T>>,
+}
+
+impl<T> Default for TxPoolContent<T> {
+    fn default() -> Self {
+        TxPoolContent {
+            pending: BTreeMap::new(),
+            queued:  BTreeMap::new(),
+        }
+    }
+}
+
+/// The transactions of a sender in the mempool grouped by nonce, the output
+/// of `txpool_contentFrom`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TxPoolContentFrom<T> {
+    pub pending: BTreeMap<u64, T>,
+    pub queued:  BTreeMap<u64, T>,
+}
+
 #[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum BlockId {
     Num(U64),
@@ -330,16 +435,10 @@ pub enum BlockId {
     Latest,
     Earliest,
     Pending,
-}
-
-impl From<BlockId> for Option<u64> {
-    fn from(id: BlockId) -> Self {
-        match id {
-            BlockId::Num(num) => Some(num.low_u64()),
-            BlockId::Earliest => Some(0),
-            _ => None,
-        }
-    }
+    /// The latest block with a stored aggregated proof. Overlord has instant
+    /// finality, so `safe` and `finalized` are the same block.
+    Safe,
+    Finalized,
 }
 
 impl<'a> Deserialize<'a> for BlockId {
@@ -362,6 +461,8 @@ impl Serialize for BlockId {
             BlockId::Latest => serializer.serialize_str("latest"),
             BlockId::Earliest => serializer.serialize_str("earliest"),
             BlockId::Pending => serializer.serialize_str("pending"),
+            BlockId::Safe => serializer.serialize_str("safe"),
+            BlockId::Finalized => serializer.serialize_str("finalized"),
         }
     }
 }
@@ -441,6 +542,8 @@ impl<'a> Visitor<'a> for BlockIdVisitor {
             "latest" => Ok(BlockId::Latest),
             "earliest" => Ok(BlockId::Earliest),
             "pending" => Ok(BlockId::Pending),
+            "safe" => Ok(BlockId::Safe),
+            "finalized" => Ok(BlockId::Finalized),
             _ if value.starts_with("0x") => u64::from_str_radix(&value[2..], 16)
                 .map(|n| BlockId::Num(U64::from(n)))
                 .map_err(|e| Error::custom(format!("Invalid block number: {}", e))),
@@ -830,6 +933,155 @@ pub enum HardforkStatus {
     Enabled,
 }
 
+/// The hardforks newly determined or enabled by a block, the notification of
+/// the `hardfork` kind of `axon_subscribe`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HardforkNotification {
+    pub status:       HardforkStatus,
+    /// The block since which the hardforks are enabled.
+    pub block_number: U64,
+    pub hardforks:    Vec<HardforkName>,
+}
+
+impl HardforkNotification {
+    pub fn new(status: HardforkStatus, block_number: u64, flags: H256) -> Self {
+        let hardforks = HardforkName::iter()
+            .filter(|name| flags & H256::from_low_u64_be((*name as u64).to_be()) != H256::zero())
+            .collect();
+
+        HardforkNotification {
+            status,
+            block_number: block_number.into(),
+            hardforks,
+        }
+    }
+}
+
+/// A finalized block together with the aggregated BLS proof of it, the output
+/// of `axon_getFinalizedBlock`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FinalizedBlock {
+    pub block: Block,
+    pub proof: Proof,
+}
+
+/// A connected peer, the output of `admin_peers`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerInfo {
+    pub peer_id:      String,
+    pub multiaddr:    String,
+    /// `inbound` or `outbound`
+    pub direction:    String,
+    /// The round trip time of the latest ping in milliseconds.
+    pub latency:      Option<u64>,
+    pub is_consensus: bool,
+}
+
+impl From<ConnectedPeer> for PeerInfo {
+    fn from(peer: ConnectedPeer) -> Self {
+        PeerInfo {
+            peer_id:      peer.peer_id,
+            multiaddr:    peer.multiaddr,
+            direction:    match peer.direction {
+                PeerDirection::Inbound => "inbound".to_string(),
+                PeerDirection::Outbound => "outbound".to_string(),
+            },
+            latency:      peer.latency.map(|latency| latency.as_millis() as u64),
+            is_consensus: peer.is_consensus,
+        }
+    }
+}
+
+/// The output of `admin_nodeInfo`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    pub version:        String,
+    pub peer_id:        String,
+    pub listen_addrs:   Vec<String>,
+    pub chain_id:       U64,
+    pub latest_block:   U64,
+    pub inbound_peers:  usize,
+    pub outbound_peers: usize,
+    pub banned_ips:     usize,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceConfig {
+    /// The name of the tracer, the struct logger is used if it is empty.
+    pub tracer:             Option<String>,
+    #[serde(default)]
+    pub tracer_config:      TracerOptions,
+    #[serde(default)]
+    pub disable_stack:      bool,
+    #[serde(default)]
+    pub disable_storage:    bool,
+    #[serde(default)]
+    pub enable_memory:      bool,
+    #[serde(default)]
+    pub enable_return_data: bool,
+    /// The timeout in the Go duration format, such as `"10s"` or `"500ms"`.
+    pub timeout:            Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TracerOptions {
+    #[serde(default)]
+    pub only_top_call: bool,
+    #[serde(default)]
+    pub with_log:      bool,
+}
+
+impl TraceConfig {
+    /// Returns `None` if the tracer is not supported.
+    pub fn tracer(&self) -> Option<Tracer> {
+        match self.tracer.as_deref() {
+            None | Some("") | Some("structLogger") => {
+                Some(Tracer::StructLogger(StructLoggerConfig {
+                    disable_stack:      self.disable_stack,
+                    disable_storage:    self.disable_storage,
+                    enable_memory:      self.enable_memory,
+                    enable_return_data: self.enable_return_data,
+                }))
+            }
+            Some("callTracer") => Some(Tracer::CallTracer(CallTracerConfig {
+                only_top_call: self.tracer_config.only_top_call,
+                with_log:      self.tracer_config.with_log,
+            })),
+            _ => None,
+        }
+    }
+
+    /// Returns `None` if the timeout is invalid.
+    pub fn timeout(&self) -> Option<Duration> {
+        let timeout = match self.timeout.as_deref() {
+            Some(timeout) => timeout,
+            None => return Some(DEFAULT_TRACE_TIMEOUT),
+        };
+
+        let (num, unit) = timeout.split_at(timeout.find(|c: char| !c.is_ascii_digit())?);
+        let num = num.parse::<u64>().ok()?;
+
+        match unit {
+            "ms" => Some(Duration::from_millis(num)),
+            "s" => Some(Duration::from_secs(num)),
+            "m" => Some(Duration::from_secs(num.checked_mul(60)?)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTraceResult {
+    pub tx_hash: H256,
+    pub result:  TraceResult,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -872,4 +1124,132 @@ mod tests {
         );
         assert_eq!(tx_json["v"], "0x25");
     }
+
+    #[test]
+    fn test_trace_config_json() {
+        let config: TraceConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.tracer(), Some(Tracer::default()));
+
+        let config: TraceConfig = serde_json::from_str(
+            r#"{"tracer":"callTracer","tracerConfig":{"onlyTopCall":true,"withLog":true}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.tracer(),
+            Some(Tracer::CallTracer(CallTracerConfig {
+                only_top_call: true,
+                with_log:      true,
+            }))
+        );
+
+        let config: TraceConfig = serde_json::from_str(r#"{"tracer":"4byteTracer"}"#).unwrap();
+        assert_eq!(config.tracer(), None);
+    }
+
+    #[test]
+    fn test_trace_config_timeout() {
+        let mut config = TraceConfig::default();
+        assert_eq!(config.timeout(), Some(DEFAULT_TRACE_TIMEOUT));
+
+        config.timeout = Some("500ms".to_string());
+        assert_eq!(config.timeout(), Some(Duration::from_millis(500)));
+        config.timeout = Some("10s".to_string());
+        assert_eq!(config.timeout(), Some(Duration::from_secs(10)));
+        config.timeout = Some("2m".to_string());
+        assert_eq!(config.timeout(), Some(Duration::from_secs(120)));
+
+        config.timeout = Some("10".to_string());
+        assert_eq!(config.timeout(), None);
+        config.timeout = Some("s".to_string());
+        assert_eq!(config.timeout(), None);
+        config.timeout = Some("1h".to_string());
+        assert_eq!(config.timeout(), None);
+    }
+
+    #[test]
+    fn test_call_overrides_json() {
+        let state_override: StateOverride = serde_json::from_str(
+            r#"{
+                "0x0000000000000000000000000000000000000001": {
+                    "balance": "0x10",
+                    "nonce": "0x1",
+                    "code": "0x6000",
+                    "stateDiff": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":
+                        "0x0000000000000000000000000000000000000000000000000000000000000002"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let account = state_override.get(&H160::from_low_u64_be(1)).unwrap();
+        assert_eq!(account.balance, Some(16.into()));
+        assert_eq!(account.nonce, Some(1.into()));
+        assert_eq!(account.code, Some(Hex::encode([0x60, 0x00])));
+        assert_eq!(account.state, None);
+        assert_eq!(
+            account
+                .state_diff
+                .as_ref()
+                .unwrap()
+                .get(&H256::from_low_u64_be(1)),
+            Some(&H256::from_low_u64_be(2))
+        );
+
+        let overrides: Web3BlockOverrides =
+            serde_json::from_str(r#"{"number":"0x10","time":"0x20","baseFeePerGas":"0x1"}"#)
+                .unwrap();
+        assert_eq!(overrides.number, Some(16.into()));
+        assert_eq!(overrides.time, Some(32.into()));
+        assert_eq!(overrides.base_fee_per_gas, Some(1.into()));
+        assert!(serde_json::from_str::<Web3BlockOverrides>(r#"{"random":"0x1"}"#).is_err());
+    }
+
+    #[test]
+    fn test_block_id_tags_json() {
+        for (tag, id) in [
+            ("latest", BlockId::Latest),
+            ("earliest", BlockId::Earliest),
+            ("pending", BlockId::Pending),
+            ("safe", BlockId::Safe),
+            ("finalized", BlockId::Finalized),
+        ] {
+            let json = format!("\"{}\"", tag);
+            assert_eq!(serde_json::from_str::<BlockId>(&json).unwrap(), id);
+            assert_eq!(serde_json::to_string(&id).unwrap(), json);
+        }
+        assert!(serde_json::from_str::<BlockId>(r#""unsafe""#).is_err());
+    }
+
+    #[test]
+    fn test_peer_info_json() {
+        let peer = ConnectedPeer {
+            peer_id:      "QmNk6bBwkLPuqnsrtxpp819XLZY3ymgjs3p1nKtxBVgqxj".to_string(),
+            multiaddr:    "/ip4/127.0.0.1/tcp/8001".to_string(),
+            direction:    PeerDirection::Outbound,
+            latency:      Some(Duration::from_micros(12_500)),
+            is_consensus: true,
+        };
+        let json = serde_json::to_value(PeerInfo::from(peer)).unwrap();
+
+        assert_eq!(json["direction"], "outbound");
+        assert_eq!(json["latency"], 12);
+        assert_eq!(json["isConsensus"], true);
+    }
+
+    #[test]
+    fn test_hardfork_notification_json() {
+        let flags = H256::from_low_u64_be(
+            ((HardforkName::Andromeda as u64) | (HardforkName::Antlia as u64)).to_be(),
+        );
+        let notification = HardforkNotification::new(HardforkStatus::Determined, 100, flags);
+        let json = serde_json::to_value(notification).unwrap();
+
+        assert_eq!(json["status"], "determined");
+        assert_eq!(json["blockNumber"], "0x64");
+        assert_eq!(
+            json["hardforks"],
+            serde_json::json!(["Andromeda", "Antlia"])
+        );
+    }
 }
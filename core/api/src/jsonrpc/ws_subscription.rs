@@ -6,6 +6,7 @@ use std::{
     time::Duration,
 };
 
+use jsonrpsee::core::{server::TrySendError, SubscriptionResult};
 use jsonrpsee::server::{
     IdProvider, IntoSubscriptionCloseResponse, PendingSubscriptionSink, RpcModule,
     SubscriptionMessage, SubscriptionSink,
@@ -14,14 +15,19 @@ use jsonrpsee::types::{error::ErrorCode, params::Params, ErrorObjectOwned, Subsc
 use serde::{Deserialize, Serialize};
 
 use core_consensus::SYNC_STATUS;
+use core_executor::system_contract::metadata::{MetadataEvent, METADATA_EVENTS};
+use protocol::tokio::sync::broadcast::{self, error::RecvError};
 use protocol::tokio::sync::mpsc::{channel, Receiver, Sender};
 use protocol::tokio::{self, select, time::interval};
 use protocol::traits::{APIAdapter, Context};
-use protocol::types::{BigEndianHash, Hash, Hex, H160, H256, U256};
+use protocol::types::{BigEndianHash, Hash, Hex, Proof, SignedTransaction, H160, H256, U256};
 
 use crate::jsonrpc::{
     r#impl::from_receipt_to_web3_log,
-    web3_types::{MultiNestType, MultiType, Web3Header, Web3SyncStatus},
+    web3_types::{
+        HardforkNotification, HardforkStatus, MultiNestType, MultiType, Web3Header, Web3SyncStatus,
+        Web3Transaction,
+    },
 };
 
 pub async fn ws_subscription_module<Adapter>(adapter: Arc<Adapter>) -> RpcModule<Sender<RawHub>>
@@ -42,6 +48,13 @@ where
         subscription_callback,
     )
     .unwrap();
+    rpc.register_subscription(
+        "axon_subscribe",
+        "axon_subscription",
+        "axon_unsubscribe",
+        axon_subscription_callback,
+    )
+    .unwrap();
     rpc
 }
 
@@ -50,7 +63,23 @@ async fn subscription_callback(
     sink: PendingSubscriptionSink,
     ctx: Arc<Sender<RawHub>>,
 ) -> impl IntoSubscriptionCloseResponse {
-    match Type::try_from(params) {
+    register_hub(Type::try_from(params), sink, ctx).await
+}
+
+async fn axon_subscription_callback(
+    params: Params<'static>,
+    sink: PendingSubscriptionSink,
+    ctx: Arc<Sender<RawHub>>,
+) -> impl IntoSubscriptionCloseResponse {
+    register_hub(Type::try_from_axon(params), sink, ctx).await
+}
+
+async fn register_hub(
+    type_: Result<Type, ErrorObjectOwned>,
+    sink: PendingSubscriptionSink,
+    ctx: Arc<Sender<RawHub>>,
+) -> SubscriptionResult {
+    match type_ {
         Ok(type_) => {
             let raw_hub = RawHub {
                 typ:  type_,
@@ -70,12 +99,23 @@ async fn subscription_callback(
 }
 
 pub struct Subscription<Adapter> {
-    log_hubs:       Vec<Hub<LoggerFilter>>,
-    header_hubs:    Vec<Hub<()>>,
-    sync_hubs:      Vec<Hub<()>>,
-    adapter:        Arc<Adapter>,
-    current_number: u64,
-    recv:           Receiver<RawHub>,
+    log_hubs:        Vec<Hub<LoggerFilter>>,
+    header_hubs:     Vec<Hub<()>>,
+    sync_hubs:       Vec<Hub<()>>,
+    // The filter is whether to send the full transactions instead of the hashes
+    pending_tx_hubs: Vec<Hub<bool>>,
+    // Only subscribe to the mempool while there are pending transaction hubs
+    new_txs:         Option<broadcast::Receiver<SignedTransaction>>,
+    proof_hubs:      Vec<Hub<()>>,
+    // Only subscribe to the proofs while there are proof hubs
+    new_proofs:      Option<broadcast::Receiver<Proof>>,
+    epoch_hubs:      Vec<Hub<()>>,
+    hardfork_hubs:   Vec<Hub<()>>,
+    // Only subscribe to the metadata while there are epoch or hardfork hubs
+    metadata_events: Option<broadcast::Receiver<MetadataEvent>>,
+    adapter:         Arc<Adapter>,
+    current_number:  u64,
+    recv:            Receiver<RawHub>,
 }
 
 impl<Adapter> Subscription<Adapter>
@@ -93,6 +133,13 @@ where
             log_hubs: Vec::new(),
             header_hubs: Vec::new(),
             sync_hubs: Vec::new(),
+            pending_tx_hubs: Vec::new(),
+            new_txs: None,
+            proof_hubs: Vec::new(),
+            new_proofs: None,
+            epoch_hubs: Vec::new(),
+            hardfork_hubs: Vec::new(),
+            metadata_events: None,
             adapter,
             current_number: latest.number,
             recv,
@@ -103,6 +150,14 @@ where
         self.header_hubs.retain(|hub| !hub.sink.is_closed());
         self.sync_hubs.retain(|hub| !hub.sink.is_closed());
         self.log_hubs.retain(|hub| !hub.sink.is_closed());
+        self.pending_tx_hubs.retain(|hub| !hub.sink.is_closed());
+        if self.pending_tx_hubs.is_empty() {
+            self.new_txs = None;
+        }
+        self.proof_hubs.retain(|hub| !hub.sink.is_closed());
+        self.epoch_hubs.retain(|hub| !hub.sink.is_closed());
+        self.hardfork_hubs.retain(|hub| !hub.sink.is_closed());
+        self.release_axon_receivers();
 
         let latest_block = self
             .adapter
@@ -209,6 +264,83 @@ where
         self.current_number = latest_header_number;
     }
 
+    fn notify_pending_tx(&mut self, stx: SignedTransaction) {
+        let hash_msg = SubscriptionMessage::from_json(&stx.transaction.hash).unwrap();
+        let full_msg = if self.pending_tx_hubs.iter().any(|hub| hub.filter) {
+            Some(SubscriptionMessage::from_json(&Web3Transaction::from(stx)).unwrap())
+        } else {
+            None
+        };
+
+        // The sink buffer is bounded, a subscriber that can not keep up is dropped
+        // rather than blocking the others.
+        self.pending_tx_hubs.retain_mut(|hub| {
+            let msg = match (&full_msg, hub.filter) {
+                (Some(msg), true) => msg.clone(),
+                _ => hash_msg.clone(),
+            };
+
+            match hub.sink.try_send(msg) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    log::warn!(
+                        "[api] drop the slow pending transaction subscriber {:?}",
+                        hub.sink.subscription_id()
+                    );
+                    false
+                }
+                Err(TrySendError::Closed(_)) => false,
+            }
+        });
+
+        if self.pending_tx_hubs.is_empty() {
+            self.new_txs = None;
+        }
+    }
+
+    fn notify_proof(&mut self, proof: Proof) {
+        let msg = SubscriptionMessage::from_json(&proof).unwrap();
+        send_to_hubs(&mut self.proof_hubs, &msg);
+        self.release_axon_receivers();
+    }
+
+    fn notify_metadata_event(&mut self, event: MetadataEvent) {
+        match event {
+            MetadataEvent::NewEpoch(metadata) => {
+                let msg = SubscriptionMessage::from_json(&metadata).unwrap();
+                send_to_hubs(&mut self.epoch_hubs, &msg);
+            }
+            MetadataEvent::HardforkDetermined(info) => {
+                let notification = HardforkNotification::new(
+                    HardforkStatus::Determined,
+                    info.block_number,
+                    info.flags,
+                );
+                let msg = SubscriptionMessage::from_json(&notification).unwrap();
+                send_to_hubs(&mut self.hardfork_hubs, &msg);
+            }
+            MetadataEvent::HardforkEnabled(info) => {
+                let notification = HardforkNotification::new(
+                    HardforkStatus::Enabled,
+                    info.block_number,
+                    info.flags,
+                );
+                let msg = SubscriptionMessage::from_json(&notification).unwrap();
+                send_to_hubs(&mut self.hardfork_hubs, &msg);
+            }
+        }
+        self.release_axon_receivers();
+    }
+
+    fn release_axon_receivers(&mut self) {
+        if self.proof_hubs.is_empty() {
+            self.new_proofs = None;
+        }
+        if self.epoch_hubs.is_empty() && self.hardfork_hubs.is_empty() {
+            self.metadata_events = None;
+        }
+    }
+
     pub async fn run(mut self) {
         let mut time_internal = interval(Duration::from_secs(3));
         loop {
@@ -219,7 +351,31 @@ where
                             match hub.typ {
                                 Type::NewHeads => self.header_hubs.push(Hub{filter: (), sink: hub.sink}),
                                 Type::Logs(filter) => self.log_hubs.push(Hub{filter, sink: hub.sink}),
-                                Type::Syncing => self.sync_hubs.push(Hub{filter: (), sink: hub.sink})
+                                Type::Syncing => self.sync_hubs.push(Hub{filter: (), sink: hub.sink}),
+                                Type::NewPendingTransactions(full) => {
+                                    if self.new_txs.is_none() {
+                                        self.new_txs = Some(self.adapter.subscribe_new_txs(Context::new()));
+                                    }
+                                    self.pending_tx_hubs.push(Hub{filter: full, sink: hub.sink})
+                                }
+                                Type::NewProofs => {
+                                    if self.new_proofs.is_none() {
+                                        self.new_proofs = Some(self.adapter.subscribe_proofs(Context::new()));
+                                    }
+                                    self.proof_hubs.push(Hub{filter: (), sink: hub.sink})
+                                }
+                                Type::NewEpoch => {
+                                    if self.metadata_events.is_none() {
+                                        self.metadata_events = Some(METADATA_EVENTS.subscribe());
+                                    }
+                                    self.epoch_hubs.push(Hub{filter: (), sink: hub.sink})
+                                }
+                                Type::Hardfork => {
+                                    if self.metadata_events.is_none() {
+                                        self.metadata_events = Some(METADATA_EVENTS.subscribe());
+                                    }
+                                    self.hardfork_hubs.push(Hub{filter: (), sink: hub.sink})
+                                }
                             }
                         },
                         None => {
@@ -227,6 +383,43 @@ where
                         }
                     }
                 }
+                res = recv_broadcast(&mut self.new_txs) => {
+                    match res {
+                        Ok(stx) => self.notify_pending_tx(stx),
+                        Err(RecvError::Lagged(n)) => {
+                            log::warn!("[api] {} pending transactions are not notified", n);
+                        }
+                        Err(RecvError::Closed) => {
+                            self.pending_tx_hubs.clear();
+                            self.new_txs = None;
+                        }
+                    }
+                }
+                res = recv_broadcast(&mut self.new_proofs) => {
+                    match res {
+                        Ok(proof) => self.notify_proof(proof),
+                        Err(RecvError::Lagged(n)) => {
+                            log::warn!("[api] {} proofs are not notified", n);
+                        }
+                        Err(RecvError::Closed) => {
+                            self.proof_hubs.clear();
+                            self.new_proofs = None;
+                        }
+                    }
+                }
+                res = recv_broadcast(&mut self.metadata_events) => {
+                    match res {
+                        Ok(event) => self.notify_metadata_event(event),
+                        Err(RecvError::Lagged(n)) => {
+                            log::warn!("[api] {} metadata events are not notified", n);
+                        }
+                        Err(RecvError::Closed) => {
+                            self.epoch_hubs.clear();
+                            self.hardfork_hubs.clear();
+                            self.metadata_events = None;
+                        }
+                    }
+                }
                 _ = time_internal.tick() => {
                     self.notify().await;
                 }
@@ -238,10 +431,56 @@ where
     }
 }
 
+async fn recv_broadcast<T: Clone>(
+    recv: &mut Option<broadcast::Receiver<T>>,
+) -> Result<T, RecvError> {
+    match recv {
+        Some(recv) => recv.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Sends the message to every hub, and drops the closed and the slow ones as
+/// the pending transaction hubs do.
+fn send_to_hubs<T>(hubs: &mut Vec<Hub<T>>, msg: &SubscriptionMessage) {
+    hubs.retain_mut(|hub| match hub.sink.try_send(msg.clone()) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) => {
+            log::warn!(
+                "[api] drop the slow subscriber {:?}",
+                hub.sink.subscription_id()
+            );
+            false
+        }
+        Err(TrySendError::Closed(_)) => false,
+    });
+}
+
 enum Type {
     NewHeads,
     Logs(LoggerFilter),
     Syncing,
+    // Whether to send the full transactions
+    NewPendingTransactions(bool),
+    NewProofs,
+    NewEpoch,
+    Hardfork,
+}
+
+impl Type {
+    /// Parses the kinds of `axon_subscribe`.
+    fn try_from_axon(value: Params) -> Result<Self, ErrorObjectOwned> {
+        let mut iter = value.sequence();
+
+        let kind: &str = iter.next()?;
+
+        match kind {
+            "newProofs" => Ok(Type::NewProofs),
+            "newEpoch" => Ok(Type::NewEpoch),
+            "hardfork" => Ok(Type::Hardfork),
+            _ => Err(ErrorCode::MethodNotFound.into()),
+        }
+    }
 }
 
 impl<'a> TryFrom<Params<'a>> for Type {
@@ -255,6 +494,10 @@ impl<'a> TryFrom<Params<'a>> for Type {
         match method {
             "newHeads" => Ok(Type::NewHeads),
             "syncing" => Ok(Type::Syncing),
+            "newPendingTransactions" => {
+                let full: Option<bool> = iter.optional_next()?;
+                Ok(Type::NewPendingTransactions(full.unwrap_or_default()))
+            }
             "logs" => {
                 let filter: RawLoggerFilter = iter.next()?;
                 Ok(Type::Logs(filter.into()))
@@ -323,3 +566,49 @@ impl IdProvider for HexIdProvider {
         SubscriptionId::Str(beef::Cow::owned(Hex::encode(hash.as_bytes()).as_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use common_config_parser::types::spec::HardforkName;
+    use protocol::types::HardforkInfoInner;
+
+    use crate::tests::MockAdapter;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribe_hardfork() {
+        let adapter = Arc::new(MockAdapter::default());
+        adapter.push_block(vec![]);
+        let module = ws_subscription_module(adapter).await;
+
+        let subscribe =
+            r#"{"jsonrpc":"2.0","id":1,"method":"axon_subscribe","params":["hardfork"]}"#;
+        let (resp, mut notifications) = module.raw_json_request(subscribe, 16).await.unwrap();
+        assert!(resp.is_success());
+
+        // The hub is registered by the subscription task.
+        while METADATA_EVENTS.receiver_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let flags = H256::from_low_u64_be((HardforkName::Andromeda as u64).to_be());
+        METADATA_EVENTS
+            .send(MetadataEvent::HardforkEnabled(HardforkInfoInner {
+                block_number: 10,
+                flags,
+            }))
+            .unwrap();
+
+        let notification = notifications.recv().await.unwrap();
+        let notification = serde_json::from_str::<serde_json::Value>(&notification).unwrap();
+        assert_eq!(notification["method"], "axon_subscription");
+        assert_eq!(
+            notification["params"]["result"],
+            serde_json::json!({
+                "status": "enabled",
+                "blockNumber": "0xa",
+                "hardforks": ["Andromeda"],
+            })
+        );
+    }
+}
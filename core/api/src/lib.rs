@@ -1,6 +1,8 @@
 pub mod adapter;
 pub mod graphql;
 pub mod jsonrpc;
+#[cfg(test)]
+mod tests;
 
 pub use adapter::DefaultAPIAdapter;
 
@@ -19,6 +21,18 @@ pub enum APIError {
     #[display(fmt = "web socket server error {:?}", _0)]
     WebSocketServer(String),
 
+    #[display(fmt = "admin server error {:?}", _0)]
+    AdminServer(String),
+
+    #[display(fmt = "ipc server error {:?}", _0)]
+    IpcServer(String),
+
+    #[display(fmt = "auth config error {:?}", _0)]
+    Auth(String),
+
+    #[display(fmt = "graphql server error {:?}", _0)]
+    GraphQLServer(String),
+
     #[display(fmt = "storage error {:?}", _0)]
     Storage(String),
 
@@ -0,0 +1,621 @@
+//! An in-memory [`APIAdapter`] for the tests of the RPC servers. The blocks,
+//! the transactions and the receipts are built by [`MockAdapter::push_block`],
+//! and the calls echo their input data.
+
+use std::collections::HashMap;
+use std::sync::{atomic::AtomicBool, Arc};
+use std::time::Duration;
+
+use parking_lot::RwLock;
+
+use common_crypto::{
+    Crypto, PrivateKey, Secp256k1Recoverable, Secp256k1RecoverablePrivateKey, Signature,
+};
+use protocol::codec::ProtocolCodec;
+use protocol::rand::rngs::OsRng;
+use protocol::traits::{APIAdapter, ConnectedPeer, Context, NetworkNodeInfo, PeerDirection};
+use protocol::types::{
+    AccessList, Account, Block, BlockNumber, Bytes, CkbRelatedInfo, Eip1559Transaction,
+    EthAccountProof, ExitReason, ExitSucceed, HardforkInfo, HardforkInfoInner, Hash, Header, Hex,
+    Log, MemPoolContent, Metadata, PendingBlock, Proof, Proposal, Public, Receipt,
+    SignatureComponents, SignedTransaction, SimulateBlock, StateOverride, TraceResult, Tracer,
+    TransactionAction, TxResp, UnsignedTransaction, UnverifiedTransaction, H160, H256, U256, U64,
+};
+use protocol::{async_trait, tokio::sync::broadcast, ProtocolResult};
+
+use crate::APIError;
+
+/// The gas used by every transaction in the mocked blocks.
+pub const MOCK_TX_GAS_USED: u64 = 21000;
+/// The peer id of the mocked node.
+pub const MOCK_PEER_ID: &str = "QmbJ5hSYHyHm6FyD3yTDAEQuq7DZWVbfw5WnxpcMD9Ysbp";
+
+pub fn mock_signed_tx(sender: H160, nonce: u64) -> SignedTransaction {
+    SignedTransaction {
+        transaction: UnverifiedTransaction {
+            unsigned:  UnsignedTransaction::Eip1559(Eip1559Transaction {
+                nonce:                    nonce.into(),
+                max_priority_fee_per_gas: U64::one(),
+                gas_price:                U64::from(10),
+                gas_limit:                U64::from(MOCK_TX_GAS_USED),
+                action:                   TransactionAction::Call(H160::random()),
+                value:                    U256::zero(),
+                data:                     Bytes::new(),
+                access_list:              Vec::new(),
+            }),
+            signature: Some(SignatureComponents {
+                standard_v: 0,
+                r:          Bytes::default(),
+                s:          Bytes::default(),
+            }),
+            chain_id:  Some(0u64),
+            hash:      H256::random(),
+        },
+        sender,
+        public: Some(Public::default()),
+    }
+}
+
+/// Returns a transaction signed by a random key and its raw bytes to send.
+pub fn mock_raw_tx(nonce: u64) -> (SignedTransaction, Hex) {
+    let priv_key = Secp256k1RecoverablePrivateKey::generate(&mut OsRng);
+    let mut utx = UnverifiedTransaction {
+        unsigned:  UnsignedTransaction::Eip1559(Eip1559Transaction {
+            nonce:                    nonce.into(),
+            max_priority_fee_per_gas: U64::one(),
+            gas_price:                U64::from(10),
+            gas_limit:                U64::from(MOCK_TX_GAS_USED),
+            action:                   TransactionAction::Call(H160::random()),
+            value:                    U256::zero(),
+            data:                     Bytes::new(),
+            access_list:              Vec::new(),
+        }),
+        signature: None,
+        chain_id:  Some(0u64),
+        hash:      H256::zero(),
+    };
+    let signature = Secp256k1Recoverable::sign_message(
+        utx.signature_hash(true).as_bytes(),
+        &priv_key.to_bytes(),
+    )
+    .unwrap()
+    .to_bytes();
+    utx.signature = Some(signature.into());
+
+    let raw = Hex::encode(utx.encode().unwrap());
+    (SignedTransaction::from_unverified(utx).unwrap(), raw)
+}
+
+pub fn mock_log(address: H160, topics: Vec<H256>) -> Log {
+    Log {
+        address,
+        topics,
+        data: Vec::new(),
+    }
+}
+
+#[derive(Default)]
+struct MockChain {
+    blocks:   Vec<Block>,
+    txs:      HashMap<Hash, SignedTransaction>,
+    receipts: HashMap<Hash, Receipt>,
+    mempool:  Vec<SignedTransaction>,
+    /// Overrides the proof in the latest header as the latest proof.
+    proof:    Option<Proof>,
+    peers:    Vec<ConnectedPeer>,
+    /// The banned ip networks with the ban durations and reasons.
+    bans:     Vec<(String, Duration, String)>,
+}
+
+pub struct MockAdapter {
+    chain:                RwLock<MockChain>,
+    /// Whether the blocks containing the logs are looked up like the log
+    /// index of the storage.
+    pub log_index:        bool,
+    pub hardforks:        HardforkInfo,
+    pub new_txs:          broadcast::Sender<SignedTransaction>,
+    pub committed_blocks: broadcast::Sender<Block>,
+    pub proofs:           broadcast::Sender<Proof>,
+}
+
+/// A chain with only the genesis block.
+impl Default for MockAdapter {
+    fn default() -> Self {
+        let adapter = MockAdapter {
+            chain:            Default::default(),
+            log_index:        false,
+            hardforks:        HardforkInfo {
+                inner: vec![HardforkInfoInner {
+                    block_number: 0,
+                    flags:        H256::zero(),
+                }],
+            },
+            new_txs:          broadcast::channel(16).0,
+            committed_blocks: broadcast::channel(16).0,
+            proofs:           broadcast::channel(16).0,
+        };
+        adapter.push_block(Vec::new());
+        adapter
+    }
+}
+
+impl MockAdapter {
+    /// Appends a block containing the transactions, each of which emits the
+    /// logs, and returns it.
+    pub fn push_block(&self, txs: Vec<(SignedTransaction, Vec<Log>)>) -> Block {
+        let mut chain = self.chain.write();
+        let number = chain.blocks.len() as u64;
+        let prev_hash = chain.blocks.last().map(|b| b.hash()).unwrap_or_default();
+        let header = Header {
+            prev_hash,
+            number,
+            proof: Proof {
+                number: number.saturating_sub(1),
+                block_hash: prev_hash,
+                ..Default::default()
+            },
+            gas_limit: U64::from(30_000_000u64),
+            gas_used: U64::from(MOCK_TX_GAS_USED * txs.len() as u64),
+            timestamp: number,
+            ..Default::default()
+        };
+        let block = Block {
+            header,
+            tx_hashes: txs.iter().map(|(tx, _)| tx.transaction.hash).collect(),
+        };
+        let block_hash = block.hash();
+
+        let mut log_index = 0;
+        for (tx_index, (tx, logs)) in txs.into_iter().enumerate() {
+            let receipt = Receipt {
+                tx_hash: tx.transaction.hash,
+                block_number: number,
+                block_hash,
+                tx_index: tx_index as u32,
+                used_gas: U64::from(MOCK_TX_GAS_USED),
+                log_index,
+                sender: tx.sender,
+                ret: ExitReason::Succeed(ExitSucceed::Stopped),
+                logs,
+                ..Default::default()
+            };
+            log_index += receipt.logs.len() as u32;
+            chain.receipts.insert(tx.transaction.hash, receipt);
+            chain.txs.insert(tx.transaction.hash, tx);
+        }
+
+        chain.blocks.push(block.clone());
+        block
+    }
+
+    /// Sets the latest proof, which is the proof of the parent block in the
+    /// latest header by default.
+    pub fn set_latest_proof(&self, proof: Proof) {
+        self.chain.write().proof = Some(proof);
+    }
+
+    pub fn push_peer(&self, peer: ConnectedPeer) {
+        self.chain.write().peers.push(peer);
+    }
+
+    pub fn bans(&self) -> Vec<(String, Duration, String)> {
+        self.chain.read().bans.clone()
+    }
+
+    pub fn mempool(&self) -> Vec<SignedTransaction> {
+        self.chain.read().mempool.clone()
+    }
+
+    fn block(&self, number: Option<u64>) -> Option<Block> {
+        let chain = self.chain.read();
+        match number {
+            Some(n) => chain.blocks.get(n as usize).cloned(),
+            None => chain.blocks.last().cloned(),
+        }
+    }
+}
+
+fn match_log(log: &Log, addresses: &[H160], topics: &[Option<Vec<Option<H256>>>]) -> bool {
+    (addresses.is_empty() || addresses.contains(&log.address))
+        && topics.iter().enumerate().all(|(i, topic)| match topic {
+            Some(items) if !items.contains(&None) => {
+                items.iter().any(|item| log.topics.get(i) == item.as_ref())
+            }
+            _ => true,
+        })
+}
+
+#[async_trait]
+impl APIAdapter for MockAdapter {
+    async fn insert_signed_txs(
+        &self,
+        _ctx: Context,
+        signed_tx: SignedTransaction,
+    ) -> ProtocolResult<()> {
+        let _ = self.new_txs.send(signed_tx.clone());
+        self.chain.write().mempool.push(signed_tx);
+        Ok(())
+    }
+
+    async fn mempool_contains_tx(&self, _ctx: Context, tx_hash: &Hash) -> bool {
+        self.chain
+            .read()
+            .mempool
+            .iter()
+            .any(|tx| &tx.transaction.hash == tx_hash)
+    }
+
+    async fn get_mempool_status(&self, _ctx: Context) -> ProtocolResult<(usize, usize)> {
+        Ok((self.chain.read().mempool.len(), 0))
+    }
+
+    async fn get_mempool_content(
+        &self,
+        _ctx: Context,
+        _sender: Option<H160>,
+    ) -> ProtocolResult<MemPoolContent> {
+        unimplemented!()
+    }
+
+    fn subscribe_new_txs(&self, _ctx: Context) -> broadcast::Receiver<SignedTransaction> {
+        self.new_txs.subscribe()
+    }
+
+    fn subscribe_committed_blocks(&self, _ctx: Context) -> broadcast::Receiver<Block> {
+        self.committed_blocks.subscribe()
+    }
+
+    fn subscribe_proofs(&self, _ctx: Context) -> broadcast::Receiver<Proof> {
+        self.proofs.subscribe()
+    }
+
+    async fn get_block_by_number(
+        &self,
+        _ctx: Context,
+        height: Option<u64>,
+    ) -> ProtocolResult<Option<Block>> {
+        Ok(self.block(height))
+    }
+
+    async fn get_block_by_hash(&self, _ctx: Context, hash: Hash) -> ProtocolResult<Option<Block>> {
+        Ok(self
+            .chain
+            .read()
+            .blocks
+            .iter()
+            .find(|block| block.hash() == hash)
+            .cloned())
+    }
+
+    async fn get_block_header_by_number(
+        &self,
+        _ctx: Context,
+        height: Option<u64>,
+    ) -> ProtocolResult<Option<Header>> {
+        Ok(self.block(height).map(|block| block.header))
+    }
+
+    async fn get_block_number_by_hash(
+        &self,
+        ctx: Context,
+        hash: Hash,
+    ) -> ProtocolResult<Option<BlockNumber>> {
+        Ok(self
+            .get_block_by_hash(ctx, hash)
+            .await?
+            .map(|block| block.header.number))
+    }
+
+    async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
+        if let Some(proof) = self.chain.read().proof.clone() {
+            return Ok(proof);
+        }
+
+        Ok(self.block(None).unwrap().header.proof)
+    }
+
+    async fn get_receipt_by_tx_hash(
+        &self,
+        _ctx: Context,
+        tx_hash: Hash,
+    ) -> ProtocolResult<Option<Receipt>> {
+        Ok(self.chain.read().receipts.get(&tx_hash).cloned())
+    }
+
+    async fn get_receipts_by_hashes(
+        &self,
+        _ctx: Context,
+        _block_number: u64,
+        tx_hashes: &[Hash],
+    ) -> ProtocolResult<Vec<Option<Receipt>>> {
+        let chain = self.chain.read();
+        Ok(tx_hashes
+            .iter()
+            .map(|hash| chain.receipts.get(hash).cloned())
+            .collect())
+    }
+
+    async fn get_log_block_numbers(
+        &self,
+        _ctx: Context,
+        from: u64,
+        to: u64,
+        addresses: &[H160],
+        topics: &[Option<Vec<Option<H256>>>],
+    ) -> ProtocolResult<Option<Vec<u64>>> {
+        if !self.log_index {
+            return Ok(None);
+        }
+
+        let chain = self.chain.read();
+        Ok(Some(
+            (from..=to)
+                .filter(|number| {
+                    chain.blocks.get(*number as usize).is_some_and(|block| {
+                        block.tx_hashes.iter().any(|hash| {
+                            chain.receipts[hash]
+                                .logs
+                                .iter()
+                                .any(|log| match_log(log, addresses, topics))
+                        })
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        _ctx: Context,
+        tx_hash: Hash,
+    ) -> ProtocolResult<Option<SignedTransaction>> {
+        let chain = self.chain.read();
+        Ok(chain.txs.get(&tx_hash).cloned().or_else(|| {
+            chain
+                .mempool
+                .iter()
+                .find(|tx| tx.transaction.hash == tx_hash)
+                .cloned()
+        }))
+    }
+
+    async fn get_transactions_by_hashes(
+        &self,
+        _ctx: Context,
+        _block_number: u64,
+        tx_hashes: &[Hash],
+    ) -> ProtocolResult<Vec<Option<SignedTransaction>>> {
+        let chain = self.chain.read();
+        Ok(tx_hashes
+            .iter()
+            .map(|hash| chain.txs.get(hash).cloned())
+            .collect())
+    }
+
+    async fn get_account(
+        &self,
+        _ctx: Context,
+        _address: H160,
+        _number: Option<BlockNumber>,
+    ) -> ProtocolResult<Account> {
+        unimplemented!()
+    }
+
+    async fn get_pending_tx_count(
+        &self,
+        _ctx: Context,
+        _address: H160,
+    ) -> ProtocolResult<(U256, Option<BlockNumber>)> {
+        unimplemented!()
+    }
+
+    async fn get_pending_block(&self, _ctx: Context) -> ProtocolResult<Arc<PendingBlock>> {
+        unimplemented!()
+    }
+
+    async fn evm_call(
+        &self,
+        _ctx: Context,
+        _from: Option<H160>,
+        _to: Option<H160>,
+        _gas_price: Option<U64>,
+        _gas_limit: Option<U64>,
+        _value: U256,
+        data: Vec<u8>,
+        _estimate: bool,
+        _state_override: Option<StateOverride>,
+        _state_root: Hash,
+        _proposal: Proposal,
+    ) -> ProtocolResult<TxResp> {
+        Ok(TxResp {
+            gas_used: MOCK_TX_GAS_USED,
+            ret: data,
+            ..Default::default()
+        })
+    }
+
+    async fn create_access_list(
+        &self,
+        _ctx: Context,
+        _from: Option<H160>,
+        _to: Option<H160>,
+        _gas_price: Option<U64>,
+        _gas_limit: Option<U64>,
+        _value: U256,
+        _data: Vec<u8>,
+        _access_list: AccessList,
+        _state_root: Hash,
+        _proposal: Proposal,
+    ) -> ProtocolResult<(AccessList, TxResp)> {
+        unimplemented!()
+    }
+
+    async fn trace_call(
+        &self,
+        _ctx: Context,
+        _from: Option<H160>,
+        _to: Option<H160>,
+        _gas_price: Option<U64>,
+        _gas_limit: Option<U64>,
+        _value: U256,
+        _data: Vec<u8>,
+        _state_root: Hash,
+        _proposal: Proposal,
+        _tracer: Tracer,
+        _cancel: Arc<AtomicBool>,
+    ) -> ProtocolResult<TraceResult> {
+        unimplemented!()
+    }
+
+    async fn trace_transactions(
+        &self,
+        _ctx: Context,
+        _state_root: Hash,
+        _proposal: Proposal,
+        _txs: Vec<SignedTransaction>,
+        _trace_from: usize,
+        _tracer: Tracer,
+        _cancel: Arc<AtomicBool>,
+    ) -> ProtocolResult<Vec<TraceResult>> {
+        unimplemented!()
+    }
+
+    async fn simulate_blocks(
+        &self,
+        _ctx: Context,
+        _state_root: Hash,
+        _blocks: Vec<SimulateBlock>,
+        _cancel: Arc<AtomicBool>,
+    ) -> ProtocolResult<Vec<Vec<TxResp>>> {
+        unimplemented!()
+    }
+
+    async fn get_code_by_hash(&self, _ctx: Context, _hash: &Hash) -> ProtocolResult<Option<Bytes>> {
+        unimplemented!()
+    }
+
+    async fn peer_count(&self, _ctx: Context) -> ProtocolResult<U256> {
+        unimplemented!()
+    }
+
+    async fn get_peers(&self, _ctx: Context) -> ProtocolResult<Vec<ConnectedPeer>> {
+        Ok(self.chain.read().peers.clone())
+    }
+
+    async fn get_network_node_info(&self, _ctx: Context) -> ProtocolResult<NetworkNodeInfo> {
+        let chain = self.chain.read();
+        let inbound = chain
+            .peers
+            .iter()
+            .filter(|peer| peer.direction == PeerDirection::Inbound)
+            .count();
+
+        Ok(NetworkNodeInfo {
+            peer_id: MOCK_PEER_ID.to_string(),
+            listen_addrs: vec![format!("/ip4/127.0.0.1/tcp/8001/p2p/{}", MOCK_PEER_ID)],
+            inbound,
+            outbound: chain.peers.len() - inbound,
+            banned: chain.bans.len(),
+        })
+    }
+
+    async fn add_peer(&self, _ctx: Context, multiaddr: String) -> ProtocolResult<()> {
+        let (_, peer_id) = multiaddr
+            .rsplit_once("/p2p/")
+            .ok_or_else(|| APIError::Adapter(format!("{} has no peer id", multiaddr)))?;
+
+        self.push_peer(ConnectedPeer {
+            peer_id: peer_id.to_string(),
+            multiaddr,
+            direction: PeerDirection::Outbound,
+            latency: None,
+            is_consensus: false,
+        });
+        Ok(())
+    }
+
+    async fn remove_peer(&self, _ctx: Context, peer_id: String) -> ProtocolResult<bool> {
+        let mut chain = self.chain.write();
+        let len = chain.peers.len();
+        chain.peers.retain(|peer| peer.peer_id != peer_id);
+        Ok(chain.peers.len() < len)
+    }
+
+    async fn ban_peer(
+        &self,
+        _ctx: Context,
+        peer_id: String,
+        timeout: Duration,
+        reason: String,
+    ) -> ProtocolResult<Vec<String>> {
+        let mut chain = self.chain.write();
+        let Some(idx) = chain.peers.iter().position(|peer| peer.peer_id == peer_id) else {
+            return Ok(Vec::new());
+        };
+
+        // The multiaddr starts with `/ip4/<ip>`.
+        let peer = chain.peers.remove(idx);
+        let network = format!(
+            "{}/32",
+            peer.multiaddr.split('/').nth(2).unwrap_or_default()
+        );
+        chain.bans.push((network.clone(), timeout, reason));
+        Ok(vec![network])
+    }
+
+    async fn unban(&self, _ctx: Context, ip_network: String) -> ProtocolResult<()> {
+        self.chain
+            .write()
+            .bans
+            .retain(|(network, ..)| *network != ip_network);
+        Ok(())
+    }
+
+    async fn get_storage_at(
+        &self,
+        _ctx: Context,
+        _address: H160,
+        _position: U256,
+        _state_root: Hash,
+    ) -> ProtocolResult<Bytes> {
+        unimplemented!()
+    }
+
+    async fn get_metadata_by_number(
+        &self,
+        _ctx: Context,
+        _block_number: Option<u64>,
+    ) -> ProtocolResult<Metadata> {
+        unimplemented!()
+    }
+
+    async fn get_ckb_related_info(&self, _ctx: Context) -> ProtocolResult<CkbRelatedInfo> {
+        unimplemented!()
+    }
+
+    async fn get_image_cell_root(&self, _ctx: Context) -> ProtocolResult<H256> {
+        unimplemented!()
+    }
+
+    async fn get_metadata_root(&self, _ctx: Context, _number: Option<u64>) -> ProtocolResult<H256> {
+        unimplemented!()
+    }
+
+    async fn hardfork_info(&self, _ctx: Context) -> ProtocolResult<HardforkInfo> {
+        Ok(self.hardforks.clone())
+    }
+
+    async fn hardfork_proposal(&self, _ctx: Context) -> ProtocolResult<Option<HardforkInfoInner>> {
+        Ok(None)
+    }
+
+    async fn get_proof(
+        &self,
+        _ctx: Context,
+        _address: H160,
+        _storage_position: Vec<U256>,
+        _storage_root: Hash,
+    ) -> ProtocolResult<EthAccountProof> {
+        unimplemented!()
+    }
+}
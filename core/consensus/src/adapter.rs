@@ -3,16 +3,19 @@ use std::sync::Arc;
 
 use overlord::types::{Node, OverlordMsg, Vote, VoteType};
 use overlord::{extract_voters, Crypto, OverlordHandler};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
 use common_apm::Instant;
 use common_apm_derive::trace_span;
-use core_executor::system_contract::metadata::MetadataHandle;
+use core_executor::system_contract::metadata::{
+    notify_metadata_events, MetadataHandle, METADATA_EVENTS,
+};
 use core_executor::{AxonExecutor, AxonExecutorApplyAdapter, AxonExecutorReadOnlyAdapter};
 use core_network::{PeerId, PeerIdExt};
 use protocol::constants::endpoints::{
     BROADCAST_HEIGHT, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_PROOF, RPC_SYNC_PULL_TXS,
 };
+use protocol::tokio::{sync::broadcast, task};
 use protocol::traits::{
     CommonConsensusAdapter, ConsensusAdapter, Context, Executor, Gossip, MemPool, MessageTarget,
     Network, PeerTrust, Priority, Rpc, Storage, SynchronizationAdapter,
@@ -22,7 +25,7 @@ use protocol::types::{
     Header, Hex, MerkleRoot, Metadata, PackedTxHashes, Proof, Proposal, Receipt, SignedTransaction,
     U256,
 };
-use protocol::{async_trait, tokio::task, trie, ProtocolResult};
+use protocol::{async_trait, trie, ProtocolResult};
 
 use crate::consensus::gen_overlord_status;
 use crate::util::{convert_hex_to_bls_pubkeys, OverlordCrypto};
@@ -30,6 +33,11 @@ use crate::BlockHeaderField::{PreviousBlockHash, Version};
 use crate::BlockProofField::{BitMap, HashMismatch, HeightMismatch, Signature, WeightNotFound};
 use crate::{types::PullTxsRequest, BlockProofField, ConsensusError};
 
+/// The number of committed blocks buffered for each commit subscriber. A
+/// subscriber that falls behind this far loses its backlog rather than
+/// blocking the commit.
+const COMMIT_NOTIFY_CAPACITY: usize = 64;
+
 pub struct OverlordConsensusAdapter<
     M: MemPool,
     N: Rpc + PeerTrust + Gossip + 'static,
@@ -43,6 +51,12 @@ pub struct OverlordConsensusAdapter<
     trie_db:          Arc<DB>,
     overlord_handler: RwLock<Option<OverlordHandler<Proposal>>>,
     crypto:           Arc<OverlordCrypto>,
+    // Notify the subscribers once a block is committed.
+    commit_notifier:  broadcast::Sender<Block>,
+    // Notify the subscribers once a proof is saved.
+    proof_notifier:   broadcast::Sender<Proof>,
+    // The proof saved before the block, which is notified with the block.
+    saved_proof:      Mutex<Option<Proof>>,
 }
 
 #[async_trait]
@@ -73,6 +87,10 @@ where
         self.mempool.get_full_txs(ctx, None, txs).await
     }
 
+    fn drop_txs_from_mempool(&self, ctx: Context, txs: &[Hash]) {
+        self.mempool.drop_txs(ctx, txs)
+    }
+
     #[trace_span(kind = "consensus.adapter")]
     async fn transmit(
         &self,
@@ -234,12 +252,47 @@ where
     /// Save a block to the database.
     #[trace_span(kind = "consensus.adapter", logs = "{txs_len: block.tx_hashes.len()}")]
     async fn save_block(&self, ctx: Context, block: Block) -> ProtocolResult<()> {
-        self.storage.insert_block(ctx, block).await
+        self.storage.insert_block(ctx, block.clone()).await?;
+
+        // The subscribers are only notified once the block is committed, so
+        // the proof saved before it and the metadata changes of it are
+        // notified here.
+        if self.commit_notifier.receiver_count() != 0 {
+            // Sending never blocks, it only fails when there is no subscriber.
+            let _ = self.commit_notifier.send(block.clone());
+        }
+
+        let proof = self.saved_proof.lock().take();
+        if let Some(proof) = proof {
+            if self.proof_notifier.receiver_count() != 0 {
+                let _ = self.proof_notifier.send(proof);
+            }
+        }
+
+        if METADATA_EVENTS.receiver_count() != 0 {
+            let proposal = Proposal::new_without_state_root(&block.header);
+            match self
+                .get_metadata_root(block.header.state_root, &proposal)
+                .await
+            {
+                Ok(root) => notify_metadata_events(root, &block.header),
+                Err(e) => log::warn!(
+                    "[consensus] get metadata root of block {}: {}",
+                    block.header.number,
+                    e
+                ),
+            }
+        }
+
+        Ok(())
     }
 
     #[trace_span(kind = "consensus.adapter")]
     async fn save_proof(&self, ctx: Context, proof: Proof) -> ProtocolResult<()> {
-        self.storage.update_latest_proof(ctx, proof).await
+        self.storage.update_latest_proof(ctx, proof.clone()).await?;
+        *self.saved_proof.lock() = Some(proof);
+
+        Ok(())
     }
 
     /// Save some signed transactions to the database.
@@ -665,9 +718,26 @@ where
             trie_db,
             overlord_handler: RwLock::new(None),
             crypto,
+            commit_notifier: broadcast::channel(COMMIT_NOTIFY_CAPACITY).0,
+            proof_notifier: broadcast::channel(COMMIT_NOTIFY_CAPACITY).0,
+            saved_proof: Mutex::new(None),
         })
     }
 
+    /// Returns the sender which broadcasts every block once it is saved. The
+    /// receivers are created by `subscribe`, so the sender can be handed out
+    /// before the adapter is moved into consensus.
+    pub fn commit_notifier(&self) -> broadcast::Sender<Block> {
+        self.commit_notifier.clone()
+    }
+
+    /// Returns the sender which broadcasts every proof once the block saved
+    /// after it is saved, which is the proof of the committed block, or the
+    /// proof in the header of a synchronized block.
+    pub fn proof_notifier(&self) -> broadcast::Sender<Proof> {
+        self.proof_notifier.clone()
+    }
+
     pub fn set_overlord_handler(&self, handler: OverlordHandler<Proposal>) {
         *self.overlord_handler.write() = Some(handler)
     }
@@ -16,16 +16,16 @@ use common_apm_derive::trace_span;
 use common_crypto::BlsPublicKey;
 use common_logger::{json, log};
 use common_merkle::TrieMerkle;
-use core_executor::MetadataHandle;
+use core_executor::{next_block_base_fee_per_gas, next_block_required_base_fee, MetadataHandle};
 use protocol::constants::endpoints::{
     END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_SIGNED_CHOKE, END_GOSSIP_SIGNED_PROPOSAL,
     END_GOSSIP_SIGNED_VOTE,
 };
-use protocol::constants::{BASE_FEE_PER_GAS, MAX_BLOCK_GAS_LIMIT};
+use protocol::constants::MAX_BLOCK_GAS_LIMIT;
 use protocol::traits::{ConsensusAdapter, Context, MessageTarget, NodeInfo};
 use protocol::types::{
-    Block, BlockVersion, Bytes, ExecResp, ExtraData, Hash, Hex, Metadata, Proof, Proposal,
-    SignedTransaction, ValidatorExtend, VecDisplayHelper, RLP_NULL,
+    Block, BlockVersion, Bytes, ExecResp, ExtraData, HardforkInfo, Hash, Header, Hex, Metadata,
+    Proof, Proposal, SignedTransaction, ValidatorExtend, VecDisplayHelper, RLP_NULL, U64,
 };
 use protocol::{
     async_trait, codec::ProtocolCodec, tokio::sync::Mutex as AsyncMutex, types::HardforkInfoInner,
@@ -76,8 +76,33 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<Proposal> for ConsensusEngine<A
             )
             .await?;
         let signed_txs = self.adapter.get_full_txs(ctx.clone(), &txs.hashes).await?;
-        let txs_root = if !txs.hashes.is_empty() {
-            TrieMerkle::from_iter(txs.hashes.iter().enumerate())
+
+        let previous_header = self
+            .adapter
+            .get_block_header_by_number(ctx.clone(), next_number - 1)
+            .await?;
+        let hardforks = self.parent_hardforks(&previous_header).await?;
+
+        let (hashes, signed_txs) = match next_block_required_base_fee(&previous_header, &hardforks)
+        {
+            Some(base_fee) => {
+                let (signed_txs, under_base_fee) = remove_under_base_fee(
+                    signed_txs,
+                    txs.call_system_script_count as usize,
+                    base_fee,
+                );
+                if !under_base_fee.is_empty() {
+                    self.adapter
+                        .drop_txs_from_mempool(ctx.clone(), &under_base_fee);
+                }
+                let hashes = signed_txs.iter().map(|tx| tx.transaction.hash).collect();
+                (hashes, signed_txs)
+            }
+            None => (txs.hashes, signed_txs),
+        };
+
+        let txs_root = if !hashes.is_empty() {
+            TrieMerkle::from_iter(hashes.iter().enumerate())
                 .root_hash()
                 .unwrap_or_else(|err| {
                     panic!("failed to calculate trie root hash for transactions since {err}")
@@ -121,11 +146,11 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<Proposal> for ConsensusEngine<A
             number:                   next_number,
             gas_limit:                MAX_BLOCK_GAS_LIMIT.into(),
             extra_data:               extra_data_hardfork,
-            base_fee_per_gas:         BASE_FEE_PER_GAS.into(),
+            base_fee_per_gas:         next_block_base_fee_per_gas(&previous_header, &hardforks),
             proof:                    status.proof,
             chain_id:                 self.node_info.chain_id,
             call_system_script_count: txs.call_system_script_count,
-            tx_hashes:                txs.hashes,
+            tx_hashes:                hashes,
         };
 
         if proposal.number != proposal.proof.number + 1 {
@@ -572,6 +597,16 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
             return Err(ProtocolError::from(ConsensusError::InvalidTimestamp));
         }
 
+        // verify the base fee per gas calculated from the previous block.
+        let hardforks = self.parent_hardforks(&previous_block.header).await?;
+        let base_fee_per_gas = next_block_base_fee_per_gas(&previous_block.header, &hardforks);
+        if proposal.base_fee_per_gas != base_fee_per_gas {
+            return Err(ProtocolError::from(ConsensusError::InvalidBaseFee {
+                expect: base_fee_per_gas,
+                actual: proposal.base_fee_per_gas,
+            }));
+        }
+
         self.adapter
             .verify_proof(
                 ctx.clone(),
@@ -600,9 +635,35 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
             .adapter
             .get_full_txs(ctx.clone(), &proposal.tx_hashes)
             .await?;
+
+        // verify the max fee per gas of the transactions covers the base fee per gas,
+        // except for the system script transactions.
+        if let Some(base_fee) = next_block_required_base_fee(&previous_block.header, &hardforks) {
+            if let Some(tx) = signed_txs
+                .iter()
+                .skip(proposal.call_system_script_count as usize)
+                .find(|tx| *tx.transaction.unsigned.max_fee_per_gas() < base_fee)
+            {
+                return Err(ProtocolError::from(ConsensusError::UnderBaseFee {
+                    tx_hash:          tx.transaction.hash,
+                    max_fee_per_gas:  *tx.transaction.unsigned.max_fee_per_gas(),
+                    base_fee_per_gas: base_fee,
+                }));
+            }
+        }
+
         self.check_order_transactions(ctx.clone(), proposal, &signed_txs)
     }
 
+    /// The hardforks in the metadata after the `parent` block is executed.
+    async fn parent_hardforks(&self, parent: &Header) -> ProtocolResult<HardforkInfo> {
+        let root = self
+            .adapter
+            .get_metadata_root(parent.state_root, &Proposal::new_without_state_root(parent))
+            .await?;
+        MetadataHandle::new(root).hardfork_infos()
+    }
+
     #[trace_span(kind = "consensus.engine", logs = "{txs_len: signed_txs.len()}")]
     fn check_order_transactions(
         &self,
@@ -838,13 +899,78 @@ fn validate_timestamp(
     true
 }
 
+/// Remove the transactions whose max fee per gas can't cover the base fee per
+/// gas, along with the later transactions of their senders which would have a
+/// nonce gap. The leading `call_system_script_count` system script transactions
+/// are kept. The hashes of the transactions under the base fee are returned to
+/// be dropped from the mempool, the others are kept for the later blocks.
+fn remove_under_base_fee(
+    signed_txs: Vec<SignedTransaction>,
+    call_system_script_count: usize,
+    base_fee_per_gas: U64,
+) -> (Vec<SignedTransaction>, Vec<Hash>) {
+    let mut removed_senders = HashSet::new();
+    let mut under_base_fee = Vec::new();
+
+    let remain = signed_txs
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, tx)| {
+            if idx < call_system_script_count {
+                return Some(tx);
+            }
+
+            if *tx.transaction.unsigned.max_fee_per_gas() < base_fee_per_gas {
+                under_base_fee.push(tx.transaction.hash);
+                removed_senders.insert(tx.sender);
+                return None;
+            }
+
+            if removed_senders.contains(&tx.sender) {
+                return None;
+            }
+
+            Some(tx)
+        })
+        .collect();
+
+    (remain, under_base_fee)
+}
+
 fn gauge_txs_len(proposal: &Proposal) {
     common_apm::metrics::consensus::ENGINE_ORDER_TX_GAUGE.set(proposal.tx_hashes.len() as i64);
 }
 
 #[cfg(test)]
 mod tests {
-    use super::validate_timestamp;
+    use protocol::types::{
+        Eip1559Transaction, Hash, SignedTransaction, TransactionAction, UnsignedTransaction,
+        UnverifiedTransaction, H160, U64,
+    };
+
+    use super::{remove_under_base_fee, validate_timestamp};
+
+    fn mock_tx(sender: H160, nonce: u64, max_fee_per_gas: u64) -> SignedTransaction {
+        SignedTransaction {
+            transaction: UnverifiedTransaction {
+                unsigned:  UnsignedTransaction::Eip1559(Eip1559Transaction {
+                    nonce:                    nonce.into(),
+                    max_priority_fee_per_gas: U64::one(),
+                    gas_price:                max_fee_per_gas.into(),
+                    gas_limit:                21000u64.into(),
+                    action:                   TransactionAction::Call(H160::random()),
+                    value:                    Default::default(),
+                    data:                     Default::default(),
+                    access_list:              Vec::new(),
+                }),
+                signature: None,
+                chain_id:  Some(0u64),
+                hash:      Hash::random(),
+            },
+            sender,
+            public: None,
+        }
+    }
 
     #[test]
     fn test_validate_timestamp() {
@@ -857,4 +983,28 @@ mod tests {
         // current 10, proposal 9, previous 11. true
         assert!(!validate_timestamp(10, 9, 11));
     }
+    #[test]
+    fn test_remove_under_base_fee() {
+        let (alice, bob) = (H160::random(), H160::random());
+        let txs = vec![
+            mock_tx(H160::zero(), 0, 0),
+            mock_tx(alice, 0, 10),
+            mock_tx(bob, 0, 9),
+            mock_tx(alice, 1, 9),
+            mock_tx(alice, 2, 20),
+            mock_tx(bob, 1, 20),
+        ];
+        let expect = vec![txs[0].transaction.hash, txs[1].transaction.hash];
+        let expect_dropped = vec![txs[2].transaction.hash, txs[3].transaction.hash];
+
+        let (remain, dropped) = remove_under_base_fee(txs, 1, 10u64.into());
+        assert_eq!(
+            remain
+                .iter()
+                .map(|tx| tx.transaction.hash)
+                .collect::<Vec<_>>(),
+            expect
+        );
+        assert_eq!(dropped, expect_dropped);
+    }
 }
@@ -17,7 +17,7 @@ use std::error::Error;
 
 use common_crypto::Error as CryptoError;
 
-use protocol::types::{ExitReason, Hash, MerkleRoot};
+use protocol::types::{ExitReason, Hash, MerkleRoot, U64};
 use protocol::{Display, ProtocolError, ProtocolErrorKind};
 
 pub use crate::adapter::OverlordConsensusAdapter;
@@ -132,6 +132,23 @@ pub enum ConsensusError {
     #[display(fmt = "Consensus invalid timestamp")]
     InvalidTimestamp,
 
+    /// Invalid base fee per gas
+    #[display(fmt = "Invalid base fee per gas, expect {}, actual {}", expect, actual)]
+    InvalidBaseFee { expect: U64, actual: U64 },
+
+    /// The max fee per gas of the transaction can't cover the base fee per gas
+    #[display(
+        fmt = "Transaction {:#x} max fee per gas {} is under the base fee per gas {}",
+        tx_hash,
+        max_fee_per_gas,
+        base_fee_per_gas
+    )]
+    UnderBaseFee {
+        tx_hash:          Hash,
+        max_fee_per_gas:  U64,
+        base_fee_per_gas: U64,
+    },
+
     /// Consensus missed the block header.
     #[display(fmt = "Consensus missed block header of {} block", _0)]
     MissingBlockHeader(u64),
@@ -11,8 +11,8 @@ use common_apm::Instant;
 use common_config_parser::types::ConfigRocksDB;
 use protocol::codec::{hex_encode, ProtocolCodec};
 use protocol::traits::{
-    IntoIteratorByRef, StorageAdapter, StorageBatchModify, StorageCategory, StorageIterator,
-    StorageSchema,
+    IntoIteratorByRef, StorageAdapter, StorageBatch, StorageBatchModify, StorageCategory,
+    StorageIterator, StorageSchema,
 };
 use protocol::{types::Bytes, Display, From, ProtocolError, ProtocolErrorKind, ProtocolResult};
 
@@ -53,6 +53,7 @@ impl RocksAdapter {
             map_category(StorageCategory::MetadataState),
             map_category(StorageCategory::CkbLightClientState),
             map_category(StorageCategory::Version),
+            map_category(StorageCategory::LogIndex),
         ];
 
         let (mut opts, cf_descriptors) = if let Some(ref file) = config.options_file {
@@ -253,6 +254,29 @@ impl StorageAdapter for RocksAdapter {
         Ok(())
     }
 
+    fn write_batch(&self, batch: StorageBatch) -> ProtocolResult<()> {
+        let mut write_batch = WriteBatch::default();
+        for (category, key, value) in batch.into_ops().into_iter() {
+            let column = self
+                .db
+                .cf_handle(map_category(category))
+                .ok_or(RocksDBError::CategoryNotFound(map_category(category)))?;
+
+            match value {
+                Some(value) => write_batch.put_cf(column, key, value),
+                None => write_batch.delete_cf(column, key),
+            }
+            .map_err(RocksDBError::from)?;
+        }
+
+        let mut opt = WriteOptions::default();
+        opt.set_sync(true);
+        self.db
+            .write_opt(&write_batch, &opt)
+            .map_err(RocksDBError::from)?;
+        Ok(())
+    }
+
     fn prepare_iter<'a, 'b: 'a, S: StorageSchema + 'static, P: AsRef<[u8]> + 'a>(
         &'b self,
         prefix: &'a P,
@@ -306,6 +330,7 @@ const C_EVM_CODE_MAP: &str = "c7";
 const C_EVM_STATE: &str = "c8";
 const C_METADATA_STATE: &str = "c9";
 const C_CKB_LIGHT_CLIENT_STATE: &str = "c10";
+const C_LOG_INDEX: &str = "c11";
 
 pub fn map_category(c: StorageCategory) -> &'static str {
     match c {
@@ -320,6 +345,7 @@ pub fn map_category(c: StorageCategory) -> &'static str {
         StorageCategory::MetadataState => C_METADATA_STATE,
         StorageCategory::CkbLightClientState => C_CKB_LIGHT_CLIENT_STATE,
         StorageCategory::Version => C_VERSION,
+        StorageCategory::LogIndex => C_LOG_INDEX,
     }
 }
 
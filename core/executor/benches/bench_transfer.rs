@@ -38,7 +38,7 @@ impl BenchAdapter {
         let db = RocksAdapter::new(DATA_PATH, Default::default()).unwrap();
 
         BenchAdapter {
-            trie_db: Arc::new(RocksTrieDB::new_evm(db.inner_db(), 1000)),
+            trie_db: Arc::new(RocksTrieDB::new_evm(db.inner_db(), 1000, None)),
             storage: Arc::new(ImplStorage::new(Arc::new(db), 100)),
         }
     }
@@ -29,7 +29,7 @@ pub fn new_storage() -> (RocksTrieDB, ImplStorage<RocksAdapter>) {
     let db = RocksAdapter::new(DATA_PATH, Default::default()).unwrap();
 
     (
-        RocksTrieDB::new_evm(db.inner_db(), 1000),
+        RocksTrieDB::new_evm(db.inner_db(), 1000, None),
         ImplStorage::new(Arc::new(db), 100),
     )
 }
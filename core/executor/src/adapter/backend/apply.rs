@@ -6,7 +6,7 @@ use protocol::traits::{
     ApplyBackend, Backend, Context, ExecutorAdapter, ExecutorReadOnlyAdapter, ReadOnlyStorage,
     Storage,
 };
-use protocol::trie::Trie;
+use protocol::trie::{Trie, DB as TrieDB};
 use protocol::types::{
     Account, Bytes, ExecutorContext, Hasher, Log, MerkleRoot, H160, H256, NIL_DATA, RLP_NULL, U256,
     U64,
@@ -104,6 +104,10 @@ where
     fn original_storage(&self, address: H160, key: H256) -> Option<H256> {
         self.inner.original_storage(address, key)
     }
+
+    fn transient_storage(&self, address: H160, key: H256) -> H256 {
+        self.inner.transient_storage(address, key)
+    }
 }
 
 impl<S, DB> ExecutorAdapter for AxonExecutorApplyAdapter<S, DB>
@@ -126,7 +130,9 @@ where
     }
 
     fn commit(&mut self) -> MerkleRoot {
-        self.inner.trie.commit().unwrap()
+        let root = self.inner.trie.commit().unwrap();
+        self.inner.db.flush().unwrap();
+        root
     }
 
     fn save_account(&mut self, address: &H160, account: &Account) {
@@ -1,2 +1,3 @@
 pub mod apply;
+pub mod overlay;
 pub mod read_only;
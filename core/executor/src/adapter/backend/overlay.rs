@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+
+use evm::backend::{Apply, Basic};
+
+use protocol::traits::{
+    ApplyBackend, Backend, ExecutorAdapter, ExecutorReadOnlyAdapter, ReadOnlyStorage,
+};
+use protocol::types::{
+    Account, AccountOverride, Bytes, ExecutorContext, Hasher, Hex, Log, MerkleRoot, StateOverride,
+    H160, H256, NIL_DATA, U256, U64,
+};
+use protocol::{codec::ProtocolCodec, trie};
+
+use crate::adapter::AxonExecutorReadOnlyAdapter;
+
+#[derive(Default, Clone, Debug)]
+struct OverlayAccount {
+    basic:          Basic,
+    code:           Option<Vec<u8>>,
+    storage:        BTreeMap<H256, H256>,
+    reset_storage:  bool,
+    deleted:        bool,
+    /// The storage committed by the applied transactions, which excludes the
+    /// state overrides.
+    original:       BTreeMap<H256, H256>,
+    reset_original: bool,
+}
+
+/// An executor adapter which keeps every state change in memory on top of a
+/// read-only state. It is used to re-execute transactions for RPC purposes,
+/// so the state MPT and the storage are never modified.
+pub struct AxonExecutorOverlayAdapter<S, DB: trie::DB> {
+    inner:    AxonExecutorReadOnlyAdapter<S, DB>,
+    accounts: BTreeMap<H160, OverlayAccount>,
+    logs:     Vec<Log>,
+}
+
+impl<S, DB> ExecutorReadOnlyAdapter for AxonExecutorOverlayAdapter<S, DB>
+where
+    S: ReadOnlyStorage + 'static,
+    DB: trie::DB + 'static,
+{
+    fn get_ctx(&self) -> ExecutorContext {
+        self.inner.get_ctx()
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Bytes> {
+        if key.len() == H160::len_bytes() {
+            let address = H160::from_slice(key);
+            if self.accounts.contains_key(&address) {
+                return Some(self.get_account(&address).encode().unwrap());
+            }
+        }
+
+        self.inner.get(key)
+    }
+
+    fn get_account(&self, address: &H160) -> Account {
+        let mut account = self.inner.get_account(address);
+
+        if let Some(overlay) = self.accounts.get(address) {
+            account.nonce = overlay.basic.nonce;
+            account.balance = overlay.basic.balance;
+
+            if let Some(code) = overlay.code.as_ref() {
+                account.code_hash = if code.is_empty() {
+                    NIL_DATA
+                } else {
+                    Hasher::digest(code)
+                };
+            }
+        }
+
+        account
+    }
+}
+
+impl<S, DB> Backend for AxonExecutorOverlayAdapter<S, DB>
+where
+    S: ReadOnlyStorage + 'static,
+    DB: trie::DB + 'static,
+{
+    fn gas_price(&self) -> U256 {
+        self.inner.gas_price()
+    }
+
+    fn origin(&self) -> H160 {
+        self.inner.origin()
+    }
+
+    fn block_hash(&self, number: U256) -> H256 {
+        self.inner.block_hash(number)
+    }
+
+    fn block_number(&self) -> U256 {
+        self.inner.block_number()
+    }
+
+    fn block_coinbase(&self) -> H160 {
+        self.inner.block_coinbase()
+    }
+
+    fn block_timestamp(&self) -> U256 {
+        self.inner.block_timestamp()
+    }
+
+    fn block_difficulty(&self) -> U256 {
+        self.inner.block_difficulty()
+    }
+
+    fn block_gas_limit(&self) -> U256 {
+        self.inner.block_gas_limit()
+    }
+
+    fn block_base_fee_per_gas(&self) -> U256 {
+        self.inner.block_base_fee_per_gas()
+    }
+
+    fn chain_id(&self) -> U256 {
+        self.inner.chain_id()
+    }
+
+    fn exists(&self, address: H160) -> bool {
+        match self.accounts.get(&address) {
+            Some(account) => !account.deleted,
+            None => self.inner.exists(address),
+        }
+    }
+
+    fn basic(&self, address: H160) -> Basic {
+        match self.accounts.get(&address) {
+            Some(account) => account.basic.clone(),
+            None => self.inner.basic(address),
+        }
+    }
+
+    fn code(&self, address: H160) -> Vec<u8> {
+        match self.accounts.get(&address).and_then(|a| a.code.as_ref()) {
+            Some(code) => code.clone(),
+            None => self.inner.code(address),
+        }
+    }
+
+    fn storage(&self, address: H160, index: H256) -> H256 {
+        match self.accounts.get(&address) {
+            Some(account) => match account.storage.get(&index) {
+                Some(value) => *value,
+                None if account.reset_storage => H256::default(),
+                None => self.inner.storage(address, index),
+            },
+            None => self.inner.storage(address, index),
+        }
+    }
+
+    // The state overrides are not the original values, the original values are
+    // the ones of the underlying state or committed by the applied
+    // transactions.
+    fn original_storage(&self, address: H160, index: H256) -> Option<H256> {
+        match self.accounts.get(&address) {
+            Some(account) => match account.original.get(&index) {
+                Some(value) => Some(*value),
+                None if account.reset_original => Some(H256::default()),
+                None => self.inner.original_storage(address, index),
+            },
+            None => self.inner.original_storage(address, index),
+        }
+    }
+
+    fn transient_storage(&self, address: H160, index: H256) -> H256 {
+        self.inner.transient_storage(address, index)
+    }
+}
+
+impl<S, DB> ApplyBackend for AxonExecutorOverlayAdapter<S, DB>
+where
+    S: ReadOnlyStorage + 'static,
+    DB: trie::DB + 'static,
+{
+    fn apply<A, I, L>(&mut self, values: A, logs: L, delete_empty: bool)
+    where
+        A: IntoIterator<Item = Apply<I>>,
+        I: IntoIterator<Item = (H256, H256)>,
+        L: IntoIterator<Item = Log>,
+    {
+        for apply in values.into_iter() {
+            match apply {
+                Apply::Modify {
+                    address,
+                    basic,
+                    code,
+                    storage,
+                    reset_storage,
+                } => {
+                    let account = self.overlay_account(address);
+                    account.basic = basic;
+                    account.deleted = false;
+
+                    if code.is_some() {
+                        account.code = code;
+                    }
+
+                    if reset_storage {
+                        account.storage.clear();
+                        account.reset_storage = true;
+                        account.original.clear();
+                        account.reset_original = true;
+                    }
+
+                    for (index, value) in storage.into_iter() {
+                        account.storage.insert(index, value);
+                        account.original.insert(index, value);
+                    }
+
+                    let is_empty = account.basic.balance.is_zero()
+                        && account.basic.nonce.is_zero()
+                        && account.code.as_ref().map(Vec::is_empty).unwrap_or(false);
+
+                    if is_empty && delete_empty {
+                        self.delete_account(address);
+                    }
+                }
+                Apply::Delete { address } => self.delete_account(address),
+            }
+        }
+
+        self.logs = logs.into_iter().collect::<Vec<_>>();
+    }
+}
+
+impl<S, DB> ExecutorAdapter for AxonExecutorOverlayAdapter<S, DB>
+where
+    S: ReadOnlyStorage + 'static,
+    DB: trie::DB + 'static,
+{
+    fn set_origin(&mut self, origin: H160) {
+        self.inner.exec_ctx.origin = origin;
+    }
+
+    fn set_gas_price(&mut self, gas_price: U64) {
+        self.inner.exec_ctx.gas_price = gas_price.low_u64().into();
+    }
+
+    fn take_logs(&mut self) -> Vec<Log> {
+        let mut ret = Vec::new();
+        ret.append(&mut self.logs);
+        ret
+    }
+
+    // The overlay state is never committed, the returned value is the root of
+    // the underlying state.
+    fn commit(&mut self) -> MerkleRoot {
+        self.inner.trie.commit().unwrap()
+    }
+
+    fn save_account(&mut self, address: &H160, account: &Account) {
+        let overlay = self.overlay_account(*address);
+        overlay.basic = Basic {
+            balance: account.balance,
+            nonce:   account.nonce,
+        };
+        overlay.deleted = false;
+    }
+}
+
+impl<S, DB> AxonExecutorOverlayAdapter<S, DB>
+where
+    S: ReadOnlyStorage + 'static,
+    DB: trie::DB + 'static,
+{
+    pub fn new(inner: AxonExecutorReadOnlyAdapter<S, DB>) -> Self {
+        AxonExecutorOverlayAdapter {
+            inner,
+            accounts: BTreeMap::new(),
+            logs: Vec::new(),
+        }
+    }
+
+    /// Move to another block context, the state changes are kept.
+    pub fn set_block_context(&mut self, exec_ctx: ExecutorContext) {
+        self.inner.exec_ctx = exec_ctx;
+    }
+
+    /// Apply the state overrides of an RPC call. Like the other changes, they
+    /// are only kept in memory.
+    pub fn apply_state_override(&mut self, state_override: &StateOverride) {
+        for (address, item) in state_override.iter() {
+            let account = self.overlay_account(*address);
+
+            if let Some(balance) = item.balance {
+                account.basic.balance = balance;
+            }
+
+            if let Some(nonce) = item.nonce {
+                account.basic.nonce = nonce.low_u64().into();
+            }
+
+            if let Some(code) = item.code.as_ref() {
+                account.code = Some(code.as_bytes().to_vec());
+            }
+
+            if let Some(state) = item.state.as_ref() {
+                account.storage = state.clone();
+                account.reset_storage = true;
+            }
+
+            if let Some(state_diff) = item.state_diff.as_ref() {
+                account.storage.extend(state_diff.clone());
+            }
+        }
+    }
+
+    /// Export the state changes as the overrides of the underlying state, so
+    /// they can be applied again by `apply_state_override`.
+    pub fn state_override(&self) -> StateOverride {
+        self.accounts
+            .iter()
+            .map(|(address, account)| {
+                (*address, AccountOverride {
+                    balance:    Some(account.basic.balance),
+                    nonce:      Some(account.basic.nonce.low_u64().into()),
+                    code:       account.code.as_ref().map(Hex::encode),
+                    state:      account.reset_storage.then(|| account.storage.clone()),
+                    state_diff: (!account.reset_storage).then(|| account.storage.clone()),
+                })
+            })
+            .collect()
+    }
+
+    fn overlay_account(&mut self, address: H160) -> &mut OverlayAccount {
+        let inner = &self.inner;
+        self.accounts
+            .entry(address)
+            .or_insert_with(|| OverlayAccount {
+                basic: inner.basic(address),
+                ..Default::default()
+            })
+    }
+
+    fn delete_account(&mut self, address: H160) {
+        self.accounts.insert(address, OverlayAccount {
+            basic:          Basic::default(),
+            code:           Some(Vec::new()),
+            storage:        BTreeMap::new(),
+            reset_storage:  true,
+            deleted:        true,
+            original:       BTreeMap::new(),
+            reset_original: true,
+        });
+    }
+}
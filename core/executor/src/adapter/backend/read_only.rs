@@ -192,6 +192,12 @@ where
         // Fixme
         Some(self.storage(address, index))
     }
+
+    // The transient storage is discarded after every transaction, which is
+    // kept in the stack state of the EVM.
+    fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+        H256::zero()
+    }
 }
 
 impl<S, DB> AxonExecutorReadOnlyAdapter<S, DB>
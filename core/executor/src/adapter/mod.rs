@@ -1,7 +1,10 @@
 mod backend;
 mod trie;
 
-pub use backend::{apply::AxonExecutorApplyAdapter, read_only::AxonExecutorReadOnlyAdapter};
+pub use backend::{
+    apply::AxonExecutorApplyAdapter, overlay::AxonExecutorOverlayAdapter,
+    read_only::AxonExecutorReadOnlyAdapter,
+};
 pub use trie::{db::RocksTrieDB, wrapped::MPTTrie};
 
 #[macro_export]
@@ -1,20 +1,37 @@
 use std::{collections::HashMap, io, sync::Arc};
 
-use parking_lot::RwLock;
-use rocksdb::ops::{GetCF, GetColumnFamilys, PutCF, WriteOps};
+use parking_lot::{Mutex, RwLock};
+use rocksdb::ops::{DeleteCF, GetCF, GetColumnFamilys, IterateCF, PutCF, WriteOps};
 use rocksdb::{ColumnFamily, WriteBatch, DB};
 
 use common_apm::metrics::storage::{on_storage_get_state, on_storage_put_state};
 use common_apm::Instant;
 use protocol::rand::{rngs::SmallRng, Rng, SeedableRng};
 use protocol::traits::StateStorageCategory;
-use protocol::trie;
+use protocol::{trie, types::RLP_NULL};
 
 use core_db::map_category;
 
+use crate::CURRENT_BLOCK_NUMBER;
+
 // 49999 is the largest prime number within 50000.
 const RAND_SEED: u64 = 49999;
 
+// The pruning bookkeeping shares the column family with the trie nodes, the
+// lengths of these keys never collide with the 32 bytes node hashes.
+// The reference count of a node: `REF_COUNT_PREFIX ++ hash => u64`.
+const REF_COUNT_PREFIX: u8 = 0x00;
+// A node dereferenced in a block: `JOURNAL_PREFIX ++ block_number ++ hash`.
+const JOURNAL_PREFIX: u8 = 0x01;
+// The largest block number whose journal has been pruned.
+const PRUNED_MARKER_KEY: [u8; 1] = [0x02];
+// The largest block number whose reference counts and journal have been
+// written, a block replayed after a crash is not counted again.
+const COMMITTED_MARKER_KEY: [u8; 1] = [0x03];
+const JOURNAL_KEY_LEN: usize = 1 + 8 + 32;
+// Limit the eras pruned by a commit to keep the block execution fast.
+const MAX_PRUNE_ERAS_PER_COMMIT: u64 = 16;
+
 macro_rules! db {
     ($db:expr, $op:ident, $column:expr$ (, $args: expr)*) => {
         $db.$op($column, $($args,)*).map_err(|e| {
@@ -27,15 +44,32 @@ macro_rules! db {
 }
 
 pub struct RocksTrieDB {
-    db:         Arc<DB>,
-    category:   StateStorageCategory,
-    cache:      RwLock<HashMap<Vec<u8>, Vec<u8>>>,
-    cache_size: usize,
+    db:          Arc<DB>,
+    category:    StateStorageCategory,
+    cache:       RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    cache_size:  usize,
+    // Prune the nodes dereferenced more than `keep_latest` blocks ago.
+    keep_latest: Option<u64>,
+    // The writes of the executing block, they are written in one batch when
+    // the block is committed by `flush`.
+    pending:     Mutex<PendingBlock>,
+}
+
+#[derive(Default)]
+struct PendingBlock {
+    number:  Option<u64>,
+    // The block has been committed before a crash, so its reference counts and
+    // journal must not be written again.
+    replay:  bool,
+    nodes:   HashMap<Vec<u8>, Vec<u8>>,
+    counts:  HashMap<Vec<u8>, u64>,
+    journal: Vec<Vec<u8>>,
 }
 
 impl trie::DB for RocksTrieDB {
     fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, io::Error> {
-        let res = { self.cache.read().get(key).cloned() };
+        let res = { self.cache.read().get(key).cloned() }
+            .or_else(|| self.pending.lock().nodes.get(key).cloned());
 
         if res.is_none() {
             let inst = Instant::now();
@@ -46,7 +80,7 @@ impl trie::DB for RocksTrieDB {
                 {
                     self.cache.write().insert(key.to_owned(), val.to_vec());
                 }
-                self.flush()?;
+                self.shrink_cache();
             }
 
             return Ok(ret.map(|r| r.to_vec()));
@@ -56,7 +90,8 @@ impl trie::DB for RocksTrieDB {
     }
 
     fn contains(&self, key: &[u8]) -> Result<bool, io::Error> {
-        let res = { self.cache.read().contains_key(key) };
+        let res =
+            { self.cache.read().contains_key(key) } || self.pending.lock().nodes.contains_key(key);
 
         if res {
             Ok(true)
@@ -69,19 +104,11 @@ impl trie::DB for RocksTrieDB {
     }
 
     fn insert(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), io::Error> {
-        let inst = Instant::now();
-        let size = key.len() + value.len();
-
-        db!(self.db, put_cf, self.get_column(), &key, &value);
-
-        {
-            self.cache.write().insert(key, value);
-        }
-
-        on_storage_put_state(inst.elapsed(), size as f64);
-        self.flush()
+        self.insert_batch(vec![key], vec![value])
     }
 
+    /// The nodes written by an executing block are staged until the block is
+    /// committed by `flush`, other nodes are written immediately.
     fn insert_batch(&self, keys: Vec<Vec<u8>>, values: Vec<Vec<u8>>) -> Result<(), io::Error> {
         if keys.len() != values.len() {
             return Err(io::Error::new(
@@ -90,8 +117,24 @@ impl trie::DB for RocksTrieDB {
             ));
         }
 
+        if let Some(number) = self.pruning_block_number() {
+            let mut pending = self.pending.lock();
+            self.stage_block(&mut pending, number)?;
+
+            for (key, val) in keys.into_iter().zip(values.into_iter()) {
+                if !pending.replay {
+                    self.reference(&mut pending.counts, &key)?;
+                }
+                pending.nodes.insert(key, val);
+            }
+
+            return Ok(());
+        }
+
         let mut total_size = 0;
         let mut batch = WriteBatch::default();
+        let mut counts = HashMap::new();
+        let column = self.get_column();
 
         {
             let mut cache = self.cache.write();
@@ -99,74 +142,309 @@ impl trie::DB for RocksTrieDB {
                 total_size += key.len();
                 total_size += val.len();
 
-                let column = self.get_column();
+                if self.keep_latest.is_some() {
+                    self.reference(&mut counts, &key)?;
+                }
                 db!(batch, put_cf, column, &key, &val);
                 cache.insert(key, val);
             }
         }
 
+        for (count_key, count) in counts.iter() {
+            db!(batch, put_cf, column, count_key, count.to_be_bytes());
+        }
+
         let inst = Instant::now();
-        self.db
-            .write(&batch)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("rocksdb error: {:?}", e)))?;
+        self.write(&batch)?;
         on_storage_put_state(inst.elapsed(), total_size as f64);
 
-        self.flush()
+        self.shrink_cache();
+        Ok(())
     }
 
-    fn remove(&self, _key: &[u8]) -> Result<(), io::Error> {
-        Ok(())
+    fn remove(&self, key: &[u8]) -> Result<(), io::Error> {
+        self.remove_batch(&[key.to_vec()])
     }
 
-    fn remove_batch(&self, _keys: &[Vec<u8>]) -> Result<(), io::Error> {
+    /// The removed nodes are not deleted immediately, they are journaled with
+    /// the current block number and dereferenced after `keep_latest` blocks,
+    /// so the recent states can still be read. Nothing is removed in archive
+    /// mode or outside of a block execution.
+    fn remove_batch(&self, keys: &[Vec<u8>]) -> Result<(), io::Error> {
+        let number = match self.pruning_block_number() {
+            Some(number) => number,
+            None => return Ok(()),
+        };
+
+        let mut pending = self.pending.lock();
+        self.stage_block(&mut pending, number)?;
+
+        if !pending.replay {
+            pending.journal.extend(
+                keys.iter()
+                    .filter(|k| k.len() == 32)
+                    .map(|k| journal_key(number, k)),
+            );
+        }
+
         Ok(())
     }
 
+    /// Commit the executing block, its nodes, reference counts and journal are
+    /// written in one batch with the committed marker. Then the eras older than
+    /// the latest `keep_latest` blocks are pruned, even if the block writes
+    /// nothing to this trie.
     fn flush(&self) -> Result<(), io::Error> {
-        let mut cache = self.cache.write();
+        let pending = {
+            let mut pending = self.pending.lock();
+            if let Some(number) = self.pruning_block_number() {
+                self.stage_block(&mut pending, number)?;
+            }
+            std::mem::take(&mut *pending)
+        };
+        let (number, keep_latest) = match (pending.number, self.keep_latest) {
+            (Some(number), Some(keep)) => (number, keep),
+            _ => return Ok(()),
+        };
 
-        let len = cache.len();
+        let column = self.get_column();
+        let mut total_size = 0;
+        let mut batch = WriteBatch::default();
+        for (key, val) in pending.nodes.iter() {
+            total_size += key.len() + val.len();
+            db!(batch, put_cf, column, key, val);
+        }
 
-        if len <= self.cache_size * 2 {
-            return Ok(());
+        if !pending.replay {
+            for (count_key, count) in pending.counts.iter() {
+                db!(batch, put_cf, column, count_key, count.to_be_bytes());
+            }
+            for key in pending.journal.iter() {
+                db!(batch, put_cf, column, key, b"");
+            }
+            db!(
+                batch,
+                put_cf,
+                column,
+                COMMITTED_MARKER_KEY,
+                number.to_be_bytes()
+            );
         }
 
-        let remove_list = {
-            let keys = cache.iter().map(|(k, _)| k).collect::<Vec<_>>();
-            rand_remove_list(keys, len - self.cache_size)
-        };
+        let inst = Instant::now();
+        self.write(&batch)?;
+        on_storage_put_state(inst.elapsed(), total_size as f64);
 
-        for item in remove_list {
-            cache.remove(&item);
+        {
+            self.cache.write().extend(pending.nodes);
         }
+        self.shrink_cache();
 
-        Ok(())
+        self.prune((number + 1).saturating_sub(keep_latest))
     }
 }
 
 impl RocksTrieDB {
-    pub fn new_evm(db: Arc<DB>, cache_size: usize) -> Self {
-        Self::new(db, StateStorageCategory::EvmState, cache_size)
+    /// Create the trie database of the world state, `state_pruning` is the
+    /// number of the latest block states kept, `None` means archive mode.
+    pub fn new_evm(db: Arc<DB>, cache_size: usize, state_pruning: Option<u64>) -> Self {
+        Self::new(
+            db,
+            StateStorageCategory::EvmState,
+            cache_size,
+            state_pruning,
+        )
     }
 
-    pub fn new_metadata(db: Arc<DB>, cache_size: usize) -> Self {
-        Self::new(db, StateStorageCategory::MetadataState, cache_size)
+    pub fn new_metadata(db: Arc<DB>, cache_size: usize, state_pruning: Option<u64>) -> Self {
+        Self::new(
+            db,
+            StateStorageCategory::MetadataState,
+            cache_size,
+            state_pruning,
+        )
     }
 
-    pub fn new_ckb_light_client(db: Arc<DB>, cache_size: usize) -> Self {
-        Self::new(db, StateStorageCategory::CkbLightClientState, cache_size)
+    pub fn new_ckb_light_client(
+        db: Arc<DB>,
+        cache_size: usize,
+        state_pruning: Option<u64>,
+    ) -> Self {
+        Self::new(
+            db,
+            StateStorageCategory::CkbLightClientState,
+            cache_size,
+            state_pruning,
+        )
     }
 
-    fn new(db: Arc<DB>, category: StateStorageCategory, cache_size: usize) -> Self {
+    fn new(
+        db: Arc<DB>,
+        category: StateStorageCategory,
+        cache_size: usize,
+        keep_latest: Option<u64>,
+    ) -> Self {
         let cache = RwLock::new(HashMap::with_capacity(cache_size));
+        let keep_latest = keep_latest.map(|n| n.max(1));
+
         RocksTrieDB {
             db,
             category,
             cache,
             cache_size,
+            keep_latest,
+            pending: Mutex::new(PendingBlock::default()),
         }
     }
 
+    /// The number of the executing block if the state is pruned.
+    fn pruning_block_number(&self) -> Option<u64> {
+        self.keep_latest
+            .and(CURRENT_BLOCK_NUMBER.with(|n| *n.borrow()))
+    }
+
+    /// Start staging the writes of the block `number`. The writes left by an
+    /// aborted block are dropped.
+    fn stage_block(&self, pending: &mut PendingBlock, number: u64) -> Result<(), io::Error> {
+        if pending.number == Some(number) {
+            return Ok(());
+        }
+
+        let committed = db!(self.db, get_cf, self.get_column(), COMMITTED_MARKER_KEY)
+            .map(|marker| decode_u64(&marker));
+        *pending = PendingBlock {
+            number: Some(number),
+            replay: committed.map(|c| c >= number).unwrap_or(false),
+            ..Default::default()
+        };
+
+        Ok(())
+    }
+
+    /// Increase the reference count of a node. The counts are collapsed in
+    /// `counts` before they are written, so a node inserted twice is counted
+    /// twice. A node written in archive mode has no count and is never pruned.
+    fn reference(&self, counts: &mut HashMap<Vec<u8>, u64>, key: &[u8]) -> Result<(), io::Error> {
+        let column = self.get_column();
+        let count_key = ref_count_key(key);
+        let count = match counts.get(&count_key) {
+            Some(count) => *count,
+            None => match db!(self.db, get_cf, column, &count_key) {
+                Some(count) => decode_u64(&count),
+                None if db!(self.db, get_cf, column, key).is_some() => return Ok(()),
+                None => 0,
+            },
+        };
+
+        counts.insert(count_key, count + 1);
+        Ok(())
+    }
+
+    /// Dereference the nodes journaled up to the block `until`. Every era is
+    /// pruned in an atomic batch with the pruned marker, so it is safe to
+    /// restart at any time.
+    fn prune(&self, until: u64) -> Result<(), io::Error> {
+        let column = self.get_column();
+        let pruned = match db!(self.db, get_cf, column, PRUNED_MARKER_KEY) {
+            Some(marker) => decode_u64(&marker),
+            None => {
+                // Nothing is journaled before the first pruning block.
+                let marker = until.saturating_sub(1);
+                db!(
+                    self.db,
+                    put_cf,
+                    column,
+                    PRUNED_MARKER_KEY,
+                    marker.to_be_bytes()
+                );
+                marker
+            }
+        };
+
+        for era in (pruned + 1)..=until.min(pruned + MAX_PRUNE_ERAS_PER_COMMIT) {
+            self.prune_era(era)?;
+        }
+
+        Ok(())
+    }
+
+    fn prune_era(&self, era: u64) -> Result<(), io::Error> {
+        let column = self.get_column();
+        let mut prefix = vec![JOURNAL_PREFIX];
+        prefix.extend_from_slice(&era.to_be_bytes());
+
+        let journal = db!(self.db, prefix_iterator_cf, column, &prefix)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .filter(|(k, _)| k.len() == JOURNAL_KEY_LEN)
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+
+        let mut batch = WriteBatch::default();
+        let mut deleted = Vec::new();
+        let mut counts = HashMap::new();
+        for key in journal.iter() {
+            let hash = &key[prefix.len()..];
+            db!(batch, delete_cf, column, key);
+
+            if hash == RLP_NULL.as_bytes() {
+                continue;
+            }
+
+            let count_key = ref_count_key(hash);
+            let count = match counts.get(&count_key) {
+                Some(count) => *count,
+                None => match db!(self.db, get_cf, column, &count_key) {
+                    Some(count) => decode_u64(&count),
+                    None => continue,
+                },
+            };
+
+            if count <= 1 {
+                db!(batch, delete_cf, column, hash);
+                db!(batch, delete_cf, column, &count_key);
+                deleted.push(hash.to_vec());
+            } else {
+                db!(batch, put_cf, column, &count_key, (count - 1).to_be_bytes());
+            }
+            counts.insert(count_key, count.saturating_sub(1));
+        }
+
+        db!(batch, put_cf, column, PRUNED_MARKER_KEY, era.to_be_bytes());
+        self.write(&batch)?;
+
+        let mut cache = self.cache.write();
+        for hash in deleted.iter() {
+            cache.remove(hash);
+        }
+
+        Ok(())
+    }
+
+    fn shrink_cache(&self) {
+        let mut cache = self.cache.write();
+
+        let len = cache.len();
+
+        if len <= self.cache_size * 2 {
+            return;
+        }
+
+        let remove_list = {
+            let keys = cache.iter().map(|(k, _)| k).collect::<Vec<_>>();
+            rand_remove_list(keys, len - self.cache_size)
+        };
+
+        for item in remove_list {
+            cache.remove(&item);
+        }
+    }
+
+    fn write(&self, batch: &WriteBatch) -> Result<(), io::Error> {
+        self.db
+            .write(batch)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("rocksdb error: {:?}", e)))
+    }
+
     fn get_column(&self) -> &ColumnFamily {
         let category = map_category(self.category.into());
         self.db
@@ -175,6 +453,28 @@ impl RocksTrieDB {
     }
 }
 
+fn ref_count_key(hash: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(hash.len() + 1);
+    key.push(REF_COUNT_PREFIX);
+    key.extend_from_slice(hash);
+    key
+}
+
+fn journal_key(number: u64, hash: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(JOURNAL_KEY_LEN);
+    key.push(JOURNAL_PREFIX);
+    key.extend_from_slice(&number.to_be_bytes());
+    key.extend_from_slice(hash);
+    key
+}
+
+fn decode_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    let len = bytes.len().min(8);
+    buf[8 - len..].copy_from_slice(&bytes[..len]);
+    u64::from_be_bytes(buf)
+}
+
 fn rand_remove_list<T: Clone>(keys: Vec<&T>, num: usize) -> impl Iterator<Item = T> {
     let mut len = keys.len() - 1;
     let mut idx_list = (0..len).collect::<Vec<_>>();
@@ -190,3 +490,163 @@ fn rand_remove_list<T: Clone>(keys: Vec<&T>, num: usize) -> impl Iterator<Item =
 
     ret.into_iter()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core_db::RocksAdapter;
+    use protocol::{
+        trie::{Trie, DB as _},
+        types::H256,
+    };
+
+    use crate::adapter::MPTTrie;
+
+    fn new_db(dir: &tempfile::TempDir, keep_latest: u64) -> Arc<RocksTrieDB> {
+        let inner_db =
+            Arc::new(RocksAdapter::new(dir.path(), Default::default()).unwrap()).inner_db();
+        Arc::new(RocksTrieDB::new_evm(inner_db, 100, Some(keep_latest)))
+    }
+
+    fn commit_block(
+        db: &RocksTrieDB,
+        mpt: &mut MPTTrie<RocksTrieDB>,
+        number: u64,
+        key: &[u8],
+        val: &[u8],
+    ) -> H256 {
+        CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = Some(number));
+        mpt.insert(key.to_vec(), val.to_vec()).unwrap();
+        let root = mpt.commit().unwrap();
+        db.flush().unwrap();
+        CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = None);
+        root
+    }
+
+    fn ref_count(db: &RocksTrieDB, hash: &[u8]) -> Option<u64> {
+        db.db
+            .get_cf(db.get_column(), ref_count_key(hash))
+            .unwrap()
+            .map(|count| decode_u64(&count))
+    }
+
+    #[test]
+    fn test_state_pruning_keep_latest() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = new_db(&dir, 2);
+        let mut mpt = MPTTrie::new(Arc::clone(&db));
+
+        let root_1 = commit_block(&db, &mut mpt, 1, b"key_1", b"value of block 1");
+        let root_2 = commit_block(&db, &mut mpt, 2, b"key_1", b"value of block 2");
+        assert!(MPTTrie::from_root(root_1, Arc::clone(&db)).is_ok());
+
+        // The states of block 2 and 3 are kept, the state of block 1 is pruned.
+        let root_3 = commit_block(&db, &mut mpt, 3, b"key_1", b"value of block 3");
+        assert!(MPTTrie::from_root(root_1, Arc::clone(&db)).is_err());
+        let mpt_2 = MPTTrie::from_root(root_2, Arc::clone(&db)).unwrap();
+        assert_eq!(
+            mpt_2.get(b"key_1").unwrap(),
+            Some(b"value of block 2".to_vec())
+        );
+
+        // The nodes which are not replaced are never pruned.
+        let root_4 = commit_block(&db, &mut mpt, 4, b"key_2", b"value of block 4");
+        assert!(MPTTrie::from_root(root_2, Arc::clone(&db)).is_err());
+        assert!(MPTTrie::from_root(root_3, Arc::clone(&db)).is_ok());
+        let mpt_4 = MPTTrie::from_root(root_4, Arc::clone(&db)).unwrap();
+        assert_eq!(
+            mpt_4.get(b"key_1").unwrap(),
+            Some(b"value of block 3".to_vec())
+        );
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_state_pruning_unchanged_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = new_db(&dir, 2);
+        let mut storage = MPTTrie::new(Arc::clone(&db));
+        let storage_root = commit_block(&db, &mut storage, 1, b"slot", b"value of block 1");
+
+        // The storage trie is committed in every block without being changed.
+        let mut mpt = MPTTrie::new(Arc::clone(&db));
+        for number in 2..=5u64 {
+            CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = Some(number));
+            let mut storage = MPTTrie::from_root(storage_root, Arc::clone(&db)).unwrap();
+            assert_eq!(storage.commit().unwrap(), storage_root);
+            mpt.insert(b"key_1".to_vec(), number.to_be_bytes().to_vec())
+                .unwrap();
+            mpt.commit().unwrap();
+            db.flush().unwrap();
+            CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = None);
+        }
+
+        let storage = MPTTrie::from_root(storage_root, Arc::clone(&db)).unwrap();
+        assert_eq!(
+            storage.get(b"slot").unwrap(),
+            Some(b"value of block 1".to_vec())
+        );
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_state_pruning_duplicate_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = new_db(&dir, 2);
+        let hash = H256::repeat_byte(0x01).as_bytes().to_vec();
+        let node = b"node".to_vec();
+
+        CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = Some(1));
+        db.insert_batch(vec![hash.clone(), hash.clone()], vec![node.clone(), node])
+            .unwrap();
+        db.flush().unwrap();
+        CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = None);
+
+        assert_eq!(ref_count(&db, &hash), Some(2));
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_state_pruning_crash_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = new_db(&dir, 2);
+
+        // Nothing of a block is written before it is committed.
+        CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = Some(1));
+        let mut mpt = MPTTrie::new(Arc::clone(&db));
+        mpt.insert(b"key_1".to_vec(), b"value of block 1".to_vec())
+            .unwrap();
+        let root_1 = mpt.commit().unwrap();
+        CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = None);
+        let db = Arc::new(RocksTrieDB::new_evm(Arc::clone(&db.db), 100, Some(2)));
+        assert!(MPTTrie::from_root(root_1, Arc::clone(&db)).is_err());
+        assert_eq!(ref_count(&db, root_1.as_bytes()), None);
+
+        let root = commit_block(
+            &db,
+            &mut MPTTrie::new(Arc::clone(&db)),
+            1,
+            b"key_1",
+            b"value of block 1",
+        );
+        assert_eq!(root, root_1);
+        assert_eq!(ref_count(&db, root_1.as_bytes()), Some(1));
+
+        // A committed block replayed after a crash is not counted again.
+        let root = commit_block(
+            &db,
+            &mut MPTTrie::new(Arc::clone(&db)),
+            1,
+            b"key_1",
+            b"value of block 1",
+        );
+        assert_eq!(root, root_1);
+        assert_eq!(ref_count(&db, root_1.as_bytes()), Some(1));
+
+        dir.close().unwrap();
+    }
+}
@@ -1,36 +1,65 @@
 use std::sync::Arc;
 
 use hasher::HasherKeccak;
+use thiserror::Error;
 
 use protocol::trie::{PatriciaTrie, Trie, TrieError, DB as TrieDB};
-use protocol::types::{Hasher, MerkleRoot};
-use protocol::ProtocolResult;
+use protocol::types::{Hasher, MerkleRoot, RLP_NULL};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
-pub struct MPTTrie<DB: TrieDB>(PatriciaTrie<DB, HasherKeccak>);
+#[derive(Error, Debug)]
+pub enum MPTTrieError {
+    #[error("The state of root {0:?} is missing, it may have been pruned")]
+    MissingState(MerkleRoot),
+}
+
+impl From<MPTTrieError> for ProtocolError {
+    fn from(error: MPTTrieError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Executor, Box::new(error))
+    }
+}
+
+pub struct MPTTrie<DB: TrieDB> {
+    trie: PatriciaTrie<DB, HasherKeccak>,
+    db:   Arc<DB>,
+    // The root of the last commit.
+    root: MerkleRoot,
+}
 
 impl<DB: TrieDB> Trie<DB, HasherKeccak> for MPTTrie<DB> {
     fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, TrieError> {
-        self.0.get(&Hasher::digest(key).0)
+        self.trie.get(&Hasher::digest(key).0)
     }
 
     fn contains(&self, key: &[u8]) -> Result<bool, TrieError> {
-        self.0.contains(&Hasher::digest(key).0)
+        self.trie.contains(&Hasher::digest(key).0)
     }
 
     fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<(), TrieError> {
-        self.0.insert(Hasher::digest(key).0.to_vec(), value)
+        self.trie.insert(Hasher::digest(key).0.to_vec(), value)
     }
 
     fn remove(&mut self, key: &[u8]) -> Result<bool, TrieError> {
-        self.0.remove(&Hasher::digest(key).0)
+        self.trie.remove(&Hasher::digest(key).0)
     }
 
+    /// The trie decodes its root node directly instead of passing its hash,
+    /// so a replaced root is removed here to be journaled for state pruning.
     fn root(&mut self) -> Result<Vec<u8>, TrieError> {
-        self.0.root()
+        let root = self.trie.root()?;
+
+        if root != self.root.as_bytes() && self.root != RLP_NULL {
+            self.db
+                .remove(self.root.as_bytes())
+                .map_err(|e| TrieError::DB(e.to_string()))?;
+        }
+
+        self.root = MerkleRoot::from_slice(&root);
+        Ok(root)
     }
 
     fn get_proof(&self, key: &[u8]) -> Result<Vec<Vec<u8>>, TrieError> {
-        self.0.get_proof(&Hasher::digest(key).0)
+        self.trie.get_proof(&Hasher::digest(key).0)
     }
 
     fn verify_proof(
@@ -39,27 +68,35 @@ impl<DB: TrieDB> Trie<DB, HasherKeccak> for MPTTrie<DB> {
         key: &[u8],
         proof: Vec<Vec<u8>>,
     ) -> Result<Option<Vec<u8>>, TrieError> {
-        self.0
+        self.trie
             .verify_proof(root_hash, &Hasher::digest(key).0, proof)
     }
 }
 
 impl<DB: TrieDB> MPTTrie<DB> {
     pub fn new(db: Arc<DB>) -> Self {
-        MPTTrie(PatriciaTrie::new(db, Arc::new(HasherKeccak::new())))
+        let trie = PatriciaTrie::new(Arc::clone(&db), Arc::new(HasherKeccak::new()));
+        MPTTrie {
+            trie,
+            db,
+            root: RLP_NULL,
+        }
     }
 
     pub fn from_root(root: MerkleRoot, db: Arc<DB>) -> ProtocolResult<Self> {
-        Ok(MPTTrie(PatriciaTrie::from(
-            db,
+        match PatriciaTrie::from(
+            Arc::clone(&db),
             Arc::new(HasherKeccak::new()),
             root.as_bytes(),
-        )?))
+        ) {
+            Ok(trie) => Ok(MPTTrie { trie, db, root }),
+            Err(TrieError::InvalidStateRoot) => Err(MPTTrieError::MissingState(root).into()),
+            Err(e) => Err(e.into()),
+        }
     }
 
     pub fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
-        self.0
-            .root()
+        self.root()
             .map(|r| MerkleRoot::from_slice(&r))
             .map_err(Into::into)
     }
@@ -83,7 +120,7 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         let inner_db =
             Arc::new(RocksAdapter::new(dir.path(), Default::default()).unwrap()).inner_db();
-        let db = RocksTrieDB::new_evm(inner_db, 100);
+        let db = RocksTrieDB::new_evm(inner_db, 100, None);
         let mut mpt = MPTTrie::new(Arc::new(db));
 
         let key_1 = rand_bytes(5);
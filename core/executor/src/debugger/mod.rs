@@ -36,7 +36,7 @@ impl EvmDebugger {
         db_state_path.push_str("/state");
         let _ = std::fs::create_dir_all(&db_state_path);
         let inner_db = rocks_adapter.inner_db();
-        let trie = Arc::new(RocksTrieDB::new_evm(inner_db, 1000));
+        let trie = Arc::new(RocksTrieDB::new_evm(inner_db, 1000, None));
 
         let mut mpt = MPTTrie::new(Arc::clone(&trie));
 
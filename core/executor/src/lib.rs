@@ -5,20 +5,24 @@ mod precompiles;
 pub mod system_contract;
 #[cfg(test)]
 mod tests;
+mod tracer;
 mod utils;
 
 pub use crate::adapter::{
-    AxonExecutorApplyAdapter, AxonExecutorReadOnlyAdapter, MPTTrie, RocksTrieDB,
+    AxonExecutorApplyAdapter, AxonExecutorOverlayAdapter, AxonExecutorReadOnlyAdapter, MPTTrie,
+    RocksTrieDB,
 };
 pub use crate::system_contract::{
     is_call_system_script, is_system_contract_address_format,
     metadata::{MetadataHandle, HARDFORK_INFO},
     DataProvider,
 };
+pub use crate::tracer::{exit_error, TraceCancelled};
 pub use crate::utils::{code_address, decode_revert_msg, DefaultFeeAllocator, FeeInlet};
 
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{atomic::AtomicBool, Arc};
 
 use arc_swap::ArcSwap;
 use common_config_parser::types::spec::HardforkName;
@@ -26,18 +30,27 @@ use evm::executor::stack::{MemoryStackState, PrecompileFn, StackExecutor, StackS
 use evm::CreateScheme;
 
 use common_merkle::TrieMerkle;
+use protocol::constants::BASE_FEE_PER_GAS;
 use protocol::traits::{Backend, Executor, ExecutorAdapter};
 use protocol::types::{
-    logs_bloom, Config, ExecResp, SignedTransaction, TransactionAction, TxResp, ValidatorExtend,
-    H160, H256, RLP_NULL, U256,
+    logs_bloom, AccessList, Bytes, Config, ExecResp, HardforkInfo, Header, MerkleRoot,
+    SignedTransaction, SimulateCall, TraceResult, Tracer, TransactionAction, TxResp,
+    ValidatorExtend, H160, H256, RLP_NULL, U256, U64,
 };
 
 use crate::precompiles::build_precompile_set;
 use crate::system_contract::{
-    after_block_hook, before_block_hook, system_contract_dispatch,
+    after_block_hook, before_block_hook, flush_system_contract_dbs, system_contract_dispatch,
     CKB_LIGHT_CLIENT_CONTRACT_ADDRESS, HEADER_CELL_ROOT_KEY, METADATA_CONTRACT_ADDRESS,
     METADATA_ROOT_KEY,
 };
+use crate::tracer::{
+    trace_until, trace_using, AccessListTracer, CallTracer, NoopTracer, StructLogger,
+};
+
+/// The accessed slots may depend on the supplied access list through the gas
+/// left, so the executions of `create_access_list` are bounded.
+const MAX_ACCESS_LIST_ITERATIONS: usize = 10;
 
 lazy_static::lazy_static! {
     pub static ref FEE_ALLOCATOR: ArcSwap<Box<dyn FeeAllocate>> = ArcSwap::from_pointee(Box::new(DefaultFeeAllocator));
@@ -46,6 +59,13 @@ lazy_static::lazy_static! {
 thread_local! {
     pub(crate) static CURRENT_HEADER_CELL_ROOT: RefCell<H256> = RefCell::new(H256::default());
     pub(crate) static CURRENT_METADATA_ROOT: RefCell<H256> = RefCell::new(H256::default());
+    // The number of the executing block, the trie writes of the block are staged
+    // until it is committed and the removed nodes are journaled with it for
+    // state pruning.
+    pub(crate) static CURRENT_BLOCK_NUMBER: RefCell<Option<u64>> = RefCell::new(None);
+    // The hardforks enabled for the executing block, which are read from the
+    // metadata of its parent state as the consensus does.
+    pub(crate) static CURRENT_HARDFORKS: RefCell<H256> = RefCell::new(H256::default());
 }
 
 pub trait FeeAllocate: Sync + Send {
@@ -71,59 +91,11 @@ impl Executor for AxonExecutor {
         to: Option<H160>,
         value: U256,
         data: Vec<u8>,
-        is_estimate: bool,
+        _is_estimate: bool,
     ) -> TxResp {
-        self.init_local_system_contract_roots(backend);
-        let config = self.config();
-        let metadata = StackSubstateMetadata::new(gas_limit, &config);
-        let state = MemoryStackState::new(metadata, backend);
-        let precompiles = build_precompile_set();
-        let mut executor = StackExecutor::new_with_precompiles(state, &config, &precompiles);
-
-        let (exit, res) = if let Some(addr) = &to {
-            executor.transact_call(
-                from.unwrap_or_default(),
-                *addr,
-                value,
-                data,
-                gas_limit,
-                Vec::new(),
-            )
-        } else {
-            executor.transact_create(from.unwrap_or_default(), value, data, gas_limit, Vec::new())
-        };
-
-        let used_gas = executor.used_gas();
-        let used_gas = if is_estimate {
-            // The estimate gas coef is 1.25
-            (used_gas / 4) + used_gas
-        } else {
-            used_gas
-        };
-
-        TxResp {
-            exit_reason:  exit,
-            ret:          res,
-            remain_gas:   executor.gas(),
-            gas_used:     used_gas,
-            fee_cost:     backend
-                .gas_price()
-                .checked_mul(used_gas.into())
-                .unwrap_or(U256::max_value()),
-            logs:         vec![],
-            code_address: if to.is_none() {
-                Some(
-                    executor
-                        .create_address(CreateScheme::Legacy {
-                            caller: from.unwrap_or_default(),
-                        })
-                        .into(),
-                )
-            } else {
-                None
-            },
-            removed:      false,
-        }
+        // The gas limit of `eth_estimateGas` is binary searched by the API, so
+        // the gas used is reported as it is.
+        self.call_with_access_list(backend, gas_limit, from, to, value, data, Vec::new())
     }
 
     // Function execute returns exit_reason, ret_data and remain_gas.
@@ -138,15 +110,16 @@ impl Executor for AxonExecutor {
         let mut res = Vec::with_capacity(txs_len);
         let mut encode_receipts = Vec::with_capacity(txs_len);
         let (mut gas, mut fee) = (0u64, U256::zero());
+        self.init_block_context(adapter);
         let precompiles = build_precompile_set();
-        self.init_local_system_contract_roots(adapter);
         let config = self.config();
+        CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = Some(block_number.low_u64()));
 
         // Execute system contracts before block hook.
         before_block_hook(adapter);
 
         for tx in txs.iter() {
-            adapter.set_gas_price(tx.transaction.unsigned.gas_price());
+            adapter.set_gas_price(tx_gas_price(adapter, tx));
             adapter.set_origin(tx.sender);
 
             // Execute a transaction, if system contract dispatch return None, means the
@@ -186,24 +159,16 @@ impl Executor for AxonExecutor {
 
         // commit changes by all txs included in this block only once
         let new_state_root = adapter.commit();
+        flush_system_contract_dbs();
+        CURRENT_BLOCK_NUMBER.with(|n| *n.borrow_mut() = None);
 
         // self.update_system_contract_roots_for_external_module();
 
-        let receipt_root = if encode_receipts.is_empty() {
-            RLP_NULL
-        } else {
-            TrieMerkle::from_receipts(&encode_receipts)
-                .root_hash()
-                .unwrap_or_else(|err| {
-                    panic!("failed to calculate trie root hash for receipts since {err}")
-                })
-        };
-
         ExecResp {
-            state_root: new_state_root,
-            receipt_root,
-            gas_used: gas,
-            tx_resp: res,
+            state_root:   new_state_root,
+            receipt_root: receipts_root(&encode_receipts),
+            gas_used:     gas,
+            tx_resp:      res,
         }
     }
 }
@@ -392,6 +357,388 @@ impl AxonExecutor {
         }
     }
 
+    /// Same as `Executor::call` but with an EIP-2930 access list applied.
+    pub fn call_with_access_list<B: Backend>(
+        &self,
+        backend: &B,
+        gas_limit: u64,
+        from: Option<H160>,
+        to: Option<H160>,
+        value: U256,
+        data: Vec<u8>,
+        access_list: AccessList,
+    ) -> TxResp {
+        self.init_call_context(backend);
+        let config = self.config();
+        let metadata = StackSubstateMetadata::new(gas_limit, &config);
+        let state = MemoryStackState::new(metadata, backend);
+        let precompiles = build_precompile_set();
+        let mut executor = StackExecutor::new_with_precompiles(state, &config, &precompiles);
+        let access_list = access_list
+            .into_iter()
+            .map(|x| (x.address, x.storage_keys))
+            .collect::<Vec<_>>();
+
+        let (exit, res) = if let Some(addr) = &to {
+            executor.transact_call(
+                from.unwrap_or_default(),
+                *addr,
+                value,
+                data,
+                gas_limit,
+                access_list,
+            )
+        } else {
+            executor.transact_create(
+                from.unwrap_or_default(),
+                value,
+                data,
+                gas_limit,
+                access_list,
+            )
+        };
+
+        let used_gas = executor.used_gas();
+
+        TxResp {
+            exit_reason:  exit,
+            ret:          res,
+            remain_gas:   executor.gas(),
+            gas_used:     used_gas,
+            fee_cost:     backend
+                .gas_price()
+                .checked_mul(used_gas.into())
+                .unwrap_or(U256::max_value()),
+            logs:         vec![],
+            code_address: if to.is_none() {
+                Some(
+                    executor
+                        .create_address(CreateScheme::Legacy {
+                            caller: from.unwrap_or_default(),
+                        })
+                        .into(),
+                )
+            } else {
+                None
+            },
+            removed:      false,
+        }
+    }
+
+    /// Generate the access list of a call. The call is repeated with the
+    /// generated access list applied until the list does not change or
+    /// `MAX_ACCESS_LIST_ITERATIONS` is reached, and the list and the response
+    /// of the last execution are returned.
+    pub fn create_access_list<B: Backend>(
+        &self,
+        backend: &B,
+        gas_limit: u64,
+        from: Option<H160>,
+        to: Option<H160>,
+        value: U256,
+        data: Vec<u8>,
+        access_list: AccessList,
+    ) -> (AccessList, TxResp) {
+        let sender = from.unwrap_or_default();
+        let recipient =
+            to.unwrap_or_else(|| code_address(&sender, &backend.basic(sender).nonce).into());
+        self.init_call_context(backend);
+        let mut excluded = build_precompile_set().into_keys().collect::<BTreeSet<_>>();
+        excluded.insert(sender);
+        excluded.insert(recipient);
+
+        let mut access_list = access_list;
+        let mut iterations = 0;
+        loop {
+            let (tracer, resp) = trace_using(
+                AccessListTracer::new(&access_list, excluded.clone()),
+                || {
+                    self.call_with_access_list(
+                        backend,
+                        gas_limit,
+                        from,
+                        to,
+                        value,
+                        data.clone(),
+                        access_list.clone(),
+                    )
+                },
+            );
+
+            let new_list = tracer.access_list();
+            iterations += 1;
+            if new_list == access_list || iterations == MAX_ACCESS_LIST_ITERATIONS {
+                return (new_list, resp);
+            }
+
+            access_list = new_list;
+        }
+    }
+
+    /// Trace a call on the given backend, the state is never modified. The
+    /// execution is aborted once the `cancel` flag is set.
+    pub fn trace_call<B: Backend>(
+        &self,
+        backend: &B,
+        gas_limit: u64,
+        from: Option<H160>,
+        to: Option<H160>,
+        value: U256,
+        data: Vec<u8>,
+        tracer: &Tracer,
+        cancel: &Arc<AtomicBool>,
+    ) -> Result<TraceResult, TraceCancelled> {
+        self.init_call_context(backend);
+        let precompiles = build_precompile_set().into_keys().collect::<BTreeSet<_>>();
+
+        let res = match tracer {
+            Tracer::StructLogger(tracer_config) => {
+                let (logger, resp) = trace_until(
+                    StructLogger::new(*tracer_config, precompiles),
+                    cancel,
+                    || self.call(backend, gas_limit, from, to, value, data, false),
+                )?;
+                logger.finish(&resp)
+            }
+            Tracer::CallTracer(tracer_config) => {
+                let input = data.clone();
+                let (call_tracer, resp) =
+                    trace_until(CallTracer::new(*tracer_config, precompiles), cancel, || {
+                        self.call(backend, gas_limit, from, to, value, data, false)
+                    })?;
+                call_tracer.finish_call(
+                    from.unwrap_or_default(),
+                    to,
+                    value,
+                    &input,
+                    gas_limit,
+                    &resp,
+                )
+            }
+        };
+
+        Ok(res)
+    }
+
+    /// Re-execute the given transactions like `exec` and trace the ones whose
+    /// index is not less than `trace_from`. The adapter should be an overlay
+    /// of the parent state of the block. The after block hook is skipped since
+    /// it runs after the last transaction and updates the global hardforks.
+    /// The execution is aborted once the `cancel` flag is set.
+    pub fn trace_exec<Adapter: ExecutorAdapter>(
+        &self,
+        adapter: &mut Adapter,
+        txs: &[SignedTransaction],
+        trace_from: usize,
+        tracer: &Tracer,
+        cancel: &Arc<AtomicBool>,
+    ) -> Result<Vec<TraceResult>, TraceCancelled> {
+        self.init_block_context(adapter);
+        let config = self.config();
+        let precompiles = build_precompile_set();
+        let precompile_addresses = precompiles.keys().copied().collect::<BTreeSet<_>>();
+        let mut res = Vec::with_capacity(txs.len().saturating_sub(trace_from));
+
+        before_block_hook(adapter);
+
+        for (idx, tx) in txs.iter().enumerate() {
+            adapter.set_gas_price(tx_gas_price(adapter, tx));
+            adapter.set_origin(tx.sender);
+
+            let exec = || {
+                system_contract_dispatch(adapter, tx)
+                    .unwrap_or_else(|| Self::evm_exec(adapter, &config, &precompiles, tx))
+            };
+
+            let trace = if idx < trace_from {
+                trace_until(NoopTracer, cancel, exec)?;
+                None
+            } else {
+                Some(match tracer {
+                    Tracer::StructLogger(tracer_config) => {
+                        let (logger, resp) = trace_until(
+                            StructLogger::new(*tracer_config, precompile_addresses.clone()),
+                            cancel,
+                            exec,
+                        )?;
+                        logger.finish(&resp)
+                    }
+                    Tracer::CallTracer(tracer_config) => {
+                        let (call_tracer, resp) = trace_until(
+                            CallTracer::new(*tracer_config, precompile_addresses.clone()),
+                            cancel,
+                            exec,
+                        )?;
+                        call_tracer.finish(tx, &resp)
+                    }
+                })
+            };
+
+            adapter.take_logs();
+            res.extend(trace);
+        }
+
+        Ok(res)
+    }
+
+    /// Execute the calls in sequence, each of them is executed on the state
+    /// changed by the previous ones. The adapter should be an overlay since
+    /// the changes are applied to it. Like `call`, no fee is charged. The gas
+    /// of the calls is capped by the gas left in the block, and the execution
+    /// is aborted once the `cancel` flag is set.
+    pub fn simulate<Adapter: ExecutorAdapter>(
+        &self,
+        adapter: &mut Adapter,
+        calls: &[SimulateCall],
+        cancel: &Arc<AtomicBool>,
+    ) -> Result<Vec<TxResp>, TraceCancelled> {
+        self.init_block_context(adapter);
+        let config = self.config();
+        let precompiles = build_precompile_set();
+        let mut gas_left = adapter.block_gas_limit().low_u64();
+        let mut res = Vec::with_capacity(calls.len());
+
+        for call in calls.iter() {
+            adapter.set_origin(call.from);
+            adapter.set_gas_price(call.gas_price);
+            let nonce = adapter.basic(call.from).nonce;
+            let gas_limit = call.gas_limit.min(gas_left);
+
+            let (_, (exit, ret, remain_gas, gas_used, changes)) =
+                trace_until(NoopTracer, cancel, || {
+                    let metadata = StackSubstateMetadata::new(gas_limit, &config);
+                    let mut executor = StackExecutor::new_with_precompiles(
+                        MemoryStackState::new(metadata, &*adapter),
+                        &config,
+                        &precompiles,
+                    );
+
+                    let (exit, ret) = match call.to {
+                        Some(to) => executor.transact_call(
+                            call.from,
+                            to,
+                            call.value,
+                            call.data.clone(),
+                            gas_limit,
+                            Vec::new(),
+                        ),
+                        None => executor.transact_create(
+                            call.from,
+                            call.value,
+                            call.data.clone(),
+                            gas_limit,
+                            Vec::new(),
+                        ),
+                    };
+
+                    let remain_gas = executor.gas();
+                    let gas_used = executor.used_gas();
+                    let changes = exit
+                        .is_succeed()
+                        .then(|| executor.into_state().deconstruct());
+                    (exit, ret, remain_gas, gas_used, changes)
+                })?;
+
+            // The changes of a failed call are discarded as a failed transaction.
+            if let Some((values, logs)) = changes {
+                adapter.apply(values, logs, true);
+            }
+            gas_left = gas_left.saturating_sub(gas_used);
+
+            res.push(TxResp {
+                code_address: if call.to.is_none() && exit.is_succeed() {
+                    Some(code_address(&call.from, &nonce))
+                } else {
+                    None
+                },
+                exit_reason: exit,
+                ret,
+                remain_gas,
+                gas_used,
+                fee_cost: U256::from(call.gas_price.low_u64()) * U256::from(gas_used),
+                logs: adapter.take_logs(),
+                removed: false,
+            });
+        }
+
+        Ok(res)
+    }
+
+    /// Speculatively execute the transactions like `exec` to build the pending
+    /// block. The adapter should be an overlay of the latest state, the system
+    /// contract calls and the block hooks are skipped since they modify the
+    /// global state, and no fee is allocated to the validators.
+    pub fn speculate<Adapter: ExecutorAdapter>(
+        &self,
+        adapter: &mut Adapter,
+        txs: &[SignedTransaction],
+    ) -> ExecResp {
+        self.init_block_context(adapter);
+        let config = self.config();
+        let precompiles = build_precompile_set();
+        let mut res = Vec::with_capacity(txs.len());
+        let mut encode_receipts = Vec::with_capacity(txs.len());
+        let mut gas = 0u64;
+
+        for tx in txs.iter() {
+            adapter.set_gas_price(tx_gas_price(adapter, tx));
+            adapter.set_origin(tx.sender);
+
+            let mut r =
+                if is_call_system_script(tx.transaction.unsigned.action()).unwrap_or_default() {
+                    let mut account = adapter.get_account(&tx.sender);
+                    account.nonce += U256::one();
+                    adapter.save_account(&tx.sender, &account);
+                    TxResp::default()
+                } else {
+                    Self::evm_exec(adapter, &config, &precompiles, tx)
+                };
+
+            r.logs = adapter.take_logs();
+            gas += r.gas_used;
+            encode_receipts.push(tx.encode_receipt(&r, logs_bloom(r.logs.iter())));
+            res.push(r);
+        }
+
+        ExecResp {
+            state_root:   adapter.commit(),
+            receipt_root: receipts_root(&encode_receipts),
+            gas_used:     gas,
+            tx_resp:      res,
+        }
+    }
+
+    /// Initialize the thread local context to call on the state of the block
+    /// of the backend, with the hardforks enabled at the block.
+    fn init_call_context<B: Backend>(&self, backend: &B) {
+        self.init_local_system_contract_roots(backend);
+        self.init_local_hardforks(backend.block_number().low_u64());
+    }
+
+    /// Initialize the thread local context to execute the block of the backend
+    /// on the state of its parent. As `exec`, the block is executed with the
+    /// hardforks enabled at its parent.
+    fn init_block_context<B: Backend>(&self, backend: &B) {
+        self.init_local_system_contract_roots(backend);
+        self.init_local_hardforks(backend.block_number().low_u64().saturating_sub(1));
+    }
+
+    /// Read the hardforks enabled at the given block from the metadata of the
+    /// current state, which falls back to `HARDFORK_INFO` if the metadata is
+    /// not initialized.
+    fn init_local_hardforks(&self, number: u64) {
+        let root = CURRENT_METADATA_ROOT.with(|r| *r.borrow());
+        let hardforks = if root.is_zero() {
+            None
+        } else {
+            MetadataHandle::new(root).hardfork_info(number).ok()
+        };
+
+        CURRENT_HARDFORKS.with(|h| {
+            *h.borrow_mut() = hardforks.unwrap_or_else(|| **HARDFORK_INFO.load());
+        });
+    }
+
     /// The `exec()` function is run in `tokio::task::block_in_place()` and all
     /// the read or write operations are in the scope of exec function. The
     /// thread context is not switched during exec function.
@@ -407,9 +754,9 @@ impl AxonExecutor {
     }
 
     fn config(&self) -> Config {
-        let mut evm_config = Config::london();
+        let mut evm_config = evm_config();
         let create_contract_limit = {
-            if enable_hardfork(HardforkName::Andromeda) {
+            if local_hardfork_enabled(HardforkName::Andromeda) {
                 let handle = MetadataHandle::new(CURRENT_METADATA_ROOT.with(|r| *r.borrow()));
                 let consensus_config = handle.get_consensus_config().unwrap();
                 Some(consensus_config.max_contract_limit as usize)
@@ -435,10 +782,10 @@ impl AxonExecutor {
         let mut encode_receipts = Vec::with_capacity(txs_len);
         let (mut gas, mut fee) = (0u64, U256::zero());
         let precompiles = build_precompile_set();
-        let config = Config::london();
+        let config = evm_config();
 
         for tx in txs.iter() {
-            adapter.set_gas_price(tx.transaction.unsigned.gas_price());
+            adapter.set_gas_price(tx_gas_price(adapter, tx));
             adapter.set_origin(tx.sender);
 
             // Execute a transaction, if system contract dispatch return None, means the
@@ -495,15 +842,81 @@ impl AxonExecutor {
     }
 }
 
+fn receipts_root(encode_receipts: &[Bytes]) -> MerkleRoot {
+    if encode_receipts.is_empty() {
+        RLP_NULL
+    } else {
+        TrieMerkle::from_receipts(encode_receipts)
+            .root_hash()
+            .unwrap_or_else(|err| {
+                panic!("failed to calculate trie root hash for receipts since {err}")
+            })
+    }
+}
+
 pub fn is_transaction_call(action: &TransactionAction, addr: &H160) -> bool {
     action == &TransactionAction::Call(*addr)
 }
 
+/// The EVM config of the hardforks enabled for the executing block, the
+/// contract size limit is not applied. The blocks before `Antlia` keep the
+/// London config, whose Shanghai and Cancun switches are all off, so they are
+/// executed exactly as before the upgrade of the evm crate.
+pub fn evm_config() -> Config {
+    if local_hardfork_enabled(HardforkName::Antlia) {
+        Config::cancun()
+    } else {
+        Config::london()
+    }
+}
+
+/// The base fee per gas of the next block of `parent`, which is a constant
+/// before the `Apus` hardfork of the next block.
+pub fn next_block_base_fee_per_gas(parent: &Header, hardforks: &HardforkInfo) -> U64 {
+    next_block_required_base_fee(parent, hardforks).unwrap_or_else(|| BASE_FEE_PER_GAS.into())
+}
+
+/// The base fee per gas that the max fee per gas of every transaction in the
+/// next block of `parent` must cover, which is only required since the `Apus`
+/// hardfork.
+pub fn next_block_required_base_fee(parent: &Header, hardforks: &HardforkInfo) -> Option<U64> {
+    block_hardfork_enabled(hardforks, parent.number + 1, HardforkName::Apus)
+        .then(|| parent.next_base_fee_per_gas())
+}
+
+/// Whether the hardfork is enabled for the block `number`. The hardforks of a
+/// block are determined by the metadata after its parent block is executed,
+/// which are the same as the hardforks it is executed with.
+pub fn block_hardfork_enabled(hardforks: &HardforkInfo, number: u64, name: HardforkName) -> bool {
+    contains_hardfork(&hardforks.flags_at(number.saturating_sub(1)), name)
+}
+
 pub fn enable_hardfork(name: HardforkName) -> bool {
-    let latest_hardfork_info = &**HARDFORK_INFO.load();
+    contains_hardfork(&HARDFORK_INFO.load(), name)
+}
+
+/// Whether the hardfork is enabled for the executing block, which is set by
+/// the entry functions of `AxonExecutor`.
+pub(crate) fn local_hardfork_enabled(name: HardforkName) -> bool {
+    CURRENT_HARDFORKS.with(|h| contains_hardfork(&h.borrow(), name))
+}
+
+/// The gas price charged for a transaction, which is the base fee plus the
+/// effective priority fee since the `Apus` hardfork.
+fn tx_gas_price<B: Backend>(backend: &B, tx: &SignedTransaction) -> U64 {
+    if local_hardfork_enabled(HardforkName::Apus) {
+        tx.transaction
+            .unsigned
+            .effective_gas_price(backend.block_base_fee_per_gas().low_u64().into())
+    } else {
+        tx.transaction.unsigned.gas_price()
+    }
+}
+
+fn contains_hardfork(hardforks: &H256, name: HardforkName) -> bool {
     let enable_flag = H256::from_low_u64_be((name as u64).to_be());
 
-    latest_hardfork_info & &enable_flag == enable_flag
+    hardforks & &enable_flag == enable_flag
 }
 
 #[cfg(test)]
@@ -11,6 +11,8 @@ use crate::precompiles::{axon_precompile_address, PrecompileContract};
 use crate::system_contract::image_cell::{image_cell_abi, CellKey, ImageCellReader};
 use crate::{err, CURRENT_HEADER_CELL_ROOT};
 
+const GAS_PER_WORD: u64 = 100;
+
 #[derive(Default, Clone)]
 pub struct GetCell;
 
@@ -24,6 +26,7 @@ impl PrecompileContract for GetCell {
         _context: &Context,
         _is_static: bool,
     ) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+        let (tx_hash, index) = parse_input(input)?;
         let gas = Self::gas_cost(input);
         if let Some(limit) = gas_limit {
             if gas > limit {
@@ -31,8 +34,6 @@ impl PrecompileContract for GetCell {
             }
         }
 
-        let (tx_hash, index) = parse_input(input)?;
-
         let root = CURRENT_HEADER_CELL_ROOT.with(|r| *r.borrow());
         let cell_opt = ImageCellReader
             .get_cell(root, &CellKey { tx_hash, index })
@@ -49,20 +50,41 @@ impl PrecompileContract for GetCell {
             return err!("get cell return None");
         }
 
+        let output = AbiEncode::encode((cell_opt.unwrap(),));
+        let gas = gas + data_gas_cost(&output);
+        if let Some(limit) = gas_limit {
+            if gas > limit {
+                return err!();
+            }
+        }
+
         Ok((
             PrecompileOutput {
                 exit_status: ExitSucceed::Returned,
-                output:      AbiEncode::encode((cell_opt.unwrap(),)),
+                output,
             },
             gas,
         ))
     }
 
-    fn gas_cost(_input: &[u8]) -> u64 {
-        Self::MIN_GAS
+    /// The gas cost of the input which is charged before reading the cell, the
+    /// cost of the cell data is charged by `data_gas_cost` after the cell is
+    /// read. An input which is not an out point is rejected, so that its gas
+    /// cost is `u64::MAX`.
+    fn gas_cost(input: &[u8]) -> u64 {
+        match parse_input(input) {
+            Ok(_) => Self::MIN_GAS + data_gas_cost(input),
+            Err(_) => u64::MAX,
+        }
     }
 }
 
+/// Estimate the dynamic gas = GAS_PER_WORD * data_word_size
+fn data_gas_cost(data: &[u8]) -> u64 {
+    let data_word_size = (data.len() + 31) / 32;
+    data_word_size as u64 * GAS_PER_WORD
+}
+
 fn parse_input(input: &[u8]) -> Result<(H256, u32), PrecompileFailure> {
     let out_point = <image_cell_abi::OutPoint as AbiDecode>::decode(input)
         .map_err(|_| err!(_, "decode input"))?;
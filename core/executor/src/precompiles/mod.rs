@@ -6,7 +6,7 @@ mod ec_add;
 mod ec_mul;
 mod ec_pairing;
 mod ecrecover;
-mod get_cell;
+pub(crate) mod get_cell;
 mod get_header;
 mod identity;
 mod modexp;
@@ -24,13 +24,15 @@ use bn::{AffineG1, Fq, Fr, Group, G1};
 use evm::executor::stack::{PrecompileFailure, PrecompileFn, PrecompileOutput};
 use evm::{Context, ExitError};
 
+use common_config_parser::types::spec::HardforkName;
 use protocol::types::H160;
 
+use crate::local_hardfork_enabled;
 use crate::precompiles::{
     blake2_f::Blake2F, call_ckb_vm::CallCkbVM, ckb_blake2b::CkbBlake2b, ckb_mbt_verify::CMBTVerify,
-    ec_add::EcAdd, ec_mul::EcMul, ec_pairing::EcPairing, ecrecover::EcRecover,
+    ec_add::EcAdd, ec_mul::EcMul, ec_pairing::EcPairing, ecrecover::EcRecover, get_cell::GetCell,
     get_header::GetHeader, identity::Identity, modexp::ModExp, ripemd160::Ripemd160,
-    sha256::Sha256,
+    rsa::RsaVerify, secp256r1::P256Verify, sha256::Sha256,
 };
 
 #[macro_export]
@@ -66,7 +68,7 @@ macro_rules! precompiles {
     }};
 }
 
-trait PrecompileContract {
+pub(crate) trait PrecompileContract {
     const ADDRESS: H160;
     const MIN_GAS: u64;
 
@@ -95,10 +97,24 @@ const fn axon_precompile_address(addr: u8) -> H160 {
 }
 
 pub fn build_precompile_set() -> BTreeMap<H160, PrecompileFn> {
-    precompiles!(
+    let mut set = precompiles!(
         EcRecover, Sha256, Ripemd160, Identity, ModExp, EcAdd, EcMul, EcPairing, Blake2F,
         CallCkbVM, CkbBlake2b, CMBTVerify, GetHeader
-    )
+    );
+
+    if local_hardfork_enabled(HardforkName::Ara) {
+        set.extend(precompiles!(GetCell));
+    }
+
+    if local_hardfork_enabled(HardforkName::Aquarius) {
+        set.extend(precompiles!(P256Verify));
+    }
+
+    if local_hardfork_enabled(HardforkName::Aquila) {
+        set.extend(precompiles!(RsaVerify));
+    }
+
+    set
 }
 
 pub(crate) fn read_point(input: &[u8], start: usize) -> Result<G1, PrecompileFailure> {
@@ -1 +1,175 @@
+use ethers::abi::{AbiDecode, AbiEncode};
+use ethers::contract::{EthAbiCodec, EthAbiType};
+use ethers::core::types::Bytes as EthBytes;
+use evm::executor::stack::{PrecompileFailure, PrecompileOutput};
+use evm::{Context, ExitError, ExitSucceed};
+use rug::{integer::Order, Integer};
 
+use protocol::types::H160;
+
+use crate::err;
+use crate::precompiles::{axon_precompile_address, PrecompileContract};
+
+/// The modulus is limited to 1024 to 8192 bits.
+const MIN_MODULUS_LEN: usize = 128;
+const MAX_MODULUS_LEN: usize = 1024;
+const MAX_EXPONENT_LEN: usize = 32;
+
+const SHA1_DIGEST_INFO: [u8; 15] = [
+    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+];
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+const SHA384_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02, 0x05,
+    0x00, 0x04, 0x30,
+];
+const SHA512_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03, 0x05,
+    0x00, 0x04, 0x40,
+];
+
+/// The hash algorithm of the digest is determined by its length, SHA-1,
+/// SHA-256, SHA-384 and SHA-512 are supported.
+#[derive(EthAbiCodec, EthAbiType, Default, Clone, Debug, PartialEq, Eq)]
+pub struct RsaVerifyPayload {
+    pub modulus:   EthBytes,
+    pub exponent:  EthBytes,
+    pub digest:    EthBytes,
+    pub signature: EthBytes,
+}
+
+/// Verify a RSASSA-PKCS1-v1_5 signature, returns an ABI encoded bool.
+#[derive(Default, Clone)]
+pub struct RsaVerify;
+
+impl PrecompileContract for RsaVerify {
+    const ADDRESS: H160 = axon_precompile_address(0x09);
+    const MIN_GAS: u64 = 3000;
+
+    fn exec_fn(
+        input: &[u8],
+        gas_limit: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+        let payload = parse_input(input)?;
+        let gas = payload_gas_cost(&payload);
+        if let Some(limit) = gas_limit {
+            if gas > limit {
+                return err!();
+            }
+        }
+
+        let res = verify(&payload)?;
+
+        Ok((
+            PrecompileOutput {
+                exit_status: ExitSucceed::Returned,
+                output:      AbiEncode::encode(res),
+            },
+            gas,
+        ))
+    }
+
+    /// Estimate the gas cost = MIN_GAS + dynamic_gas
+    ///                       = MIN_GAS + words ^ 2 * exponent_bits / 3
+    /// The dynamic gas follows the modular exponentiation of EIP-2565, which
+    /// uses 8 bytes words of the modulus.
+    ///
+    /// An input which can not be decoded or whose lengths are out of range is
+    /// rejected by the precompile, so that it consumes all the gas given and
+    /// its gas cost is `u64::MAX`.
+    fn gas_cost(input: &[u8]) -> u64 {
+        parse_input(input)
+            .map(|payload| payload_gas_cost(&payload))
+            .unwrap_or(u64::MAX)
+    }
+}
+
+fn payload_gas_cost(payload: &RsaVerifyPayload) -> u64 {
+    let modulus_words = (payload.modulus.len() as u64 + 7) / 8;
+    let exponent_bits = Integer::from_digits(&payload.exponent, Order::MsfBe)
+        .significant_bits()
+        .max(1) as u64;
+
+    modulus_words * modulus_words * exponent_bits / 3 + RsaVerify::MIN_GAS
+}
+
+/// Decode the input and check the lengths of the modulus, the exponent and the
+/// digest.
+fn parse_input(input: &[u8]) -> Result<RsaVerifyPayload, PrecompileFailure> {
+    let payload = <(RsaVerifyPayload,) as AbiDecode>::decode(input)
+        .map(|payload| payload.0)
+        .map_err(|_| err!(_, "decode input"))?;
+
+    if !(MIN_MODULUS_LEN..=MAX_MODULUS_LEN).contains(&payload.modulus.len()) {
+        return err!("Invalid modulus length");
+    }
+
+    if payload.exponent.is_empty() || payload.exponent.len() > MAX_EXPONENT_LEN {
+        return err!("Invalid exponent length");
+    }
+
+    if digest_info(payload.digest.len()).is_none() {
+        return err!("Invalid digest length");
+    }
+
+    Ok(payload)
+}
+
+fn digest_info(digest_len: usize) -> Option<&'static [u8]> {
+    match digest_len {
+        20 => Some(&SHA1_DIGEST_INFO),
+        32 => Some(&SHA256_DIGEST_INFO),
+        48 => Some(&SHA384_DIGEST_INFO),
+        64 => Some(&SHA512_DIGEST_INFO),
+        _ => None,
+    }
+}
+
+/// Verify a payload which has been checked by `parse_input`.
+fn verify(payload: &RsaVerifyPayload) -> Result<bool, PrecompileFailure> {
+    let modulus_len = payload.modulus.len();
+    let digest_info = digest_info(payload.digest.len()).unwrap_or_default();
+    if payload.signature.len() != modulus_len {
+        return Ok(false);
+    }
+
+    let n = Integer::from_digits(&payload.modulus, Order::MsfBe);
+    let e = Integer::from_digits(&payload.exponent, Order::MsfBe);
+    let s = Integer::from_digits(&payload.signature, Order::MsfBe);
+    if s >= n {
+        return Ok(false);
+    }
+
+    // The result is less than the modulus, so it can be padded to the length of
+    // the modulus.
+    let m = s
+        .pow_mod(&e, &n)
+        .map_err(|_| err!(_, "Invalid modulus"))?
+        .to_digits::<u8>(Order::MsfBe);
+    let mut decrypted = vec![0u8; modulus_len - m.len()];
+    decrypted.extend_from_slice(&m);
+
+    Ok(encode_message(modulus_len, digest_info, &payload.digest) == Some(decrypted))
+}
+
+/// The EMSA-PKCS1-v1_5 encoding of the digest, which is
+/// `0x00 || 0x01 || PS || 0x00 || DigestInfo || digest`.
+fn encode_message(len: usize, digest_info: &[u8], digest: &[u8]) -> Option<Vec<u8>> {
+    let t_len = digest_info.len() + digest.len();
+    if len < t_len + 11 {
+        return None;
+    }
+
+    let mut em = vec![0xff; len];
+    em[0] = 0x00;
+    em[1] = 0x01;
+    em[len - t_len - 1] = 0x00;
+    em[len - t_len..len - digest.len()].copy_from_slice(digest_info);
+    em[len - digest.len()..].copy_from_slice(digest);
+    Some(em)
+}
@@ -1 +1,83 @@
+use evm::executor::stack::{PrecompileFailure, PrecompileOutput};
+use evm::{Context, ExitError, ExitSucceed};
+use p256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use p256::{EncodedPoint, FieldBytes};
 
+use protocol::types::H160;
+
+use crate::err;
+use crate::precompiles::{axon_precompile_address, PrecompileContract};
+
+const INPUT_LEN: usize = 160;
+
+/// The secp256r1 signature verification precompile specified by
+/// [RIP-7212](https://github.com/ethereum/RIPs/blob/master/RIPS/rip-7212.md).
+///
+/// The input is the 32 bytes message hash, the 32 bytes `r`, the 32 bytes
+/// `s`, the 32 bytes `x` and the 32 bytes `y` of the public key. The output
+/// is `1` in 32 bytes if the signature is valid, otherwise it is empty.
+#[derive(Default, Clone)]
+pub struct P256Verify;
+
+impl PrecompileContract for P256Verify {
+    const ADDRESS: H160 = axon_precompile_address(0x08);
+    const MIN_GAS: u64 = 3450;
+
+    fn exec_fn(
+        input: &[u8],
+        gas_limit: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> Result<(PrecompileOutput, u64), PrecompileFailure> {
+        let gas = Self::gas_cost(input);
+        if let Some(limit) = gas_limit {
+            if gas > limit {
+                return err!();
+            }
+        }
+
+        let output = if verify(input) {
+            let mut ret = vec![0u8; 32];
+            ret[31] = 1;
+            ret
+        } else {
+            Vec::new()
+        };
+
+        Ok((
+            PrecompileOutput {
+                exit_status: ExitSucceed::Returned,
+                output,
+            },
+            gas,
+        ))
+    }
+
+    fn gas_cost(_input: &[u8]) -> u64 {
+        Self::MIN_GAS
+    }
+}
+
+fn verify(input: &[u8]) -> bool {
+    if input.len() != INPUT_LEN {
+        return false;
+    }
+
+    let signature = match Signature::from_scalars(
+        FieldBytes::clone_from_slice(&input[32..64]),
+        FieldBytes::clone_from_slice(&input[64..96]),
+    ) {
+        Ok(sig) => sig,
+        Err(_) => return false,
+    };
+
+    let point = EncodedPoint::from_affine_coordinates(
+        FieldBytes::from_slice(&input[96..128]),
+        FieldBytes::from_slice(&input[128..160]),
+        false,
+    );
+
+    VerifyingKey::from_encoded_point(&point)
+        .map(|key| key.verify_prehash(&input[..32], &signature).is_ok())
+        .unwrap_or(false)
+}
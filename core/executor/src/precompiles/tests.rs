@@ -3,21 +3,25 @@ use std::collections::HashSet;
 use ckb_types::prelude::*;
 use ckb_types::utilities::{merkle_root, CBMT};
 use ethers::abi::AbiEncode;
-use evm::Context;
+use evm::executor::stack::PrecompileFailure;
+use evm::{Context, ExitError};
 use sha2::Digest;
 
+use common_config_parser::types::spec::HardforkName;
 use protocol::{
     ckb_blake2b_256,
     codec::hex_decode,
     rand::random,
-    types::{H256, U256},
+    types::{H160, H256, U256},
 };
 
 use crate::precompiles::ckb_mbt_verify::{Proof, VerifyProofPayload};
+use crate::precompiles::rsa::RsaVerifyPayload;
 use crate::precompiles::{
-    Blake2F, CMBTVerify, CkbBlake2b, EcAdd, EcMul, EcPairing, EcRecover, Identity, ModExp,
-    PrecompileContract, Ripemd160, Sha256,
+    build_precompile_set, Blake2F, CMBTVerify, CkbBlake2b, EcAdd, EcMul, EcPairing, EcRecover,
+    GetCell, Identity, ModExp, P256Verify, PrecompileContract, Ripemd160, RsaVerify, Sha256,
 };
+use crate::{err, CURRENT_HARDFORKS};
 
 macro_rules! test_precompile {
     ($ty: ident, $input: expr, $output: expr, $expect_gas_cost: expr) => {
@@ -34,7 +38,7 @@ macro_rules! test_precompile {
             <$ty as PrecompileContract>::exec_fn($input, Some($gas_limit), &mock_context(), false);
         assert!(resp.is_err());
         assert_eq!(resp, $err);
-        assert_eq!(gas_cost, expect_gas_cost);
+        assert_eq!(gas_cost, $expect_gas_cost);
     };
 }
 
@@ -50,6 +54,39 @@ fn rand_bytes(len: usize) -> Vec<u8> {
     (0..len).map(|_| random::<u8>()).collect()
 }
 
+fn precompile_set_with(hardforks: u64) -> HashSet<H160> {
+    CURRENT_HARDFORKS.with(|h| *h.borrow_mut() = H256::from_low_u64_be(hardforks.to_be()));
+    build_precompile_set().into_keys().collect()
+}
+
+#[test]
+fn test_precompile_set_hardforks() {
+    let set = precompile_set_with(0);
+    assert!(!set.contains(&GetCell::ADDRESS));
+    assert!(!set.contains(&RsaVerify::ADDRESS));
+    assert!(!set.contains(&P256Verify::ADDRESS));
+
+    let set = precompile_set_with(HardforkName::Antlia as u64);
+    assert!(!set.contains(&GetCell::ADDRESS));
+    assert!(!set.contains(&RsaVerify::ADDRESS));
+    assert!(!set.contains(&P256Verify::ADDRESS));
+
+    let set = precompile_set_with(HardforkName::Ara as u64);
+    assert!(set.contains(&GetCell::ADDRESS));
+    assert!(!set.contains(&RsaVerify::ADDRESS));
+    assert!(!set.contains(&P256Verify::ADDRESS));
+
+    let set = precompile_set_with(HardforkName::Aquarius as u64);
+    assert!(!set.contains(&GetCell::ADDRESS));
+    assert!(!set.contains(&RsaVerify::ADDRESS));
+    assert!(set.contains(&P256Verify::ADDRESS));
+
+    let set = precompile_set_with(HardforkName::Aquila as u64);
+    assert!(!set.contains(&GetCell::ADDRESS));
+    assert!(set.contains(&RsaVerify::ADDRESS));
+    assert!(!set.contains(&P256Verify::ADDRESS));
+}
+
 #[test]
 fn test_sha256() {
     let input = rand_bytes(100);
@@ -77,6 +114,65 @@ fn test_ecrecover() {
     test_precompile!(EcRecover, &input, output, 3000);
 }
 
+#[test]
+fn test_p256_verify() {
+    let mut input = hex_decode("62e41862ee96651121415287ee2e77f1187e4fe7fa5c2b0ea2ea6fcea9b2419d9ab7651cb7f9c22936dbb317368b12099b9fa30a300bc320d97f737653b829667e5e1239ff79aaffbde1abba6632a4b55382243d32f874cd7aa5c2297cf194da471c3e758c4904285bba7e53118ed0f524adeb0757d25bd2f8e7b0d76dfa714cdd520f7aca8a8b917acc37f51de8f0c9bbe3ad858382e702dc25a12d09f7a858").unwrap();
+    let mut output = vec![0u8; 32];
+    output[31] = 1;
+    test_precompile!(P256Verify, &input, output, 3450);
+
+    // The signature does not match the message hash.
+    input[0] ^= 1;
+    test_precompile!(P256Verify, &input, Vec::<u8>::new(), 3450);
+
+    // The input length is invalid.
+    test_precompile!(P256Verify, &input[..159], Vec::<u8>::new(), 3450);
+}
+
+#[test]
+fn test_rsa_verify() {
+    let mut payload = RsaVerifyPayload {
+        modulus:   hex_decode("b25cc3e89671328c10629d782612572cf971b1ecf74676fcb9a71cfd063bffb2b7b306fbff2726b45db354429f542850c822faaf8c4c28ffa8108b5d0a62c58d9c3cb4ed6f8e37dc898cfd97cc3531cf5ba2a2f05b68d2f56896c22f922a14c094fb5d9321e9a08aa66738efb934297eac2f70cbfb0cd0b697108d98014211cf").unwrap().into(),
+        exponent:  vec![0x01, 0x00, 0x01].into(),
+        digest:    hex_decode("6407650f96136eac80dc0b1ba4a67bbcbd0d03f8d9b1bc5fd1689a7d5537481f").unwrap().into(),
+        signature: hex_decode("10dd2b3289c2eaf7f5048f64f1adea1987c686af241e1192de59608c23575ce9c0888b3f52b7bde684073ef3e1f4ac2214e8faea49d0d0bc9f20984a6a462bf2fcb38b5d1874eca219cf902dd5b6a7c088e2b78ffae966f10d74d0dacfd6564bbb6152ff7324b7ca0d78e9368c0f0d722f27c476b754d3afbd58f399b3578951").unwrap().into(),
+    };
+    let input = AbiEncode::encode((payload.clone(),));
+    test_precompile!(RsaVerify, &input, AbiEncode::encode(true), 4450);
+
+    // The digest does not match the signature.
+    let mut digest = payload.digest.to_vec();
+    digest[0] ^= 1;
+    payload.digest = digest.into();
+    let input = AbiEncode::encode((payload.clone(),));
+    test_precompile!(RsaVerify, &input, AbiEncode::encode(false), 4450);
+
+    // The invalid lengths are rejected by both the gas cost and the execution.
+    let mut modulus = payload.modulus.to_vec();
+    modulus.resize(1025, 0xff);
+    let mut long_modulus = payload.clone();
+    long_modulus.modulus = modulus.into();
+    let input = AbiEncode::encode((long_modulus,));
+    test_precompile!(
+        RsaVerify,
+        &input,
+        u64::MAX,
+        u64::MAX,
+        err!("Invalid modulus length")
+    );
+
+    let mut long_exponent = payload;
+    long_exponent.exponent = vec![0x01; 33].into();
+    let input = AbiEncode::encode((long_exponent,));
+    test_precompile!(
+        RsaVerify,
+        &input,
+        u64::MAX,
+        u64::MAX,
+        err!("Invalid exponent length")
+    );
+}
+
 #[test]
 fn test_ripemd160() {
     let input = &[0xff];
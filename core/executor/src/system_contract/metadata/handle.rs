@@ -54,6 +54,11 @@ impl MetadataHandle {
         MetadataStore::new(self.root)?.hardfork_infos()
     }
 
+    /// The hardforks enabled at the given block.
+    pub fn hardfork_info(&self, block_number: u64) -> ProtocolResult<H256> {
+        MetadataStore::new(self.root)?.hardfork_info(block_number)
+    }
+
     pub fn init_hardfork(&self, block_number: u64) -> ProtocolResult<()> {
         let hardfork = MetadataStore::new(self.root)?
             .hardfork_info(block_number)
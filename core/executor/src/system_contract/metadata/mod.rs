@@ -15,8 +15,11 @@ use lru::LruCache;
 use parking_lot::RwLock;
 
 use protocol::codec::ProtocolCodec;
+use protocol::tokio::sync::broadcast;
 use protocol::traits::{ApplyBackend, ExecutorAdapter};
-use protocol::types::{HardforkInfoInner, Hasher, Metadata, SignedTransaction, TxResp, H160, H256};
+use protocol::types::{
+    ExtraData, HardforkInfoInner, Hasher, Header, Metadata, SignedTransaction, TxResp, H160, H256,
+};
 
 use crate::system_contract::utils::{
     generate_mpt_root_changes, revert_resp, succeed_resp, update_states,
@@ -28,6 +31,7 @@ type Epoch = u64;
 
 pub const METADATA_CONTRACT_ADDRESS: H160 = system_contract_address(0x1);
 const METADATA_CACHE_SIZE: NonZeroUsize = unsafe { NonZeroUsize::new_unchecked(10) };
+const METADATA_EVENT_CAPACITY: usize = 16;
 
 lazy_static::lazy_static! {
     pub static ref EPOCH_SEGMENT_KEY: H256 = Hasher::digest("epoch_segment");
@@ -36,6 +40,20 @@ lazy_static::lazy_static! {
     pub static ref HARDFORK_KEY: H256 = Hasher::digest("hardfork");
     pub static ref HARDFORK_INFO: ArcSwap<H256> = ArcSwap::new(Arc::new(H256::zero()));
     static ref METADATA_CACHE: RwLock<LruCache<Epoch, Metadata>> =  RwLock::new(LruCache::new(METADATA_CACHE_SIZE));
+    /// Broadcasts the metadata changes of every committed block, which are
+    /// subscribed by `axon_subscribe`.
+    pub static ref METADATA_EVENTS: broadcast::Sender<MetadataEvent> = broadcast::channel(METADATA_EVENT_CAPACITY).0;
+}
+
+#[derive(Clone, Debug)]
+pub enum MetadataEvent {
+    /// The block is the first one of the epoch.
+    NewEpoch(Metadata),
+    /// The hardforks recorded by the block, which are enabled since the block
+    /// number of the info.
+    HardforkDetermined(HardforkInfoInner),
+    /// The hardforks enabled since the block.
+    HardforkEnabled(HardforkInfoInner),
 }
 
 system_contract_struct!(MetadataContract);
@@ -116,12 +134,10 @@ impl<Adapter: ExecutorAdapter + ApplyBackend> SystemContract<Adapter>
 
         let mut store = MetadataStore::new(root).unwrap();
 
-        if let Some(t) = adapter.get_ctx().extra_data.get(0) {
-            if let Ok(data) = HardforkInfoInner::decode(&t.inner) {
-                store
-                    .set_hardfork_info(data.block_number, data.flags)
-                    .expect("set new hardfork info fail")
-            }
+        if let Some(data) = determined_hardfork(&adapter.get_ctx().extra_data) {
+            store
+                .set_hardfork_info(data.block_number, data.flags)
+                .expect("set new hardfork info fail");
         }
 
         let hardfork = store.hardfork_info(block_number.as_u64()).unwrap();
@@ -137,6 +153,71 @@ impl<Adapter: ExecutorAdapter + ApplyBackend> SystemContract<Adapter>
     }
 }
 
+fn determined_hardfork(extra_data: &[ExtraData]) -> Option<HardforkInfoInner> {
+    extra_data
+        .first()
+        .and_then(|t| HardforkInfoInner::decode(&t.inner).ok())
+}
+
+/// Sends the new epoch, and the hardforks newly determined or enabled by the
+/// block once it is committed, with the metadata root of its state. The
+/// events are compared with the state of the previous block rather than
+/// `HARDFORK_INFO`, which is reset on restart.
+pub fn notify_metadata_events(root: H256, header: &Header) {
+    let block_number = header.number;
+    if block_number == 0 || METADATA_EVENTS.receiver_count() == 0 {
+        return;
+    }
+
+    let handle = MetadataHandle::new(root);
+
+    match handle.get_metadata_by_block_number(block_number) {
+        Ok(metadata) if metadata.version.start == block_number => {
+            let _ = METADATA_EVENTS.send(MetadataEvent::NewEpoch(metadata));
+        }
+        Ok(_) => (),
+        Err(e) => log::warn!("[metadata] get metadata of block {}: {}", block_number, e),
+    }
+
+    let enabled = match handle.hardfork_info(block_number) {
+        Ok(enabled) => enabled,
+        Err(e) => {
+            log::warn!("[metadata] get hardfork of block {}: {}", block_number, e);
+            return;
+        }
+    };
+
+    if let Some(info) = determined_hardfork(&header.extra_data) {
+        let flags = info.flags ^ (info.flags & enabled);
+        if !flags.is_zero() {
+            let _ = METADATA_EVENTS.send(MetadataEvent::HardforkDetermined(HardforkInfoInner {
+                block_number: info.block_number,
+                flags,
+            }));
+        }
+    }
+
+    let Some(previous_number) = block_number.checked_sub(1) else {
+        return;
+    };
+    match handle.hardfork_info(previous_number) {
+        Ok(previous) => {
+            let flags = enabled ^ (enabled & previous);
+            if !flags.is_zero() {
+                let _ = METADATA_EVENTS.send(MetadataEvent::HardforkEnabled(HardforkInfoInner {
+                    block_number,
+                    flags,
+                }));
+            }
+        }
+        Err(e) => log::warn!(
+            "[metadata] get hardfork of block {}: {}",
+            previous_number,
+            e
+        ),
+    }
+}
+
 pub fn check_ckb_related_info_exist(root: H256) -> bool {
     MetadataHandle::new(root).get_ckb_related_info().is_ok()
 }
@@ -245,17 +245,7 @@ impl MetadataStore {
 
     pub fn hardfork_info(&self, target_number: u64) -> ProtocolResult<H256> {
         match self.trie.get(HARDFORK_KEY.as_bytes())? {
-            Some(data) => {
-                let hardfork_info = HardforkInfo::decode(data)?;
-
-                for k in hardfork_info.inner.iter().rev() {
-                    if k.block_number > target_number {
-                        continue;
-                    }
-                    return Ok(k.flags);
-                }
-                Ok(H256::zero())
-            }
+            Some(data) => Ok(HardforkInfo::decode(data)?.flags_at(target_number)),
             None => Ok(H256::zero()),
         }
     }
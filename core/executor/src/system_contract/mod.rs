@@ -29,6 +29,7 @@ use parking_lot::RwLock;
 use rocksdb::DB;
 
 use protocol::traits::{CkbDataProvider, ExecutorAdapter};
+use protocol::trie::DB as _;
 use protocol::types::{
     Bytes, HardforkInfoInner, Hasher, Metadata, SignedTransaction, TransactionAction, TxResp, H160,
     H256,
@@ -106,6 +107,17 @@ pub trait SystemContract<Adapter: ExecutorAdapter + ApplyBackend> {
     fn after_block_hook(&self, _adapter: &mut Adapter) {}
 }
 
+/// Commit the writes of the executing block to the metadata and CKB light
+/// client DBs.
+pub(crate) fn flush_system_contract_dbs() {
+    for db in [&METADATA_DB, &HEADER_CELL_DB] {
+        if let Some(db) = db.read().as_ref() {
+            db.flush()
+                .unwrap_or_else(|e| panic!("failed to flush system contract db since {e}"));
+        }
+    }
+}
+
 pub fn swap_metadata_db(new_db: Arc<RocksTrieDB>) -> Arc<RocksTrieDB> {
     METADATA_DB
         .write()
@@ -131,8 +143,9 @@ pub fn init<Adapter: ExecutorAdapter + ApplyBackend>(
     adapter: &mut Adapter,
     metadata_list: &[Metadata],
     hardfork: HardforkInfoInner,
+    state_pruning: Option<u64>,
 ) -> ProtocolResult<(H256, H256)> {
-    let ret = init_system_contract_db(db, adapter);
+    let ret = init_system_contract_db(db, adapter, state_pruning);
     init_metadata_and_hardfork(adapter, ret.0, metadata_list, hardfork)?;
 
     Ok(ret)
@@ -140,10 +153,12 @@ pub fn init<Adapter: ExecutorAdapter + ApplyBackend>(
 
 /// This method only init the CKB light client and metadata DB and should be
 /// used in run process. The return value`tuple[0]` is current metadata MPT
-/// root, `tuple[1]` is current CKB light client MPT root.
+/// root, `tuple[1]` is current CKB light client MPT root. The states of the
+/// two DBs are pruned as the world state by `state_pruning`.
 pub fn init_system_contract_db<Adapter: ExecutorAdapter + ApplyBackend>(
     db: Arc<DB>,
     adapter: &mut Adapter,
+    state_pruning: Option<u64>,
 ) -> (H256, H256) {
     let current_metadata_root = adapter.storage(METADATA_CONTRACT_ADDRESS, *METADATA_ROOT_KEY);
 
@@ -153,6 +168,7 @@ pub fn init_system_contract_db<Adapter: ExecutorAdapter + ApplyBackend>(
         _db.replace(Arc::new(RocksTrieDB::new_metadata(
             Arc::clone(&db),
             METADATA_DB_CACHE_SIZE,
+            state_pruning,
         )));
     }
 
@@ -161,6 +177,7 @@ pub fn init_system_contract_db<Adapter: ExecutorAdapter + ApplyBackend>(
         _db.replace(Arc::new(RocksTrieDB::new_ckb_light_client(
             db,
             HEADER_CELL_DB_CACHE_SIZE,
+            state_pruning,
         )));
     }
 
@@ -2,23 +2,34 @@ mod system_script;
 
 use std::collections::BTreeMap;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{atomic::AtomicBool, Arc};
 
 use evm::backend::{MemoryAccount, MemoryVicinity};
 use evm::Config;
 
+use common_config_parser::types::spec::HardforkName;
+use protocol::codec::{hex_decode, hex_encode};
+use protocol::traits::{Backend, Executor, ExecutorReadOnlyAdapter};
 use protocol::types::{
-    Bytes, Eip1559Transaction, ExecutorContext, ExitReason, ExitSucceed, Public,
-    SignatureComponents, SignedTransaction, TransactionAction, UnsignedTransaction,
-    UnverifiedTransaction, H160, H256, U256, U64,
+    AccessListItem, AccountOverride, Bytes, CallTracerConfig, Eip1559Transaction, ExecutorContext,
+    ExitReason, ExitSucceed, HardforkInfo, HardforkInfoInner, Header, Hex, Public,
+    SignatureComponents, SignedTransaction, SimulateCall, StateOverride, StructLoggerConfig,
+    TraceResult, Tracer, TransactionAction, UnsignedTransaction, UnverifiedTransaction, H160, H256,
+    U256, U64,
 };
-use protocol::{codec::hex_decode, tokio, traits::Executor, trie::MemoryDB};
+use protocol::{tokio, trie::MemoryDB};
 
 use core_db::MemoryAdapter;
 use core_storage::ImplStorage;
 
-use crate::AxonExecutorApplyAdapter;
-use crate::{precompiles::build_precompile_set, AxonExecutor as EvmExecutor, AxonExecutor};
+use crate::{
+    evm_config, next_block_base_fee_per_gas, next_block_required_base_fee,
+    precompiles::build_precompile_set, AxonExecutor as EvmExecutor, AxonExecutor,
+};
+use crate::{
+    AxonExecutorApplyAdapter, AxonExecutorOverlayAdapter, AxonExecutorReadOnlyAdapter,
+    TraceCancelled, CURRENT_HARDFORKS,
+};
 
 fn exec_adapter() -> AxonExecutorApplyAdapter<ImplStorage<MemoryAdapter>, MemoryDB> {
     let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()), 20);
@@ -31,6 +42,20 @@ fn exec_adapter() -> AxonExecutorApplyAdapter<ImplStorage<MemoryAdapter>, Memory
     AxonExecutorApplyAdapter::new(Arc::new(MemoryDB::new(false)), Arc::new(storage), ctx).unwrap()
 }
 
+fn overlay_adapter() -> AxonExecutorOverlayAdapter<ImplStorage<MemoryAdapter>, MemoryDB> {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()), 20);
+    let ctx = ExecutorContext {
+        block_gas_limit: u32::MAX.into(),
+        block_base_fee_per_gas: U64::one(),
+        ..Default::default()
+    };
+    let backend =
+        AxonExecutorReadOnlyAdapter::new(Arc::new(MemoryDB::new(false)), Arc::new(storage), ctx)
+            .unwrap();
+
+    AxonExecutorOverlayAdapter::new(backend)
+}
+
 fn gen_vicinity() -> MemoryVicinity {
     MemoryVicinity {
         gas_price:              U256::zero(),
@@ -196,3 +221,438 @@ async fn test_simplestorage() {
     );
     assert_eq!(r.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
 }
+
+// The transactions creating the SimpleStorage contract of `test_simplestorage`
+// at `0xc15d2ba57d126e6603240e89437efd419ce329d2` and calling `set(42)`.
+fn simplestorage_txs() -> Vec<SignedTransaction> {
+    let sender = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+    let contract = H160::from_str("0xc15d2ba57d126e6603240e89437efd419ce329d2").unwrap();
+    let mut create = gen_tx(
+        sender,
+        contract,
+        0,
+        hex_decode("608060405234801561001057600080fd5b5060df8061001f6000396000f3006080604052600436106049576000357c0100000000000000000000000000000000000000000000000000000000900463ffffffff16806360fe47b114604e5780636d4ce63c146078575b600080fd5b348015605957600080fd5b5060766004803603810190808035906020019092919050505060a0565b005b348015608357600080fd5b50608a60aa565b6040518082815260200191505060405180910390f35b8060008190555050565b600080549050905600a165627a7a7230582099c66a25d59f0aa78f7ebc40748fa1d1fbc335d8d780f284841b30e0365acd960029").unwrap(),
+    );
+    create
+        .transaction
+        .unsigned
+        .set_action(TransactionAction::Create);
+    let set = gen_tx(
+        sender,
+        contract,
+        0,
+        hex_decode("60fe47b1000000000000000000000000000000000000000000000000000000000000002a")
+            .unwrap(),
+    );
+
+    vec![create, set]
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trace_exec_struct_logger() {
+    let mut adapter = overlay_adapter();
+    let traces = AxonExecutor
+        .trace_exec(
+            &mut adapter,
+            &simplestorage_txs(),
+            1,
+            &Tracer::StructLogger(StructLoggerConfig::default()),
+            &Arc::default(),
+        )
+        .unwrap();
+
+    // Only the transactions from `trace_from` are traced.
+    assert_eq!(traces.len(), 1);
+    let res = match &traces[0] {
+        TraceResult::StructLogs(res) => res,
+        _ => panic!("expect struct logs"),
+    };
+    assert!(!res.failed);
+    assert!(res.return_value.is_empty());
+    assert_eq!(res.struct_logs[0].op, "PUSH1");
+    assert_eq!(res.struct_logs.last().unwrap().op, "STOP");
+    assert!(res.struct_logs.iter().all(|log| log.depth == 1));
+
+    let sstore = res
+        .struct_logs
+        .iter()
+        .find(|log| log.op == "SSTORE")
+        .unwrap();
+    assert_eq!(
+        sstore
+            .storage
+            .as_ref()
+            .unwrap()
+            .get(&hex_encode(H256::zero())),
+        Some(&hex_encode(H256::from_low_u64_be(42)))
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trace_exec_call_tracer() {
+    let contract = H160::from_str("0xc15d2ba57d126e6603240e89437efd419ce329d2").unwrap();
+    let mut txs = simplestorage_txs();
+    txs.push(gen_tx(
+        txs[0].sender,
+        contract,
+        0,
+        hex_decode("6d4ce63c").unwrap(),
+    ));
+
+    let mut adapter = overlay_adapter();
+    let traces = AxonExecutor
+        .trace_exec(
+            &mut adapter,
+            &txs,
+            0,
+            &Tracer::CallTracer(CallTracerConfig::default()),
+            &Arc::default(),
+        )
+        .unwrap();
+    assert_eq!(traces.len(), 3);
+
+    let frames = traces
+        .into_iter()
+        .map(|trace| match trace {
+            TraceResult::CallFrame(frame) => frame,
+            _ => panic!("expect call frame"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(frames[0].call_type, "CREATE");
+    assert_eq!(frames[0].to, Some(contract));
+    assert!(frames
+        .iter()
+        .all(|f| f.error.is_none() && f.calls.is_empty()));
+
+    // The `get()` call reads the value stored by the previous `set(42)`.
+    assert_eq!(frames[2].call_type, "CALL");
+    assert_eq!(frames[2].from, txs[2].sender);
+    assert_eq!(frames[2].to, Some(contract));
+    assert_eq!(
+        frames[2].input,
+        Hex::encode(hex_decode("6d4ce63c").unwrap())
+    );
+    assert_eq!(
+        frames[2].output,
+        Some(Hex::encode(H256::from_low_u64_be(42)))
+    );
+    assert!(frames[2].gas_used.as_u64() > 21000);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trace_exec_cancelled() {
+    let mut adapter = overlay_adapter();
+    let res = AxonExecutor.trace_exec(
+        &mut adapter,
+        &simplestorage_txs(),
+        1,
+        &Tracer::default(),
+        &Arc::new(AtomicBool::new(true)),
+    );
+    assert_eq!(res, Err(TraceCancelled));
+
+    // The cancelled execution does not break the tracing afterwards.
+    let mut adapter = overlay_adapter();
+    let res = AxonExecutor.trace_exec(
+        &mut adapter,
+        &simplestorage_txs(),
+        1,
+        &Tracer::default(),
+        &Arc::default(),
+    );
+    assert_eq!(res.unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_create_access_list() {
+    let sender = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+    let contract = H160::from_str("0x1000000000000000000000000000000000000000").unwrap();
+    let other = H160::from_str("0x2000000000000000000000000000000000000000").unwrap();
+
+    // PUSH1 0x01 SLOAD POP
+    // PUSH1 0x2a PUSH1 0x02 SSTORE
+    // PUSH20 0x2000000000000000000000000000000000000000 BALANCE POP
+    // STOP
+    let code =
+        hex_decode("60015450602a600255732000000000000000000000000000000000000000315000").unwrap();
+    let mut adapter = overlay_adapter();
+    adapter.apply_state_override(&StateOverride::from([(contract, AccountOverride {
+        code: Some(Hex::encode(code)),
+        ..Default::default()
+    })]));
+
+    let (access_list, resp) = AxonExecutor.create_access_list(
+        &adapter,
+        1_000_000,
+        Some(sender),
+        Some(contract),
+        U256::zero(),
+        Vec::new(),
+        Vec::new(),
+    );
+    assert!(resp.exit_reason.is_succeed());
+
+    // The slots of the called contract are recorded though the address is
+    // excluded.
+    assert_eq!(access_list, vec![
+        AccessListItem {
+            address:      contract,
+            storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+        },
+        AccessListItem {
+            address:      other,
+            storage_keys: vec![],
+        },
+    ]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_simulate_carries_state() {
+    let mut adapter = exec_adapter();
+    let sender = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+    let contract = H160::from_str("0xc15d2ba57d126e6603240e89437efd419ce329d2").unwrap();
+    let call = |to: Option<H160>, data: &str| SimulateCall {
+        from: sender,
+        to,
+        gas_limit: 1_000_000,
+        data: hex_decode(data).unwrap(),
+        ..Default::default()
+    };
+
+    // Create the SimpleStorage contract of `test_simplestorage`, then call
+    // `set(42)` and `get()` on the created contract.
+    let calls = vec![
+        call(None, "608060405234801561001057600080fd5b5060df8061001f6000396000f3006080604052600436106049576000357c0100000000000000000000000000000000000000000000000000000000900463ffffffff16806360fe47b114604e5780636d4ce63c146078575b600080fd5b348015605957600080fd5b5060766004803603810190808035906020019092919050505060a0565b005b348015608357600080fd5b50608a60aa565b6040518082815260200191505060405180910390f35b8060008190555050565b600080549050905600a165627a7a7230582099c66a25d59f0aa78f7ebc40748fa1d1fbc335d8d780f284841b30e0365acd960029"),
+        call(
+            Some(contract),
+            "60fe47b1000000000000000000000000000000000000000000000000000000000000002a",
+        ),
+        call(Some(contract), "6d4ce63c"),
+    ];
+    let resps = AxonExecutor
+        .simulate(&mut adapter, &calls, &Arc::default())
+        .unwrap();
+
+    assert_eq!(resps.len(), 3);
+    assert_eq!(resps[0].code_address.map(H160::from), Some(contract));
+    assert!(resps.iter().all(|r| r.exit_reason.is_succeed()));
+    assert_eq!(resps[2].ret, H256::from_low_u64_be(42).as_bytes());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_simulate_block_gas_limit() {
+    let sender = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+    let receiver = H160::from_str("0x1000000000000000000000000000000000000000").unwrap();
+    let transfer = SimulateCall {
+        from: sender,
+        to: Some(receiver),
+        gas_limit: 30_000,
+        ..Default::default()
+    };
+
+    let mut adapter = overlay_adapter();
+    adapter.set_block_context(ExecutorContext {
+        block_gas_limit: 50_000.into(),
+        ..Default::default()
+    });
+
+    // The third call only gets the gas left in the block.
+    let resps = AxonExecutor
+        .simulate(
+            &mut adapter,
+            &[transfer.clone(), transfer.clone(), transfer.clone()],
+            &Arc::default(),
+        )
+        .unwrap();
+    assert!(resps[0].exit_reason.is_succeed());
+    assert!(resps[1].exit_reason.is_succeed());
+    assert!(resps[2].exit_reason.is_error());
+    assert_eq!(resps[2].gas_used, 8_000);
+
+    let res = AxonExecutor.simulate(
+        &mut overlay_adapter(),
+        &[transfer],
+        &Arc::new(AtomicBool::new(true)),
+    );
+    assert_eq!(res, Err(TraceCancelled));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_speculate_state_override() {
+    let sender = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+    let receiver = H160::from_str("0x1000000000000000000000000000000000000000").unwrap();
+
+    let mut adapter = overlay_adapter();
+    adapter.apply_state_override(&StateOverride::from([(sender, AccountOverride {
+        balance: Some(1_000_000.into()),
+        ..Default::default()
+    })]));
+    let resp = AxonExecutor.speculate(&mut adapter, &[gen_tx(sender, receiver, 1000, vec![])]);
+    assert_eq!(resp.tx_resp.len(), 1);
+    assert!(resp.tx_resp[0].exit_reason.is_succeed());
+
+    // The changes exported from the overlay make up the same state when they
+    // are applied to the underlying state again.
+    let state_override = adapter.state_override();
+    assert_eq!(state_override[&sender].balance, Some(999_000.into()));
+    assert_eq!(state_override[&sender].nonce, Some(U64::one()));
+    assert_eq!(state_override[&receiver].balance, Some(1000.into()));
+
+    let mut replayed = overlay_adapter();
+    replayed.apply_state_override(&state_override);
+    for address in [sender, receiver] {
+        assert_eq!(
+            replayed.get_account(&address),
+            adapter.get_account(&address)
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_state_override() {
+    let sender = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+    let contract = H160::from_str("0x1000000000000000000000000000000000000000").unwrap();
+    let call = |adapter: &AxonExecutorOverlayAdapter<_, _>| {
+        let resp = AxonExecutor.call(
+            adapter,
+            1_000_000,
+            Some(sender),
+            Some(contract),
+            U256::zero(),
+            Vec::new(),
+            false,
+        );
+        assert!(resp.exit_reason.is_succeed());
+        U256::from_big_endian(&resp.ret)
+    };
+
+    // CALLER BALANCE PUSH1 0x01 SLOAD ADD PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00
+    // RETURN
+    let code = hex_decode("33316001540160005260206000f3").unwrap();
+    let mut adapter = overlay_adapter();
+    assert!(adapter.code(contract).is_empty());
+
+    adapter.apply_state_override(&StateOverride::from([(contract, AccountOverride {
+        code: Some(Hex::encode(code)),
+        ..Default::default()
+    })]));
+    assert_eq!(call(&adapter), U256::zero());
+
+    adapter.apply_state_override(&StateOverride::from([(sender, AccountOverride {
+        balance: Some(1000.into()),
+        ..Default::default()
+    })]));
+    assert_eq!(call(&adapter), U256::from(1000));
+
+    adapter.apply_state_override(&StateOverride::from([(contract, AccountOverride {
+        state_diff: Some(BTreeMap::from([(
+            H256::from_low_u64_be(1),
+            H256::from_low_u64_be(42),
+        )])),
+        ..Default::default()
+    })]));
+    assert_eq!(call(&adapter), U256::from(1042));
+
+    // The whole storage is replaced.
+    adapter.apply_state_override(&StateOverride::from([(contract, AccountOverride {
+        state: Some(BTreeMap::new()),
+        ..Default::default()
+    })]));
+    assert_eq!(call(&adapter), U256::from(1000));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_overlay_original_storage() {
+    let sender = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+    let contract = H160::from_str("0x1000000000000000000000000000000000000000").unwrap();
+    let slot = H256::from_low_u64_be(2);
+
+    // PUSH1 0x2a PUSH1 0x02 SSTORE STOP
+    let code = hex_decode("602a60025500").unwrap();
+    let mut adapter = overlay_adapter();
+    adapter.apply_state_override(&StateOverride::from([
+        (sender, AccountOverride {
+            balance: Some(1_000_000.into()),
+            ..Default::default()
+        }),
+        (contract, AccountOverride {
+            code: Some(Hex::encode(code)),
+            state_diff: Some(BTreeMap::from([(slot, H256::from_low_u64_be(7))])),
+            ..Default::default()
+        }),
+    ]));
+
+    // The overridden value is not the original one.
+    assert_eq!(adapter.storage(contract, slot), H256::from_low_u64_be(7));
+    assert_eq!(adapter.original_storage(contract, slot), Some(H256::zero()));
+
+    // The value committed by a transaction is the original one of the next
+    // transaction.
+    let resp = AxonExecutor.speculate(&mut adapter, &[gen_tx(sender, contract, 0, vec![])]);
+    assert!(resp.tx_resp[0].exit_reason.is_succeed());
+    assert_eq!(adapter.storage(contract, slot), H256::from_low_u64_be(42));
+    assert_eq!(
+        adapter.original_storage(contract, slot),
+        Some(H256::from_low_u64_be(42))
+    );
+}
+
+#[test]
+fn test_next_block_base_fee_by_height() {
+    // The hardfork determined at block 10 is enabled since block 11.
+    let hardforks = HardforkInfo {
+        inner: vec![HardforkInfoInner {
+            block_number: 10,
+            flags:        H256::from_low_u64_be((HardforkName::Apus as u64).to_be()),
+        }],
+    };
+    let parent = |number| Header {
+        number,
+        gas_limit: 30_000_000u64.into(),
+        gas_used: 30_000_000u64.into(),
+        base_fee_per_gas: 2000u64.into(),
+        ..Default::default()
+    };
+
+    let before = parent(9);
+    assert_eq!(next_block_required_base_fee(&before, &hardforks), None);
+    assert_eq!(
+        next_block_base_fee_per_gas(&before, &hardforks),
+        protocol::constants::BASE_FEE_PER_GAS.into()
+    );
+
+    let after = parent(10);
+    assert_eq!(
+        next_block_required_base_fee(&after, &hardforks),
+        Some(after.next_base_fee_per_gas())
+    );
+    assert_eq!(
+        next_block_base_fee_per_gas(&after, &hardforks),
+        after.next_base_fee_per_gas()
+    );
+    assert!(after.next_base_fee_per_gas() > after.base_fee_per_gas);
+}
+
+#[test]
+fn test_evm_config_by_hardfork() {
+    let config_with = |hardforks: u64| {
+        CURRENT_HARDFORKS.with(|h| *h.borrow_mut() = H256::from_low_u64_be(hardforks.to_be()));
+        evm_config()
+    };
+
+    let london = config_with(HardforkName::Andromeda as u64);
+    assert!(!london.has_push0);
+    assert!(!london.has_tloadstore);
+    assert!(!london.has_mcopy);
+    assert!(!london.has_eip_6780);
+    assert!(!london.warm_coinbase_address);
+    assert_eq!(london.max_initcode_size, None);
+
+    let cancun = config_with(HardforkName::Andromeda as u64 | HardforkName::Antlia as u64);
+    assert!(cancun.has_push0);
+    assert!(cancun.has_tloadstore);
+    assert!(cancun.has_mcopy);
+    assert!(cancun.has_eip_6780);
+    assert!(cancun.warm_coinbase_address);
+    assert!(cancun.max_initcode_size.is_some());
+}
@@ -25,7 +25,7 @@ pub fn test_write_functions() {
     let inner_db = RocksAdapter::new(ROCKSDB_PATH, Default::default())
         .unwrap()
         .inner_db();
-    init_system_contract_db(inner_db, &mut backend);
+    init_system_contract_db(inner_db, &mut backend, None);
 
     // need to refactor to be OO
     test_update_first(&mut backend, &executor);
@@ -2,11 +2,17 @@ use std::collections::BTreeMap;
 use std::str::FromStr;
 
 use ckb_types::{bytes::Bytes, packed, prelude::*};
-use ethers::abi::AbiEncode;
+use ethers::abi::{AbiDecode, AbiEncode};
+use evm::executor::stack::PrecompileFailure;
+use evm::{Context, ExitError};
 
 use core_db::RocksAdapter;
 use protocol::types::{Backend, MemoryBackend, TxResp, H160, U256};
 
+use crate::precompiles::{
+    get_cell::{Cell, GetCell},
+    PrecompileContract,
+};
 use crate::system_contract::image_cell::{
     image_cell_abi, CellInfo, CellKey, ImageCellContract, ImageCellReader,
 };
@@ -27,7 +33,7 @@ pub fn test_write_functions() {
     let inner_db = RocksAdapter::new(ROCKSDB_PATH, Default::default())
         .unwrap()
         .inner_db();
-    let (m_root, h_root) = init_system_contract_db(inner_db, &mut backend);
+    let (m_root, h_root) = init_system_contract_db(inner_db, &mut backend, None);
 
     CURRENT_METADATA_ROOT.with(|r| *r.borrow_mut() = m_root);
     CURRENT_HEADER_CELL_ROOT.with(|r| *r.borrow_mut() = h_root);
@@ -62,6 +68,11 @@ fn test_update_first<'a>(
     let cell_key = CellKey::new([7u8; 32], 0x0);
     let get_cell = ImageCellReader.get_cell(root, &cell_key).unwrap().unwrap();
     check_cell(&get_cell, 0x1, None);
+
+    let cell = get_cell_by_precompile(backend).unwrap();
+    assert!(!cell.is_consumed);
+    assert_eq!(cell.created_number, 0x1);
+    assert_eq!(cell.cell_data, prepare_outputs()[0].data);
 }
 
 fn test_update_second<'a>(
@@ -88,6 +99,11 @@ fn test_update_second<'a>(
     let cell_key = CellKey::new([7u8; 32], 0x0);
     let get_cell = ImageCellReader.get_cell(root, &cell_key).unwrap().unwrap();
     check_cell(&get_cell, 0x1, Some(0x2));
+
+    let cell = get_cell_by_precompile(backend).unwrap();
+    assert!(cell.is_consumed);
+    assert_eq!(cell.created_number, 0x1);
+    assert_eq!(cell.consumed_number, 0x2);
 }
 
 fn test_rollback_first<'a>(
@@ -134,6 +150,7 @@ fn test_rollback_second<'a>(
     let cell_key = CellKey::new([7u8; 32], 0x0);
     let get_cell = ImageCellReader.get_cell(root, &cell_key).unwrap();
     assert!(get_cell.is_none());
+    assert!(get_cell_by_precompile(backend).is_none());
 }
 
 fn test_set_state<'a>(
@@ -161,6 +178,41 @@ fn exec<'a>(
     executor.exec_(backend, &tx)
 }
 
+fn get_cell_by_precompile(backend: &MemoryBackend<'_>) -> Option<Cell> {
+    let root = backend.storage(CKB_LIGHT_CLIENT_CONTRACT_ADDRESS, *HEADER_CELL_ROOT_KEY);
+    CURRENT_HEADER_CELL_ROOT.with(|r| *r.borrow_mut() = root);
+
+    let input = image_cell_abi::OutPoint {
+        tx_hash: [7u8; 32],
+        index:   0x0,
+    }
+    .encode();
+    let context = Context {
+        address:        Default::default(),
+        caller:         Default::default(),
+        apparent_value: Default::default(),
+    };
+
+    // The gas of the input is charged before the cell is read, so a missing
+    // cell runs out of gas as well.
+    let gas_cost = GetCell::gas_cost(&input);
+    assert_eq!(
+        GetCell::exec_fn(&input, Some(gas_cost - 1), &context, false),
+        Err(PrecompileFailure::Error {
+            exit_status: ExitError::OutOfGas,
+        })
+    );
+    assert_eq!(GetCell::gas_cost(&input[1..]), u64::MAX);
+
+    GetCell::exec_fn(&input, None, &context, false)
+        .ok()
+        .map(|(output, gas)| {
+            // The gas cost contains the cost of the cell data.
+            assert!(gas > gas_cost);
+            <(Cell,) as AbiDecode>::decode(output.output).unwrap().0
+        })
+}
+
 fn check_cell(get_cell: &CellInfo, created_number: u64, consumed_number: Option<u64>) {
     let cell = &prepare_outputs()[0];
 
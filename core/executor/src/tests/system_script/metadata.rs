@@ -30,7 +30,7 @@ fn test_write_functions() {
     let inner_db = RocksAdapter::new(ROCKSDB_PATH, Default::default())
         .unwrap()
         .inner_db();
-    init_system_contract_db(inner_db, &mut backend);
+    init_system_contract_db(inner_db, &mut backend, None);
 
     test_init(&mut backend, &executor);
 
@@ -220,6 +220,7 @@ fn test_set_ckb_related_info() {
         _db.replace(Arc::new(RocksTrieDB::new_metadata(
             Arc::clone(&inner_db),
             METADATA_DB_CACHE_SIZE,
+            None,
         )));
     }
 
@@ -0,0 +1,111 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use evm::tracing::Event as EvmEvent;
+use evm_gasometer::tracing::Event as GasometerEvent;
+use evm_runtime::tracing::Event as RuntimeEvent;
+
+use protocol::types::{AccessList, AccessListItem, H160, H256};
+
+use crate::tracer::TraceListener;
+
+/// A tracer which records every address and storage slot accessed during an
+/// execution, compatible with the access list tracer of geth.
+pub struct AccessListTracer {
+    excluded: BTreeSet<H160>,
+    list:     BTreeMap<H160, BTreeSet<H256>>,
+}
+
+impl TraceListener for AccessListTracer {
+    fn evm_event(&mut self, _event: EvmEvent) {}
+
+    fn runtime_event(&mut self, event: RuntimeEvent) {
+        match event {
+            RuntimeEvent::Step { opcode, stack, .. } => {
+                let stack = stack.data();
+                let operand = match opcode.0 {
+                    // BALANCE, EXTCODESIZE, EXTCODECOPY, EXTCODEHASH and SELFDESTRUCT
+                    0x31 | 0x3b | 0x3c | 0x3f | 0xff => stack.last(),
+                    // CALL, CALLCODE, DELEGATECALL and STATICCALL
+                    0xf1 | 0xf2 | 0xf4 | 0xfa => stack.iter().rev().nth(1),
+                    _ => None,
+                };
+
+                if let Some(operand) = operand {
+                    self.add_address(H160::from(*operand));
+                }
+            }
+            RuntimeEvent::SLoad { address, index, .. }
+            | RuntimeEvent::SStore { address, index, .. } => self.add_slot(address, index),
+            _ => (),
+        }
+    }
+
+    fn gasometer_event(&mut self, _event: GasometerEvent) {}
+}
+
+impl AccessListTracer {
+    pub fn new(access_list: &AccessList, excluded: BTreeSet<H160>) -> Self {
+        let mut tracer = AccessListTracer {
+            excluded,
+            list: BTreeMap::new(),
+        };
+
+        for item in access_list.iter() {
+            tracer.add_address(item.address);
+            for slot in item.storage_keys.iter() {
+                tracer.add_slot(item.address, *slot);
+            }
+        }
+
+        tracer
+    }
+
+    pub fn access_list(&self) -> AccessList {
+        self.list
+            .iter()
+            .map(|(address, slots)| AccessListItem {
+                address:      *address,
+                storage_keys: slots.iter().copied().collect(),
+            })
+            .collect()
+    }
+
+    fn add_address(&mut self, address: H160) {
+        if !self.excluded.contains(&address) {
+            self.list.entry(address).or_default();
+        }
+    }
+
+    // The slots of the excluded addresses are still recorded as geth does.
+    fn add_slot(&mut self, address: H160, slot: H256) {
+        self.list.entry(address).or_default().insert(slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_list_exclusion() {
+        let excluded = H160::random();
+        let address = H160::random();
+        let slot = H256::random();
+        let access_list = vec![
+            AccessListItem {
+                address:      excluded,
+                storage_keys: vec![],
+            },
+            AccessListItem {
+                address,
+                storage_keys: vec![slot],
+            },
+        ];
+
+        let tracer = AccessListTracer::new(&access_list, [excluded].into_iter().collect());
+        assert_eq!(tracer.access_list(), vec![AccessListItem {
+            address,
+            storage_keys: vec![slot],
+        }]);
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::BTreeSet;
+
+use evm::tracing::Event as EvmEvent;
+use evm::CreateScheme;
+use evm_gasometer::tracing::Event as GasometerEvent;
+use evm_runtime::tracing::Event as RuntimeEvent;
+
+use protocol::types::{
+    CallFrame, CallLog, CallTracerConfig, ExitReason, Hex, SignedTransaction, TraceResult, TxResp,
+    H160, U256,
+};
+
+use crate::tracer::{exit_error, revert_reason, GasStep, TraceListener};
+
+/// A tracer which records the call tree of an execution, compatible with the
+/// `callTracer` of geth.
+pub struct CallTracer {
+    config:      CallTracerConfig,
+    precompiles: BTreeSet<H160>,
+    // The running call frames and the gas used by each of them.
+    stack:       Vec<(CallFrame, u64)>,
+    // A call frame which is entered but has not executed any opcode yet, and
+    // the context address of it.
+    pending:     Option<(CallFrame, H160)>,
+    root:        Option<CallFrame>,
+    remaining:   u64,
+}
+
+impl TraceListener for CallTracer {
+    fn evm_event(&mut self, event: EvmEvent) {
+        match event {
+            EvmEvent::Call {
+                code_address,
+                transfer,
+                input,
+                target_gas,
+                is_static,
+                context,
+            } => {
+                self.flush_pending();
+
+                let call_type = if is_static {
+                    "STATICCALL"
+                } else if context.address != code_address {
+                    if transfer.is_some() {
+                        "CALLCODE"
+                    } else {
+                        "DELEGATECALL"
+                    }
+                } else {
+                    "CALL"
+                };
+                let from = if context.address != code_address {
+                    context.address
+                } else {
+                    context.caller
+                };
+                let value = match call_type {
+                    "STATICCALL" => None,
+                    "DELEGATECALL" => Some(context.apparent_value),
+                    _ => Some(transfer.as_ref().map(|t| t.value).unwrap_or_default()),
+                };
+
+                let frame = self.new_frame(call_type, from, code_address, value, target_gas, input);
+
+                // The precompile contracts do not emit any exit event.
+                if self.precompiles.contains(&code_address) {
+                    self.add_frame(frame);
+                } else {
+                    self.pending = Some((frame, context.address));
+                }
+            }
+            EvmEvent::Create {
+                caller,
+                address,
+                scheme,
+                value,
+                init_code,
+                target_gas,
+            } => {
+                self.flush_pending();
+
+                let call_type = match scheme {
+                    CreateScheme::Create2 { .. } => "CREATE2",
+                    _ => "CREATE",
+                };
+                let frame = self.new_frame(
+                    call_type,
+                    caller,
+                    address,
+                    Some(value),
+                    target_gas,
+                    init_code,
+                );
+                self.pending = Some((frame, address));
+            }
+            EvmEvent::Exit {
+                reason,
+                return_value,
+            } => {
+                let (mut frame, used) = match self.pending.take() {
+                    Some((frame, _)) => (frame, 0),
+                    None => match self.stack.pop() {
+                        Some(item) => item,
+                        None => return,
+                    },
+                };
+
+                frame.gas_used = used.into();
+                frame.error = exit_error(reason);
+
+                match reason {
+                    ExitReason::Succeed(_) => frame.output = Some(Hex::encode(return_value)),
+                    ExitReason::Revert(_) => {
+                        frame.output = Some(Hex::encode(return_value));
+                        frame.revert_reason = revert_reason(return_value);
+                    }
+                    _ => (),
+                }
+
+                if frame.error.is_some() {
+                    clear_logs(&mut frame);
+                }
+
+                self.add_frame(frame);
+            }
+            _ => (),
+        }
+    }
+
+    fn runtime_event(&mut self, event: RuntimeEvent) {
+        if let RuntimeEvent::Step {
+            context,
+            opcode,
+            stack,
+            memory,
+            ..
+        } = event
+        {
+            if let Some((frame, address)) = self.pending.take() {
+                if address == context.address {
+                    self.stack.push((frame, 0));
+                } else {
+                    // The call was aborted before any execution.
+                    self.add_frame(frame);
+                }
+            }
+
+            if !self.config.with_log || !(0xa0..=0xa4).contains(&opcode.0) {
+                return;
+            }
+
+            // The operands of a LOG opcode are the offset, the size and the topics,
+            // from the top of the stack.
+            let operands = stack.data().iter().rev().collect::<Vec<_>>();
+            let topic_num = (opcode.0 - 0xa0) as usize;
+            if operands.len() < 2 + topic_num {
+                return;
+            }
+
+            let offset = U256::from_big_endian(operands[0].as_bytes());
+            let size = U256::from_big_endian(operands[1].as_bytes());
+            let data = if size.is_zero() {
+                Vec::new()
+            } else if offset > usize::MAX.into() || size > usize::MAX.into() {
+                return;
+            } else {
+                read_memory(memory.data(), offset.as_usize(), size.as_usize())
+            };
+
+            if let Some((frame, _)) = self.stack.last_mut() {
+                frame.logs.push(CallLog {
+                    address: context.address,
+                    topics:  operands[2..2 + topic_num].iter().map(|t| **t).collect(),
+                    data:    Hex::encode(data),
+                });
+            }
+        }
+    }
+
+    fn gasometer_event(&mut self, event: GasometerEvent) {
+        if let Some(step) = GasStep::from_event(&event) {
+            if let Some((_, used)) = self.stack.last_mut() {
+                *used = step.used();
+            }
+
+            self.remaining = step.after;
+        }
+    }
+}
+
+impl CallTracer {
+    pub fn new(config: CallTracerConfig, precompiles: BTreeSet<H160>) -> Self {
+        CallTracer {
+            config,
+            precompiles,
+            stack: Vec::new(),
+            pending: None,
+            root: None,
+            remaining: 0,
+        }
+    }
+
+    pub fn finish(self, tx: &SignedTransaction, resp: &TxResp) -> TraceResult {
+        let tx_ref = &tx.transaction.unsigned;
+        self.finish_call(
+            tx.sender,
+            tx_ref.to(),
+            *tx_ref.value(),
+            tx_ref.data(),
+            tx_ref.gas_limit().low_u64(),
+            resp,
+        )
+    }
+
+    /// Build the call tree of a top call, the frame is built from the given
+    /// arguments if the call was not executed by the EVM.
+    pub fn finish_call(
+        mut self,
+        from: H160,
+        to: Option<H160>,
+        value: U256,
+        input: &[u8],
+        gas_limit: u64,
+        resp: &TxResp,
+    ) -> TraceResult {
+        self.flush_pending();
+        while let Some((frame, _)) = self.stack.pop() {
+            self.add_frame(frame);
+        }
+
+        let mut root = self.root.unwrap_or_else(|| CallFrame {
+            call_type: if to.is_none() {
+                "CREATE".to_string()
+            } else {
+                "CALL".to_string()
+            },
+            from,
+            to,
+            value: Some(value),
+            gas: Default::default(),
+            gas_used: Default::default(),
+            input: Hex::encode(input),
+            output: None,
+            error: exit_error(&resp.exit_reason),
+            revert_reason: None,
+            calls: Vec::new(),
+            logs: Vec::new(),
+        });
+
+        root.gas = gas_limit.into();
+        root.gas_used = resp.gas_used.into();
+
+        TraceResult::CallFrame(root)
+    }
+
+    fn new_frame(
+        &self,
+        call_type: &str,
+        from: H160,
+        to: H160,
+        value: Option<U256>,
+        target_gas: Option<u64>,
+        input: &[u8],
+    ) -> CallFrame {
+        // At most all but one 64th of the remaining gas is passed to a sub call.
+        let available = self.remaining - self.remaining / 64;
+        let gas = target_gas.map(|g| g.min(available)).unwrap_or(available);
+
+        CallFrame {
+            call_type: call_type.to_string(),
+            from,
+            to: Some(to),
+            value,
+            gas: gas.into(),
+            gas_used: Default::default(),
+            input: Hex::encode(input),
+            output: None,
+            error: None,
+            revert_reason: None,
+            calls: Vec::new(),
+            logs: Vec::new(),
+        }
+    }
+
+    fn add_frame(&mut self, frame: CallFrame) {
+        match self.stack.last_mut() {
+            Some((parent, _)) => {
+                if !self.config.only_top_call {
+                    parent.calls.push(frame);
+                }
+            }
+            None => self.root = Some(frame),
+        }
+    }
+
+    fn flush_pending(&mut self) {
+        if let Some((frame, _)) = self.pending.take() {
+            self.add_frame(frame);
+        }
+    }
+}
+
+fn clear_logs(frame: &mut CallFrame) {
+    frame.logs.clear();
+    frame.calls.iter_mut().for_each(clear_logs);
+}
+
+fn read_memory(memory: &[u8], offset: usize, size: usize) -> Vec<u8> {
+    let mut ret = vec![0u8; size];
+    if offset < memory.len() {
+        let end = memory.len().min(offset.saturating_add(size));
+        ret[..end - offset].copy_from_slice(&memory[offset..end]);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::types::H256;
+
+    use super::*;
+
+    #[test]
+    fn test_read_memory() {
+        let memory = (0u8..64).collect::<Vec<_>>();
+
+        assert_eq!(read_memory(&memory, 0, 4), vec![0, 1, 2, 3]);
+        assert_eq!(read_memory(&memory, 62, 4), vec![62, 63, 0, 0]);
+        assert_eq!(read_memory(&memory, 100, 2), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_clear_logs() {
+        let log = CallLog {
+            address: H160::default(),
+            topics:  vec![H256::default()],
+            data:    Hex::empty(),
+        };
+        let mut frame = CallFrame {
+            call_type:     "CALL".to_string(),
+            from:          H160::default(),
+            to:            None,
+            value:         None,
+            gas:           Default::default(),
+            gas_used:      Default::default(),
+            input:         Hex::empty(),
+            output:        None,
+            error:         None,
+            revert_reason: None,
+            calls:         Vec::new(),
+            logs:          vec![log.clone()],
+        };
+        let mut child = frame.clone();
+        child.logs.push(log);
+        frame.calls.push(child);
+
+        clear_logs(&mut frame);
+        assert!(frame.logs.is_empty());
+        assert!(frame.calls[0].logs.is_empty());
+    }
+}
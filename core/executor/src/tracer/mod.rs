@@ -0,0 +1,256 @@
+mod access_list;
+mod call_tracer;
+mod opcode;
+mod struct_logger;
+
+pub use access_list::AccessListTracer;
+pub use call_tracer::CallTracer;
+pub use struct_logger::StructLogger;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use evm::tracing::{using as evm_using, Event as EvmEvent, EventListener as EvmListener};
+use evm_gasometer::tracing::{
+    using as gasometer_using, Event as GasometerEvent, EventListener as GasometerListener,
+};
+use evm_runtime::tracing::{
+    using as runtime_using, Event as RuntimeEvent, EventListener as RuntimeListener,
+};
+
+use protocol::types::ExitReason;
+
+use crate::utils::decode_revert_msg;
+
+const EXEC_REVERTED: &str = "execution reverted";
+
+/// A listener of all the tracing events emitted by the EVM, the runtime and
+/// the gasometer.
+pub trait TraceListener {
+    fn evm_event(&mut self, event: EvmEvent);
+
+    fn runtime_event(&mut self, event: RuntimeEvent);
+
+    fn gasometer_event(&mut self, event: GasometerEvent);
+}
+
+/// A tracer which records nothing, it makes an execution without tracing
+/// cancellable.
+pub struct NoopTracer;
+
+impl TraceListener for NoopTracer {
+    fn evm_event(&mut self, _event: EvmEvent) {}
+
+    fn runtime_event(&mut self, _event: RuntimeEvent) {}
+
+    fn gasometer_event(&mut self, _event: GasometerEvent) {}
+}
+
+/// The three EVM modules require three distinct listeners, so the tracer is
+/// shared through a reference counted cell.
+struct ListenerProxy<T> {
+    tracer: Rc<RefCell<T>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl<T> ListenerProxy<T> {
+    fn new(tracer: &Rc<RefCell<T>>, cancel: &Arc<AtomicBool>) -> Self {
+        ListenerProxy {
+            tracer: Rc::clone(tracer),
+            cancel: Arc::clone(cancel),
+        }
+    }
+}
+
+impl<T: TraceListener> EvmListener for ListenerProxy<T> {
+    fn event(&mut self, event: EvmEvent) {
+        self.tracer.borrow_mut().evm_event(event);
+    }
+}
+
+impl<T: TraceListener> RuntimeListener for ListenerProxy<T> {
+    fn event(&mut self, event: RuntimeEvent) {
+        // The EVM can not be interrupted, so the execution is unwound from the
+        // step before the next opcode once the trace is cancelled.
+        if matches!(event, RuntimeEvent::Step { .. }) && self.cancel.load(Ordering::Acquire) {
+            panic::resume_unwind(Box::new(TraceCancelled));
+        }
+
+        self.tracer.borrow_mut().runtime_event(event);
+    }
+}
+
+impl<T: TraceListener> GasometerListener for ListenerProxy<T> {
+    fn event(&mut self, event: GasometerEvent) {
+        self.tracer.borrow_mut().gasometer_event(event);
+    }
+}
+
+/// The error of a trace which is cancelled before the execution finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceCancelled;
+
+/// Run the given function with the tracer listening to all the EVM events,
+/// return the tracer and the result of the function.
+pub fn trace_using<T, R, F>(tracer: T, f: F) -> (T, R)
+where
+    T: TraceListener + 'static,
+    F: FnOnce() -> R,
+{
+    match trace_until(tracer, &Arc::default(), f) {
+        Ok(ret) => ret,
+        Err(_) => unreachable!("the trace is never cancelled"),
+    }
+}
+
+/// Same as `trace_using` but the execution is aborted once the `cancel` flag
+/// is set, such as the deadline of the trace is reached. The state changed by
+/// an aborted execution should be discarded.
+pub fn trace_until<T, R, F>(
+    tracer: T,
+    cancel: &Arc<AtomicBool>,
+    f: F,
+) -> Result<(T, R), TraceCancelled>
+where
+    T: TraceListener + 'static,
+    F: FnOnce() -> R,
+{
+    let tracer = Rc::new(RefCell::new(tracer));
+    let mut evm_listener = ListenerProxy::new(&tracer, cancel);
+    let mut runtime_listener = ListenerProxy::new(&tracer, cancel);
+    let mut gasometer_listener = ListenerProxy::new(&tracer, cancel);
+
+    let ret = panic::catch_unwind(AssertUnwindSafe(|| {
+        evm_using(&mut evm_listener, || {
+            runtime_using(&mut runtime_listener, || {
+                gasometer_using(&mut gasometer_listener, f)
+            })
+        })
+    }));
+
+    drop(evm_listener);
+    drop(runtime_listener);
+    drop(gasometer_listener);
+
+    let ret = match ret {
+        Ok(ret) => ret,
+        Err(payload) if payload.is::<TraceCancelled>() => return Err(TraceCancelled),
+        Err(payload) => panic::resume_unwind(payload),
+    };
+
+    let tracer = match Rc::try_unwrap(tracer) {
+        Ok(cell) => cell.into_inner(),
+        Err(_) => unreachable!("all the listener proxies have been dropped"),
+    };
+
+    Ok((tracer, ret))
+}
+
+/// The gas state of the running gasometer around a gasometer event.
+pub(crate) struct GasStep {
+    pub gas_limit: u64,
+    pub before:    u64,
+    pub after:     u64,
+}
+
+impl GasStep {
+    pub fn from_event(event: &GasometerEvent) -> Option<Self> {
+        let (snapshot, after) = match event {
+            GasometerEvent::RecordCost { cost, snapshot } => {
+                let s = (*snapshot)?;
+                (
+                    s,
+                    remaining(s.gas_limit, s.used_gas, s.memory_gas).saturating_sub(*cost),
+                )
+            }
+            GasometerEvent::RecordDynamicCost {
+                gas_cost,
+                memory_gas,
+                snapshot,
+                ..
+            } => {
+                let s = (*snapshot)?;
+                let memory_gas = s.memory_gas.max(*memory_gas);
+                (
+                    s,
+                    remaining(s.gas_limit, s.used_gas, memory_gas).saturating_sub(*gas_cost),
+                )
+            }
+            GasometerEvent::RecordStipend { stipend, snapshot } => {
+                let s = (*snapshot)?;
+                (
+                    s,
+                    remaining(s.gas_limit, s.used_gas, s.memory_gas) + stipend,
+                )
+            }
+            GasometerEvent::RecordRefund { snapshot, .. } => {
+                let s = (*snapshot)?;
+                (s, remaining(s.gas_limit, s.used_gas, s.memory_gas))
+            }
+            GasometerEvent::RecordTransaction { cost, snapshot } => {
+                let s = (*snapshot)?;
+                (
+                    s,
+                    remaining(s.gas_limit, s.used_gas, s.memory_gas).saturating_sub(*cost),
+                )
+            }
+        };
+
+        Some(GasStep {
+            gas_limit: snapshot.gas_limit,
+            before: remaining(snapshot.gas_limit, snapshot.used_gas, snapshot.memory_gas),
+            after,
+        })
+    }
+
+    pub fn cost(&self) -> u64 {
+        self.before.saturating_sub(self.after)
+    }
+
+    pub fn used(&self) -> u64 {
+        self.gas_limit.saturating_sub(self.after)
+    }
+}
+
+fn remaining(gas_limit: u64, used_gas: u64, memory_gas: u64) -> u64 {
+    gas_limit
+        .saturating_sub(used_gas)
+        .saturating_sub(memory_gas)
+}
+
+/// Returns the geth compatible error message of a failed exit reason.
+pub fn exit_error(reason: &ExitReason) -> Option<String> {
+    match reason {
+        ExitReason::Succeed(_) => None,
+        ExitReason::Revert(_) => Some(EXEC_REVERTED.to_string()),
+        ExitReason::Error(e) => Some(match e {
+            evm::ExitError::OutOfGas => "out of gas".to_string(),
+            evm::ExitError::StackUnderflow => "stack underflow".to_string(),
+            evm::ExitError::StackOverflow => "stack limit reached".to_string(),
+            evm::ExitError::InvalidJump => "invalid jump destination".to_string(),
+            evm::ExitError::CallTooDeep => "max call depth exceeded".to_string(),
+            evm::ExitError::OutOfFund => "insufficient balance for transfer".to_string(),
+            evm::ExitError::CreateCollision => "contract address collision".to_string(),
+            evm::ExitError::CreateContractLimit => "max code size exceeded".to_string(),
+            evm::ExitError::Other(msg) => msg.to_string(),
+            e => format!("{:?}", e),
+        }),
+        ExitReason::Fatal(e) => Some(format!("{:?}", e)),
+    }
+}
+
+/// Returns the reason of a reverted execution if the return data is an ABI
+/// encoded `Error(string)`.
+pub(crate) fn revert_reason(ret: &[u8]) -> Option<String> {
+    const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+    if !ret.starts_with(&ERROR_SELECTOR) {
+        return None;
+    }
+
+    let msg = decode_revert_msg(ret);
+    msg.strip_prefix(EXEC_REVERTED)
+        .map(|reason| reason.trim_start_matches(": ").to_string())
+        .or(Some(msg))
+}
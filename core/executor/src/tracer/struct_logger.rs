@@ -0,0 +1,181 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use evm::tracing::Event as EvmEvent;
+use evm_gasometer::tracing::Event as GasometerEvent;
+use evm_runtime::tracing::Event as RuntimeEvent;
+
+use protocol::codec::hex_encode;
+use protocol::types::{
+    Hex, StructLog, StructLogResult, StructLoggerConfig, TraceResult, TxResp, H160, U256,
+};
+
+use crate::tracer::{opcode::opcode_name, GasStep, TraceListener};
+
+/// A tracer which records every executed opcode, compatible with the default
+/// struct logger of geth.
+pub struct StructLogger {
+    config:      StructLoggerConfig,
+    precompiles: BTreeSet<H160>,
+    logs:        Vec<StructLog>,
+    storages:    BTreeMap<H160, BTreeMap<String, String>>,
+    // The return data buffer of each running call frame, the length of it is
+    // the depth of the current frame.
+    return_data: Vec<Vec<u8>>,
+    // The context address of a call frame which is entered but has not
+    // executed any opcode yet.
+    pending:     Option<H160>,
+    // The index of the log which the gasometer events are accounted to.
+    current:     Option<usize>,
+    gas_pending: bool,
+    remaining:   u64,
+}
+
+impl TraceListener for StructLogger {
+    fn evm_event(&mut self, event: EvmEvent) {
+        match event {
+            EvmEvent::Call {
+                code_address,
+                context,
+                ..
+            } => {
+                if !self.precompiles.contains(&code_address) {
+                    self.pending = Some(context.address);
+                }
+            }
+            EvmEvent::Create { address, .. } => self.pending = Some(address),
+            EvmEvent::Exit { return_value, .. } => {
+                // A frame without any opcode executed does not change the depth.
+                if self.pending.take().is_none() {
+                    self.return_data.pop();
+                }
+
+                if let Some(buf) = self.return_data.last_mut() {
+                    *buf = return_value.to_vec();
+                }
+
+                self.current = None;
+            }
+            _ => (),
+        }
+    }
+
+    fn runtime_event(&mut self, event: RuntimeEvent) {
+        match event {
+            RuntimeEvent::Step {
+                context,
+                opcode,
+                position,
+                stack,
+                memory,
+            } => {
+                if let Some(address) = self.pending.take() {
+                    // If the next executed opcode is not in the entered frame, the
+                    // call was aborted before any execution.
+                    if address == context.address {
+                        self.return_data.push(Vec::new());
+                    }
+                }
+
+                let stack = (!self.config.disable_stack).then(|| {
+                    stack
+                        .data()
+                        .iter()
+                        .map(|v| U256::from_big_endian(v.as_bytes()))
+                        .collect()
+                });
+                let memory = self
+                    .config
+                    .enable_memory
+                    .then(|| memory.data().chunks(32).map(hex_encode).collect());
+                let return_data = self
+                    .config
+                    .enable_return_data
+                    .then(|| self.return_data.last().cloned().unwrap_or_default())
+                    .filter(|data| !data.is_empty())
+                    .map(Hex::encode);
+
+                self.logs.push(StructLog {
+                    pc: position.as_ref().map(|pc| *pc as u64).unwrap_or_default(),
+                    op: opcode_name(opcode.0),
+                    gas: self.remaining,
+                    gas_cost: 0,
+                    depth: self.return_data.len(),
+                    error: None,
+                    stack,
+                    return_data,
+                    memory,
+                    storage: None,
+                });
+                self.current = Some(self.logs.len() - 1);
+                self.gas_pending = true;
+            }
+            RuntimeEvent::SLoad {
+                address,
+                index,
+                value,
+            }
+            | RuntimeEvent::SStore {
+                address,
+                index,
+                value,
+            } => {
+                if self.config.disable_storage {
+                    return;
+                }
+
+                let storage = self.storages.entry(address).or_default();
+                storage.insert(hex_encode(index), hex_encode(value));
+
+                if let Some(log) = self.current.and_then(|idx| self.logs.get_mut(idx)) {
+                    log.storage = Some(storage.clone());
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn gasometer_event(&mut self, event: GasometerEvent) {
+        let step = match GasStep::from_event(&event) {
+            Some(step) => step,
+            None => return,
+        };
+
+        if let Some(log) = self.current.and_then(|idx| self.logs.get_mut(idx)) {
+            // The snapshot of the first gasometer event after a step is the gas
+            // state before the opcode executed.
+            if self.gas_pending {
+                log.gas = step.before;
+                self.gas_pending = false;
+            }
+
+            log.gas_cost += step.cost();
+        }
+
+        self.remaining = step.after;
+    }
+}
+
+impl StructLogger {
+    pub fn new(config: StructLoggerConfig, precompiles: BTreeSet<H160>) -> Self {
+        StructLogger {
+            config,
+            precompiles,
+            logs: Vec::new(),
+            storages: BTreeMap::new(),
+            return_data: Vec::new(),
+            pending: None,
+            current: None,
+            gas_pending: false,
+            remaining: 0,
+        }
+    }
+
+    pub fn finish(self, resp: &TxResp) -> TraceResult {
+        TraceResult::StructLogs(StructLogResult {
+            gas:          resp.gas_used,
+            failed:       !resp.exit_reason.is_succeed(),
+            return_value: hex_encode(&resp.ret),
+            struct_logs:  self.logs,
+        })
+    }
+}
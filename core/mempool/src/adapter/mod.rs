@@ -31,7 +31,8 @@ use protocol::{
 use common_apm_derive::trace_span;
 use common_crypto::{Crypto, Secp256k1Recoverable};
 use core_executor::{
-    is_call_system_script, AxonExecutorReadOnlyAdapter, DataProvider, MetadataHandle,
+    is_call_system_script, next_block_required_base_fee, AxonExecutorReadOnlyAdapter, DataProvider,
+    MetadataHandle,
 };
 use core_interoperation::InteroperationImpl;
 
@@ -123,6 +124,9 @@ pub struct DefaultMemPoolAdapter<C, N, S, DB, I> {
     trie_db: Arc<DB>,
 
     addr_nonce:  DashMap<H160, (U64, U256)>,
+    // The base fee required by the next block of the latest header, which is
+    // cached with the hash of the header.
+    base_fee:    Mutex<Option<(Hash, Option<U64>)>>,
     max_tx_size: AtomicUsize,
     chain_id:    u64,
 
@@ -167,6 +171,7 @@ where
             trie_db,
 
             addr_nonce: DashMap::new(),
+            base_fee: Mutex::new(None),
             max_tx_size: AtomicUsize::new(max_tx_size),
             chain_id,
 
@@ -249,6 +254,48 @@ where
         Ok(())
     }
 
+    async fn verify_base_fee(&self, ctx: Context, stx: &SignedTransaction) -> ProtocolResult<()> {
+        if is_call_system_script(stx.transaction.unsigned.action())? {
+            return Ok(());
+        }
+
+        let header = self.storage.get_latest_block_header(ctx).await?;
+        let header_hash = header.hash();
+        let cached = (*self.base_fee.lock())
+            .filter(|(hash, _)| *hash == header_hash)
+            .map(|(_, base_fee)| base_fee);
+        let required_base_fee = match cached {
+            Some(base_fee) => base_fee,
+            None => {
+                let root = AxonExecutorReadOnlyAdapter::from_root(
+                    header.state_root,
+                    Arc::clone(&self.trie_db),
+                    Arc::clone(&self.storage),
+                    Default::default(),
+                )?
+                .get_metadata_root();
+                let hardforks = MetadataHandle::new(root).hardfork_infos()?;
+                let base_fee = next_block_required_base_fee(&header, &hardforks);
+                *self.base_fee.lock() = Some((header_hash, base_fee));
+                base_fee
+            }
+        };
+
+        if let Some(base_fee) = required_base_fee {
+            let max_fee_per_gas = *stx.transaction.unsigned.max_fee_per_gas();
+            if max_fee_per_gas < base_fee {
+                return Err(MemPoolError::UnderBaseFee {
+                    tx_hash: stx.transaction.hash,
+                    max_fee_per_gas,
+                    base_fee_per_gas: base_fee,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
     fn verify_gas_limit(&self, ctx: Context, stx: &SignedTransaction) -> ProtocolResult<()> {
         let gas_limit_tx = stx.transaction.unsigned.gas_limit();
         if gas_limit_tx < &(MIN_TRANSACTION_GAS_LIMIT.into()) {
@@ -450,6 +497,7 @@ where
         self.verify_chain_id(ctx.clone(), stx)?;
         self.verify_tx_size(ctx.clone(), stx)?;
         self.verify_gas_price(stx)?;
+        self.verify_base_fee(ctx.clone(), stx).await?;
         self.verify_gas_limit(ctx.clone(), stx)?;
         self.verify_signature(ctx, stx).await?;
 
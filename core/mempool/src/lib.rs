@@ -16,11 +16,12 @@ use futures::future::try_join_all;
 
 use common_apm::Instant;
 
+use protocol::tokio::{self, sync::broadcast};
 use protocol::traits::{Context, MemPool, MemPoolAdapter};
 use protocol::types::{
-    BlockNumber, Hash, PackedTxHashes, SignedTransaction, H160, H256, U256, U64,
+    BlockNumber, Hash, MemPoolContent, PackedTxHashes, SignedTransaction, H160, H256, U256, U64,
 };
-use protocol::{async_trait, tokio, Display, ProtocolError, ProtocolErrorKind, ProtocolResult};
+use protocol::{async_trait, Display, ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use core_executor::is_call_system_script;
 use core_network::NetworkContext;
@@ -335,6 +336,26 @@ where
         self.pool.get_by_hash(tx_hash)
     }
 
+    fn drop_txs(&self, _ctx: Context, tx_hashes: &[Hash]) {
+        self.pool.drop_txs(tx_hashes)
+    }
+
+    fn peek_package(&self, _ctx: Context, tx_num_limit: u64) -> PackedTxHashes {
+        self.pool.peek_package(tx_num_limit as usize)
+    }
+
+    fn get_status(&self, _ctx: Context) -> (usize, usize) {
+        self.pool.status()
+    }
+
+    fn get_content(&self, _ctx: Context, sender: Option<H160>) -> MemPoolContent {
+        self.pool.content(sender)
+    }
+
+    fn subscribe_new_txs(&self, _ctx: Context) -> broadcast::Receiver<SignedTransaction> {
+        self.pool.subscribe_new_txs()
+    }
+
     fn set_args(&self, context: Context, state_root: H256, gas_limit: u64, max_tx_size: u64) {
         self.adapter
             .set_args(context, state_root, gas_limit, max_tx_size);
@@ -378,6 +399,18 @@ pub enum MemPoolError {
     #[display(fmt = "Invalid gas price {:?}", _0)]
     InvalidGasPrice(u64),
 
+    #[display(
+        fmt = "Tx: {:?} max fee per gas {} is under the base fee per gas {}",
+        tx_hash,
+        max_fee_per_gas,
+        base_fee_per_gas
+    )]
+    UnderBaseFee {
+        tx_hash:          Hash,
+        max_fee_per_gas:  U64,
+        base_fee_per_gas: U64,
+    },
+
     #[display(
         fmt = "Tx: {:?} exceeds size limit, now: {}, limit: {} Bytes",
         tx_hash,
@@ -9,15 +9,20 @@ use crossbeam_queue::ArrayQueue;
 use dashmap::DashMap;
 use parking_lot::{Mutex, RwLock};
 
-use protocol::tokio::{self, time::sleep};
+use protocol::tokio::{self, sync::broadcast, time::sleep};
 use protocol::types::{
-    BlockNumber, Bytes, Hash, PackedTxHashes, SignedTransaction, H160, U256, U64,
+    BlockNumber, Bytes, Hash, MemPoolContent, PackedTxHashes, SignedTransaction, H160, U256, U64,
 };
 use protocol::{constants::MEMPOOL_REFRESH_TIMEOUT, ProtocolResult};
 
 use crate::tx_wrapper::{PendingQueue, TxPtr, TxWrapper};
 use crate::MemPoolError;
 
+/// The number of new transactions buffered for each pending transaction
+/// subscriber. A subscriber that falls behind this far loses its backlog
+/// rather than blocking the insertion.
+const NEW_TX_NOTIFY_CAPACITY: usize = 1024;
+
 pub struct PriorityPool {
     sys_tx_bucket:          BuiltInContractTxBucket,
     // The transaction data in this queue should be consistent with the real queue.
@@ -38,7 +43,9 @@ pub struct PriorityPool {
     // in the transaction pool, the transaction will be discarded.
     timeout_config:         u64,
 
-    flush_lock: Arc<RwLock<()>>,
+    flush_lock:      Arc<RwLock<()>>,
+    // Notify the subscribers once a transaction is accepted.
+    new_tx_notifier: broadcast::Sender<SignedTransaction>,
 }
 
 impl PriorityPool {
@@ -53,6 +60,7 @@ impl PriorityPool {
             timeout_gap: Mutex::new(BTreeMap::new()),
             timeout_config,
             flush_lock: Arc::new(RwLock::new(())),
+            new_tx_notifier: broadcast::channel(NEW_TX_NOTIFY_CAPACITY).0,
         };
 
         let co_queue = Arc::clone(&pool.co_queue);
@@ -145,6 +153,11 @@ impl PriorityPool {
             }
             dashmap::mapref::entry::Entry::Vacant(v) => {
                 v.insert(Arc::clone(&ptr));
+                if self.new_tx_notifier.receiver_count() != 0 {
+                    // Sending never blocks, it only fails when there is no
+                    // subscriber.
+                    let _ = self.new_tx_notifier.send(ptr.raw_tx());
+                }
                 let _ = self.co_queue.push((ptr, check_nonce));
             }
         }
@@ -183,6 +196,31 @@ impl PriorityPool {
         }
     }
 
+    /// Returns the transactions `package` would return without changing the
+    /// pool, so the received transactions not yet moved into the real queue
+    /// are left out.
+    pub fn peek_package(&self, limit: usize) -> PackedTxHashes {
+        let _flushing = self.flush_lock.read();
+
+        let mut hashes = self.sys_tx_bucket.package();
+        let call_system_script_count = hashes.len() as u32;
+
+        let mut q = self.real_queue.lock().clone();
+        q.sort_unstable();
+
+        hashes.extend(
+            q.iter()
+                .filter(|ptr| !ptr.is_dropped())
+                .map(|ptr| ptr.hash())
+                .take(limit),
+        );
+
+        PackedTxHashes {
+            hashes,
+            call_system_script_count,
+        }
+    }
+
     fn flush_to_pending_queue(&self) {
         let mut q = self.real_queue.lock();
         let txs = pop_all_item(Arc::clone(&self.co_queue));
@@ -204,6 +242,10 @@ impl PriorityPool {
         }
     }
 
+    pub fn subscribe_new_txs(&self) -> broadcast::Receiver<SignedTransaction> {
+        self.new_tx_notifier.subscribe()
+    }
+
     pub fn len(&self) -> usize {
         self.stock_len.load(Ordering::Acquire)
     }
@@ -235,6 +277,42 @@ impl PriorityPool {
         }
     }
 
+    /// The transactions in the package list and the first system script
+    /// transaction of each bucket are pending, the others are queued.
+    pub fn status(&self) -> (usize, usize) {
+        let _flushing = self.flush_lock.read();
+        let (mut pending, mut queued) = self.sys_tx_bucket.status();
+
+        for ptr in self.tx_map.iter().filter(|ptr| !ptr.is_dropped()) {
+            if ptr.is_package() {
+                pending += 1;
+            } else {
+                queued += 1;
+            }
+        }
+
+        (pending, queued)
+    }
+
+    pub fn content(&self, sender: Option<H160>) -> MemPoolContent {
+        let _flushing = self.flush_lock.read();
+        let mut content = self.sys_tx_bucket.content(sender);
+
+        for ptr in self.tx_map.iter() {
+            if ptr.is_dropped() || sender.map(|s| s != ptr.sender()).unwrap_or_default() {
+                continue;
+            }
+
+            if ptr.is_package() {
+                content.pending.push(ptr.raw_tx());
+            } else {
+                content.queued.push(ptr.raw_tx());
+            }
+        }
+
+        content
+    }
+
     pub fn flush(&self, hashes: &[Hash], number: BlockNumber) {
         let _flushing = self.flush_lock.write();
         self.flush_to_pending_queue();
@@ -247,6 +325,17 @@ impl PriorityPool {
         }
     }
 
+    /// Mark the transactions dropped, they are removed by the next flush.
+    pub fn drop_txs(&self, hashes: &[Hash]) {
+        let _flushing = self.flush_lock.read();
+
+        for hash in hashes {
+            if let Some(ptr) = self.tx_map.get(hash) {
+                ptr.set_dropped();
+            }
+        }
+    }
+
     fn flush_inner(&self, hashes: &[Hash], reduce_len: &mut usize, number: BlockNumber) {
         let mut q = self.real_queue.lock();
         let mut timeout_gap = self.timeout_gap.lock();
@@ -372,6 +461,34 @@ impl BuiltInContractTxBucket {
         }
     }
 
+    pub fn status(&self) -> (usize, usize) {
+        self.tx_buckets
+            .iter()
+            .fold((0, 0), |(pending, queued), kv| {
+                (pending + 1, queued + kv.value().len() - 1)
+            })
+    }
+
+    pub fn content(&self, sender: Option<H160>) -> MemPoolContent {
+        let mut content = MemPoolContent::default();
+
+        for kv in self.tx_buckets.iter() {
+            for (i, tx) in kv.value().values().enumerate() {
+                if sender.map(|s| s != tx.sender).unwrap_or_default() {
+                    continue;
+                }
+
+                if i == 0 {
+                    content.pending.push(tx.clone());
+                } else {
+                    content.queued.push(tx.clone());
+                }
+            }
+        }
+
+        content
+    }
+
     pub fn contains(&self, hash: &Hash) -> bool {
         if let Some(data) = self.hash_data_map.get(hash) {
             if let Some(tx_map) = self.tx_buckets.get(data.value()) {
@@ -124,6 +124,59 @@ async fn test_package_multi_types() {
     assert_eq!(mempool.len(), 0);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_peek_package() {
+    let mempool = Arc::new(new_mempool(1024, 0, 0, 0).await);
+    let pool = mempool.get_tx_cache();
+    for tx in mock_sys_txs(2) {
+        pool.insert_system_script_tx(tx).unwrap();
+    }
+    for tx in default_mock_txs(10) {
+        pool.insert(tx, false, 0.into()).unwrap();
+    }
+    // the received transactions are moved into the real queue in the background
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert_eq!(10, pool.real_queue_len());
+
+    let peeked = mempool.peek_package(Context::new(), 5);
+    assert_eq!(peeked.call_system_script_count, 2);
+    assert_eq!(peeked.hashes.len(), 7);
+    // peeking leaves the pool unchanged
+    assert_eq!(
+        mempool.peek_package(Context::new(), 5).hashes,
+        peeked.hashes
+    );
+    assert_eq!(10, pool.real_queue_len());
+    assert_eq!(12, pool.len());
+
+    let packed = pool.package(1000.into(), 5);
+    assert_eq!(packed.hashes, peeked.hashes);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_drop_txs() {
+    let mempool = Arc::new(new_mempool(1024, 0, 0, 0).await);
+    let pool = mempool.get_tx_cache();
+    let txs = default_mock_txs(10);
+    for tx in txs.iter() {
+        pool.insert(tx.clone(), false, 0.into()).unwrap();
+    }
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+    let dropped = txs[..3]
+        .iter()
+        .map(|tx| tx.transaction.hash)
+        .collect::<Vec<_>>();
+    mempool.drop_txs(Context::new(), &dropped);
+    let packed = pool.package(1000.into(), 10);
+    assert_eq!(packed.hashes.len(), 7);
+    assert!(packed.hashes.iter().all(|hash| !dropped.contains(hash)));
+
+    // the dropped transactions are removed by the next flush
+    exec_flush(Vec::new(), Arc::clone(&mempool)).await;
+    assert_eq!(pool.len(), 7);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_flush() {
     let mempool = Arc::new(default_mempool().await);
@@ -314,6 +367,51 @@ async fn test_nonce_insert() {
     assert_eq!(0, pool.real_queue_len());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_status_and_content() {
+    let mempool = Arc::new(new_mempool(1024, 0, 0, 0).await);
+
+    let priv_key = Secp256k1RecoverablePrivateKey::generate(&mut OsRng);
+    let pub_key = priv_key.pub_key();
+    let txs: Vec<SignedTransaction> = (0..3)
+        .map(|i| mock_signed_tx(&priv_key, &pub_key, 0, i as u64, true))
+        .collect();
+
+    let pool = mempool.get_tx_cache();
+    pool.insert(txs[0].clone(), false, 0.into()).unwrap();
+    // the transaction of nonce 2 is queued until the nonce 1 is inserted
+    pool.insert(txs[2].clone(), false, 2.into()).unwrap();
+    pool.package(1000.into(), 3);
+
+    assert_eq!(pool.status(), (1, 1));
+    let content = mempool.get_content(Context::new(), Some(txs[0].sender));
+    assert_eq!(content.pending, vec![txs[0].clone()]);
+    assert_eq!(content.queued, vec![txs[2].clone()]);
+    let content = mempool.get_content(Context::new(), Some(H160::random()));
+    assert!(content.pending.is_empty() && content.queued.is_empty());
+
+    pool.insert(txs[1].clone(), false, 1.into()).unwrap();
+    pool.package(1000.into(), 3);
+    assert_eq!(mempool.get_status(Context::new()), (3, 0));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_subscribe_new_txs() {
+    let mempool = Arc::new(new_mempool(1024, 0, 0, 0).await);
+    let txs = default_mock_txs(2);
+
+    let pool = mempool.get_tx_cache();
+    pool.insert(txs[0].clone(), false, 0.into()).unwrap();
+
+    let mut recv = mempool.subscribe_new_txs(Context::new());
+    pool.insert(txs[1].clone(), false, 0.into()).unwrap();
+    // a duplicated transaction is not notified again
+    pool.insert(txs[1].clone(), false, 0.into()).unwrap();
+
+    assert_eq!(recv.recv().await.unwrap(), txs[1]);
+    assert!(recv.try_recv().is_err());
+}
+
 macro_rules! ensure_order_txs {
     ($in_pool: expr, $out_pool: expr, $pool_size: expr) => {
         let mempool = &Arc::new(new_mempool($pool_size, 0, 0, 0).await);
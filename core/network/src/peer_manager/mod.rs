@@ -2,6 +2,7 @@ use parking_lot::RwLock;
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::Duration,
 };
 use tentacle::{
     multiaddr::Multiaddr, secio::PeerId, utils::extract_peer_id, ProtocolId, SessionId,
@@ -235,6 +236,10 @@ impl PeerManager {
             || self.with_peer_store(|peer_store| !peer_store.is_addr_banned(addr))
     }
 
+    pub fn default_listen(&self) -> Multiaddr {
+        self.config.default_listen.clone()
+    }
+
     pub fn local_listen_addrs(&self) -> Vec<Multiaddr> {
         self.public_addrs.read().iter().cloned().collect()
     }
@@ -257,6 +262,17 @@ impl PeerManager {
         None
     }
 
+    pub fn update_peer_ping(&self, session_id: SessionId, ping: Duration) {
+        let mut online = self.online.write();
+        if let Some(info) = online
+            .peers
+            .values_mut()
+            .find(|info| info.session_id == session_id)
+        {
+            info.ping = Some(ping);
+        }
+    }
+
     pub fn ban_session_id(&self, session_id: SessionId, timeout: u64, ban_reason: String) {
         let addr = self.online.read().peers.values().find_map(|info| {
             if info.session_id == session_id {
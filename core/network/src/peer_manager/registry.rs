@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::Duration,
 };
 use tentacle::{
     context::SessionContext, multiaddr::Multiaddr, secio::PeerId, service::SessionType, ProtocolId,
@@ -14,6 +15,8 @@ pub struct PeerInfo {
     pub session_type: SessionType,
     pub listens:      Vec<Multiaddr>,
     pub reuse:        bool,
+    /// The round trip time of the latest ping
+    pub ping:         Option<Duration>,
 }
 
 impl PeerInfo {
@@ -25,6 +28,7 @@ impl PeerInfo {
             session_type:     ctx.ty,
             listens:          Vec::new(),
             reuse:            false,
+            ping:             None,
         }
     }
 
@@ -52,7 +52,9 @@ impl PingHandler {
 
     fn ping_received(&mut self, _id: SessionId) {}
 
-    fn pong_received(&mut self, _id: SessionId, _last_ping: Instant) {}
+    fn pong_received(&mut self, id: SessionId, last_ping: Instant) {
+        self.peer_manager.update_peer_ping(id, last_ping.elapsed());
+    }
 
     async fn ping_peers(&mut self, context: &ProtocolContext) {
         let now = Instant::now();
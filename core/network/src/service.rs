@@ -1,5 +1,6 @@
 use std::{collections::HashSet, sync::Arc, time::Duration};
 
+use ipnetwork::IpNetwork;
 use tentacle::{
     builder::ServiceBuilder,
     context::ServiceContext,
@@ -20,8 +21,8 @@ use protocol::tokio::time::{Instant, MissedTickBehavior};
 use protocol::{
     async_trait, tokio,
     traits::{
-        Context, Gossip, MessageCodec, MessageHandler, Network, PeerTag, PeerTrust, Priority, Rpc,
-        TrustFeedback,
+        ConnectedPeer, Context, Gossip, MessageCodec, MessageHandler, Network, NetworkNodeInfo,
+        PeerDirection, PeerTag, PeerTrust, Priority, Rpc, TrustFeedback,
     },
     types::Bytes,
     ProtocolResult,
@@ -182,6 +183,158 @@ impl Network for NetworkServiceHandle {
             .peer_manager
             .with_registry(|reg| reg.peers.len()))
     }
+
+    fn peers(&self, _ctx: Context) -> ProtocolResult<Vec<ConnectedPeer>> {
+        let peer_manager = &self.gossip.peer_manager;
+        let consensus_list = peer_manager.consensus_list.read();
+
+        Ok(peer_manager.with_registry(|reg| {
+            reg.peers
+                .iter()
+                .map(|(peer_id, info)| ConnectedPeer {
+                    peer_id:      peer_id.to_base58(),
+                    multiaddr:    info.addr.to_string(),
+                    direction:    if info.session_type.is_outbound() {
+                        PeerDirection::Outbound
+                    } else {
+                        PeerDirection::Inbound
+                    },
+                    latency:      info.ping,
+                    is_consensus: consensus_list.contains(peer_id),
+                })
+                .collect()
+        }))
+    }
+
+    fn node_info(&self, _ctx: Context) -> ProtocolResult<NetworkNodeInfo> {
+        let peer_manager = &self.gossip.peer_manager;
+        let mut listen_addrs = vec![peer_manager.default_listen().to_string()];
+        for addr in peer_manager.local_listen_addrs() {
+            let addr = addr.to_string();
+            if !listen_addrs.contains(&addr) {
+                listen_addrs.push(addr);
+            }
+        }
+        let status = peer_manager.with_registry(|reg| reg.connection_status());
+
+        Ok(NetworkNodeInfo {
+            peer_id: peer_manager.local_peer_id().to_base58(),
+            listen_addrs,
+            inbound: status.inbound,
+            outbound: status.outbound,
+            banned: peer_manager.with_peer_store(|store| store.ban_list().count()),
+        })
+    }
+
+    fn add_peer(&self, _ctx: Context, multiaddr: &str) -> ProtocolResult<()> {
+        let addr = multiaddr
+            .parse::<Multiaddr>()
+            .map_err(|_| NetworkError::UnexpectedPeerAddr(multiaddr.to_owned()))?;
+        let peer_id = extract_peer_id(&addr)
+            .ok_or_else(|| NetworkError::UnexpectedPeerAddr(multiaddr.to_owned()))?;
+        let peer_manager = &self.gossip.peer_manager;
+
+        if peer_manager.with_peer_store(|store| store.is_addr_banned(&addr)) {
+            return Err(
+                NetworkError::UnexpectedPeerAddr(format!("{} is banned", multiaddr)).into(),
+            );
+        }
+        if peer_manager.with_registry(|reg| reg.peers.contains_key(&peer_id)) {
+            return Ok(());
+        }
+
+        peer_manager
+            .with_peer_store_mut(|store| store.add_addr(addr.clone()))
+            .map_err(NetworkError::from)?;
+
+        if peer_manager.with_registry_mut(|reg| reg.dialing.insert(addr.clone())) {
+            let control = self.gossip.transmitter.clone();
+            tokio::spawn(async move {
+                let _ignore = control
+                    .dial(
+                        addr,
+                        TargetProtocol::Single(SupportProtocols::Identify.protocol_id()),
+                    )
+                    .await;
+            });
+        }
+        Ok(())
+    }
+
+    fn remove_peer(&self, _ctx: Context, peer_id: &str) -> ProtocolResult<bool> {
+        let peer_id = peer_id
+            .parse::<PeerId>()
+            .map_err(|_| NetworkError::InvalidPeerId)?;
+        let session_id = self
+            .gossip
+            .peer_manager
+            .with_registry(|reg| reg.peers.get(&peer_id).map(|info| info.session_id));
+
+        if let Some(id) = session_id {
+            let sender = self.gossip.transmitter.clone();
+            tokio::spawn(async move {
+                let _ignore = sender.disconnect(id).await;
+            });
+        }
+        Ok(session_id.is_some())
+    }
+
+    fn ban_peer(
+        &self,
+        _ctx: Context,
+        peer_id: &str,
+        timeout: Duration,
+        reason: String,
+    ) -> ProtocolResult<Vec<String>> {
+        let peer_id = peer_id
+            .parse::<PeerId>()
+            .map_err(|_| NetworkError::InvalidPeerId)?;
+        let peer_manager = &self.gossip.peer_manager;
+        let session = peer_manager.with_registry(|reg| {
+            reg.peers
+                .get(&peer_id)
+                .map(|info| (info.session_id, info.addr.clone()))
+        });
+
+        let mut addrs: Vec<Multiaddr> = peer_manager
+            .with_peer_store(|store| store.fetch_addr_by_peer_id(&peer_id).into_iter().collect());
+        if let Some((_, addr)) = &session {
+            if !addrs.contains(addr) {
+                addrs.push(addr.clone());
+            }
+        }
+
+        let timeout_ms = timeout.as_millis() as u64;
+        let mut networks = peer_manager.with_peer_store_mut(|store| {
+            addrs
+                .iter()
+                .filter_map(|addr| {
+                    store.ban_addr(addr, timeout_ms, reason.clone());
+                    multiaddr_to_socketaddr(addr).map(|addr| IpNetwork::from(addr.ip()).to_string())
+                })
+                .collect::<Vec<_>>()
+        });
+        networks.sort();
+        networks.dedup();
+
+        if let Some((id, _)) = session {
+            let sender = self.gossip.transmitter.clone();
+            tokio::spawn(async move {
+                let _ignore = sender.disconnect(id).await;
+            });
+        }
+        Ok(networks)
+    }
+
+    fn unban(&self, _ctx: Context, ip_network: &str) -> ProtocolResult<()> {
+        let network = ip_network
+            .parse::<IpNetwork>()
+            .map_err(|_| NetworkError::UnexpectedPeerAddr(ip_network.to_owned()))?;
+        self.gossip
+            .peer_manager
+            .with_peer_store_mut(|store| store.mut_ban_list().unban_network(&network));
+        Ok(())
+    }
 }
 
 pub struct NetworkService<K> {
@@ -14,9 +14,10 @@ use protocol::{
 };
 
 pub(crate) struct DatabaseGroup {
-    storage:  Arc<ImplStorage<RocksAdapter>>,
-    trie_db:  Arc<RocksTrieDB>,
-    inner_db: Arc<RocksDB>,
+    storage:       Arc<ImplStorage<RocksAdapter>>,
+    trie_db:       Arc<RocksTrieDB>,
+    inner_db:      Arc<RocksDB>,
+    state_pruning: Option<u64>,
 }
 
 impl DatabaseGroup {
@@ -25,6 +26,7 @@ impl DatabaseGroup {
         rocksdb_path: P,
         is_first_run: bool,
         triedb_cache_size: usize,
+        state_pruning: Option<u64>,
     ) -> ProtocolResult<Self> {
         let adapter_inner = if is_first_run {
             RocksAdapter::new(rocksdb_path, config.clone())
@@ -33,12 +35,19 @@ impl DatabaseGroup {
         }?;
         let adapter = Arc::new(adapter_inner);
         let inner_db = adapter.inner_db();
-        let trie_db = Arc::new(RocksTrieDB::new_evm(adapter.inner_db(), triedb_cache_size));
-        let storage = Arc::new(ImplStorage::new(adapter, config.cache_size));
+        let trie_db = Arc::new(RocksTrieDB::new_evm(
+            adapter.inner_db(),
+            triedb_cache_size,
+            state_pruning,
+        ));
+        let storage =
+            Arc::new(ImplStorage::new(adapter, config.cache_size).with_log_index(config.log_index));
+        storage.prepare_log_index()?;
         Ok(Self {
             storage,
             trie_db,
             inner_db,
+            state_pruning,
         })
     }
 
@@ -53,6 +62,10 @@ impl DatabaseGroup {
     pub(crate) fn inner_db(&self) -> Arc<RocksDB> {
         Arc::clone(&self.inner_db)
     }
+
+    pub(crate) fn state_pruning(&self) -> Option<u64> {
+        self.state_pruning
+    }
 }
 
 #[async_trait]
@@ -19,7 +19,7 @@ use protocol::types::{
 };
 use protocol::{lazy::CHAIN_ID, trie::DB as TrieDB, ProtocolResult};
 
-use core_api::{jsonrpc::run_jsonrpc_server, DefaultAPIAdapter};
+use core_api::{graphql::run_graphql_server, jsonrpc::run_jsonrpc_server, DefaultAPIAdapter};
 use core_consensus::status::{CurrentStatus, StatusAgent};
 use core_consensus::{
     util::OverlordCrypto, ConsensusWal, DurationConfig, OverlordConsensus,
@@ -70,6 +70,7 @@ pub fn init(config: Config, spec: ChainSpec) -> ProtocolResult<()> {
             path_rocksdb,
             true,
             config.executor.triedb_cache_size,
+            config.executor.state_pruning.keep_latest(),
         )?;
         log::info!("Initialize genesis block.");
         execute_genesis(genesis, &spec, &db_group).await
@@ -104,6 +105,7 @@ pub fn run<K: KeyProvider>(
             path_rocksdb,
             false,
             config.executor.triedb_cache_size,
+            config.executor.state_pruning.keep_latest(),
         )?;
         log::info!("Start all services.");
         start(version, config, key_provider, &db_group, stop_opt).await
@@ -144,6 +146,16 @@ async fn start<K: KeyProvider>(
 
     log::info!("At block number {}", current_block.header.number + 1);
 
+    // Index the logs of the existing blocks in background
+    if config.rocksdb.log_index {
+        let storage = Arc::clone(&storage);
+        tokio::spawn(async move {
+            if let Err(e) = storage.rebuild_log_index(Context::new()).await {
+                log::error!("rebuild log index error {:?}", e);
+            }
+        });
+    }
+
     let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
     let stop_signal = match stop_opt {
         Some(opt) => {
@@ -184,7 +196,7 @@ async fn start<K: KeyProvider>(
 
     // The first two metadata has been inserted in the init process, only need to
     // init the system contract DB here.
-    system_contract::init_system_contract_db(inner_db, &mut backend);
+    system_contract::init_system_contract_db(inner_db, &mut backend, db_group.state_pruning());
 
     // Init mempool and recover signed transactions with the current block number
     let current_stxs = txs_wal.load_by_number(current_block.header.number + 1);
@@ -234,6 +246,8 @@ async fn start<K: KeyProvider>(
         Arc::clone(&trie_db),
         Arc::clone(&crypto),
     )?;
+    let commit_notifier = consensus_adapter.commit_notifier();
+    let proof_notifier = consensus_adapter.proof_notifier();
     let consensus_adapter = Arc::new(consensus_adapter);
     let status_agent = get_status_agent(&storage, &current_block, &metadata).await?;
 
@@ -292,7 +306,10 @@ async fn start<K: KeyProvider>(
         Arc::clone(&storage),
         Arc::clone(&trie_db),
         Arc::new(network_handle),
+        commit_notifier,
+        proof_notifier,
     ));
+    let _graphql_handle = run_graphql_server(&config, Arc::clone(&api_adapter)).await?;
     let _handles = run_jsonrpc_server(version, config, api_adapter).await?;
 
     // Run sync
@@ -511,7 +528,13 @@ fn execute_genesis_transactions(
         Proposal::new_without_state_root(&rich.block.header).into(),
     )?;
 
-    system_contract::init(db_group.inner_db(), &mut backend, metadata_list, hardfork)?;
+    system_contract::init(
+        db_group.inner_db(),
+        &mut backend,
+        metadata_list,
+        hardfork,
+        db_group.state_pruning(),
+    )?;
 
     let resp = AxonExecutor.exec(&mut backend, &rich.txs, &[]);
 
@@ -552,6 +575,7 @@ pub fn set_hardfork_info(
             path_rocksdb,
             false,
             config.executor.triedb_cache_size,
+            config.executor.state_pruning.keep_latest(),
         )?;
 
         let storage = db_group.storage();
@@ -569,7 +593,7 @@ pub fn set_hardfork_info(
             Proposal::new_without_state_root(&current_block.header).into(),
         )?;
 
-        system_contract::init_system_contract_db(inner_db, &mut backend);
+        system_contract::init_system_contract_db(inner_db, &mut backend, db_group.state_pruning());
 
         let metadata_root = AxonExecutorReadOnlyAdapter::from_root(
             current_state_root,
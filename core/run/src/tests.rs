@@ -113,6 +113,7 @@ async fn check_genesis_data<'a>(case: &TestCase<'a>) {
         path_block,
         true,
         config.executor.triedb_cache_size,
+        config.executor.state_pruning.keep_latest(),
     )
     .expect("initialize databases");
 
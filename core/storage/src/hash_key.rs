@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use protocol::types::{Bytes, Hash, Hasher};
+use protocol::types::{Bytes, Hash, Hasher, H160, H256};
 use protocol::{codec::ProtocolCodec, ProtocolResult};
 
 const PREFIX_LEN: usize = 8;
@@ -128,3 +128,95 @@ impl FromStr for CommonHashKey {
 }
 
 pub type BlockKey = CommonPrefix;
+
+const LOG_INDEX_PREFIX_LEN: usize = 33;
+const LOG_INDEX_KEY_LEN: usize = LOG_INDEX_PREFIX_LEN + PREFIX_LEN;
+
+/// The kind of the item indexed by a `LogIndexKey`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogIndexKind {
+    Address,
+    Topic(u8),
+}
+
+impl LogIndexKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            LogIndexKind::Address => 0,
+            LogIndexKind::Topic(position) => position + 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            0 => LogIndexKind::Address,
+            n => LogIndexKind::Topic(n - 1),
+        }
+    }
+}
+
+/// The key of a posting list entry, which means the block contains the logs
+/// with the given address or topic. The block height is put in the end so
+/// that the entries of an item are sorted by height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogIndexKey {
+    kind:         LogIndexKind,
+    item:         H256,
+    block_height: u64,
+}
+
+impl LogIndexKey {
+    pub fn new(kind: LogIndexKind, item: H256, block_height: u64) -> Self {
+        LogIndexKey {
+            kind,
+            item,
+            block_height,
+        }
+    }
+
+    pub fn address(address: H160, block_height: u64) -> Self {
+        LogIndexKey::new(LogIndexKind::Address, address.into(), block_height)
+    }
+
+    pub fn topic(position: u8, topic: H256, block_height: u64) -> Self {
+        LogIndexKey::new(LogIndexKind::Topic(position), topic, block_height)
+    }
+
+    pub fn height(&self) -> u64 {
+        self.block_height
+    }
+
+    /// Returns whether the key belongs to the same posting list.
+    pub fn same_item(&self, other: &LogIndexKey) -> bool {
+        self.kind == other.kind && self.item == other.item
+    }
+
+    pub fn to_bytes(self) -> [u8; LOG_INDEX_KEY_LEN] {
+        let mut key = [0u8; LOG_INDEX_KEY_LEN];
+        key[0] = self.kind.to_byte();
+        key[1..LOG_INDEX_PREFIX_LEN].copy_from_slice(self.item.as_bytes());
+        key[LOG_INDEX_PREFIX_LEN..].copy_from_slice(&self.block_height.to_be_bytes());
+
+        key
+    }
+}
+
+impl ProtocolCodec for LogIndexKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::copy_from_slice(&self.to_bytes()))
+    }
+
+    fn decode<B: AsRef<[u8]>>(bytes: B) -> ProtocolResult<Self> {
+        let bytes = bytes.as_ref();
+        debug_assert!(bytes.len() >= LOG_INDEX_KEY_LEN);
+
+        let mut h_buf = [0u8; PREFIX_LEN];
+        h_buf.copy_from_slice(&bytes[LOG_INDEX_PREFIX_LEN..LOG_INDEX_KEY_LEN]);
+
+        Ok(LogIndexKey {
+            kind:         LogIndexKind::from_byte(bytes[0]),
+            item:         H256::from_slice(&bytes[1..LOG_INDEX_PREFIX_LEN]),
+            block_height: u64::from_be_bytes(h_buf),
+        })
+    }
+}
@@ -4,7 +4,7 @@ mod schema;
 #[cfg(test)]
 mod tests;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::convert::From;
 use std::error::Error;
 use std::sync::Arc;
@@ -16,23 +16,24 @@ use common_apm::Instant;
 use common_apm_derive::trace_span;
 use protocol::codec::ProtocolCodec;
 use protocol::traits::{
-    Context, ReadOnlyStorage, Storage, StorageAdapter, StorageBatchModify, StorageCategory,
-    StorageSchema,
+    Context, ReadOnlyStorage, Storage, StorageAdapter, StorageBatch, StorageBatchModify,
+    StorageCategory, StorageSchema,
 };
 use protocol::types::{
     Block, BlockNumber, Bytes, DBBytes, HardforkInfoInner, Hash, Hasher, Header, Proof, Receipt,
-    SignedTransaction, H256,
+    SignedTransaction, H160, H256,
 };
 use protocol::{
     async_trait, tokio, Display, From, ProtocolError, ProtocolErrorKind, ProtocolResult,
 };
 
 use crate::cache::StorageCache;
-use crate::hash_key::{BlockKey, CommonHashKey, CommonPrefix};
+use crate::hash_key::{BlockKey, CommonHashKey, CommonPrefix, LogIndexKey};
 use crate::schema::{
     BlockHashNumberSchema, BlockHeaderSchema, BlockSchema, EvmCodeAddressSchema, EvmCodeSchema,
-    HardforkSchema, LatestBlockSchema, LatestProofSchema, ReceiptBytesSchema, ReceiptSchema,
-    TransactionBytesSchema, TransactionSchema, TxHashNumberSchema,
+    HardforkSchema, LatestBlockSchema, LatestProofSchema, LogIndexMarkerSchema, LogIndexSchema,
+    ReceiptBytesSchema, ReceiptSchema, TransactionBytesSchema, TransactionSchema,
+    TxHashNumberSchema,
 };
 
 const BATCH_VALUE_DECODE_NUMBER: usize = 1000;
@@ -43,6 +44,8 @@ lazy_static::lazy_static! {
     pub static ref OVERLORD_WAL_KEY: Hash = Hasher::digest(Bytes::from("overlord_wal"));
     pub static ref MONITOR_CKB_NUMBER_KEY: Hash = Hasher::digest(Bytes::from("monitor_ckb_number"));
     pub static ref HAEDFORK_PROPOSAL: Hash = Hasher::digest(Bytes::from("hardfork_proposal"));
+    pub static ref LOG_INDEX_START_KEY: Hash = Hasher::digest(Bytes::from("log_index_start"));
+    pub static ref LOG_INDEX_END_KEY: Hash = Hasher::digest(Bytes::from("log_index_end"));
 }
 
 macro_rules! get_cache {
@@ -93,6 +96,7 @@ pub struct ImplStorage<Adapter> {
     cache:        Arc<StorageCache>,
     latest_block: ArcSwap<Option<Block>>,
     latest_proof: ArcSwap<Option<Proof>>,
+    log_index:    bool,
 }
 
 impl<Adapter: StorageAdapter> ImplStorage<Adapter> {
@@ -102,9 +106,148 @@ impl<Adapter: StorageAdapter> ImplStorage<Adapter> {
             cache: Arc::new(StorageCache::new(cache_size)),
             latest_block: ArcSwap::new(Arc::new(None)),
             latest_proof: ArcSwap::new(Arc::new(None)),
+            log_index: false,
         }
     }
 
+    /// Maintain the index of the logs by address and topic, which makes
+    /// `get_log_block_numbers` available.
+    pub fn with_log_index(mut self, enable: bool) -> Self {
+        self.log_index = enable;
+        self
+    }
+
+    /// Prepare the markers of the log index. The index covers the blocks in
+    /// `[start, end)`, the new blocks are appended to the end, and the
+    /// existing blocks are indexed backwards by `rebuild_log_index`.
+    pub fn prepare_log_index(&self) -> ProtocolResult<()> {
+        if !self.log_index {
+            return Ok(());
+        }
+
+        let next = self
+            .adapter
+            .get::<LatestBlockSchema>(*LATEST_BLOCK_KEY)?
+            .map(|b| b.header.number + 1)
+            .unwrap_or_default();
+
+        match self.log_index_range()? {
+            // The index is up to date.
+            Some((_, end)) if end == next => Ok(()),
+            // The index was disabled for a while, it has to be rebuilt since
+            // the coverage must be continuous.
+            _ => {
+                let mut batch = StorageBatch::default();
+                batch.insert::<LogIndexMarkerSchema>(*LOG_INDEX_END_KEY, next)?;
+                batch.insert::<LogIndexMarkerSchema>(*LOG_INDEX_START_KEY, next)?;
+                self.adapter.write_batch(batch)
+            }
+        }
+    }
+
+    /// Index the logs of the existing blocks, from the start of the log index
+    /// backwards to the genesis.
+    pub async fn rebuild_log_index(&self, ctx: Context) -> ProtocolResult<()> {
+        if !self.log_index {
+            return Ok(());
+        }
+
+        while let Some((start, _)) = self.log_index_range()? {
+            if start == 0 {
+                break;
+            }
+
+            let height = start - 1;
+            let block = match self.get_block(ctx.clone(), height).await? {
+                Some(block) => block,
+                None => break,
+            };
+            let receipts = self
+                .get_receipts(ctx.clone(), height, &block.tx_hashes)
+                .await?
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+
+            // The entries and the marker are written together, so that the
+            // index is consistent with the marker after a crash.
+            let mut batch = StorageBatch::default();
+            for (key, count) in log_index_entries(&receipts, height).into_iter() {
+                batch.insert::<LogIndexSchema>(key, count)?;
+            }
+            batch.insert::<LogIndexMarkerSchema>(*LOG_INDEX_START_KEY, height)?;
+            self.adapter.write_batch(batch)?;
+
+            if height % 10_000 == 0 {
+                log::info!("[storage] log index is rebuilt to height {}", height);
+            }
+            tokio::task::yield_now().await;
+        }
+
+        Ok(())
+    }
+
+    fn log_index_range(&self) -> ProtocolResult<Option<(u64, u64)>> {
+        let start = self
+            .adapter
+            .get::<LogIndexMarkerSchema>(*LOG_INDEX_START_KEY)?;
+        let end = self
+            .adapter
+            .get::<LogIndexMarkerSchema>(*LOG_INDEX_END_KEY)?;
+
+        Ok(start.zip(end))
+    }
+
+    /// Returns the receipts stored in the block, which are overwritten if the
+    /// block is inserted again.
+    fn stored_receipts(&self, block_number: BlockNumber) -> ProtocolResult<Vec<Receipt>> {
+        let key_prefix = CommonPrefix::new(block_number);
+        let prepare_iter = self.adapter.prepare_iter::<ReceiptSchema, _>(&key_prefix)?;
+        let mut iter = prepare_iter.ref_to_iter();
+
+        let mut ret = Vec::new();
+        loop {
+            let (key, receipt) = match iter.next() {
+                None => break,
+                Some(Ok(key_to_receipt)) => key_to_receipt,
+                Some(Err(err)) => return Err(err),
+            };
+
+            if key.height() != block_number {
+                break;
+            }
+            ret.push(receipt);
+        }
+
+        Ok(ret)
+    }
+
+    /// Returns the block numbers in `[from, to]` of one posting list.
+    fn scan_log_index(&self, key: LogIndexKey, to: u64) -> ProtocolResult<BTreeSet<u64>> {
+        let inst = Instant::now();
+        let prepare_iter = self
+            .adapter
+            .prepare_iter::<LogIndexSchema, _>(&key.to_bytes())?;
+        let mut iter = prepare_iter.ref_to_iter();
+
+        let mut ret = BTreeSet::new();
+        loop {
+            let (found, _) = match iter.next() {
+                None => break,
+                Some(Ok(key_to_count)) => key_to_count,
+                Some(Err(err)) => return Err(err),
+            };
+
+            if !found.same_item(&key) || found.height() > to {
+                break;
+            }
+            ret.insert(found.height());
+        }
+        on_storage_get_cf(StorageCategory::LogIndex, inst.elapsed(), ret.len() as f64);
+
+        Ok(ret)
+    }
+
     async fn get_block_number_by_hash(&self, hash: &Hash) -> ProtocolResult<Option<u64>> {
         get_cache!(self, hash, block_numbers);
         let ret = self.adapter.get::<BlockHashNumberSchema>(*hash)?;
@@ -146,36 +289,40 @@ impl<Adapter: StorageAdapter> ImplStorage<Adapter> {
         Ok(())
     }
 
-    async fn batch_insert_receipts(
+    fn batch_insert_receipts(
         &self,
+        batch: &mut StorageBatch,
         receipts: Vec<Receipt>,
         block_number: BlockNumber,
     ) -> ProtocolResult<()> {
-        let (hashes, heights) = receipts
-            .iter()
-            .map(|item| (item.tx_hash, StorageBatchModify::Insert(block_number)))
-            .unzip();
-
-        let (keys, batch_stxs): (Vec<_>, Vec<_>) = receipts
-            .into_iter()
-            .map(|item| {
-                (
-                    CommonHashKey::new(block_number, item.tx_hash),
-                    StorageBatchModify::Insert(item),
-                )
-            })
-            .unzip();
-
-        self.adapter
-            .batch_modify::<ReceiptSchema>(keys, batch_stxs)?;
-
-        self.adapter
-            .batch_modify::<TxHashNumberSchema>(hashes, heights)?;
+        for item in receipts.into_iter() {
+            batch.insert::<TxHashNumberSchema>(item.tx_hash, block_number)?;
+            batch.insert::<ReceiptSchema>(CommonHashKey::new(block_number, item.tx_hash), item)?;
+        }
 
         Ok(())
     }
 }
 
+/// Returns the posting list entries of the logs in the block, with the number
+/// of the logs of each entry.
+fn log_index_entries(receipts: &[Receipt], block_number: BlockNumber) -> HashMap<LogIndexKey, u64> {
+    let mut postings: HashMap<LogIndexKey, u64> = HashMap::new();
+    for log in receipts.iter().flat_map(|r| r.logs.iter()) {
+        *postings
+            .entry(LogIndexKey::address(log.address, block_number))
+            .or_default() += 1;
+
+        for (position, topic) in log.topics.iter().enumerate() {
+            *postings
+                .entry(LogIndexKey::topic(position as u8, *topic, block_number))
+                .or_default() += 1;
+        }
+    }
+
+    postings
+}
+
 #[async_trait]
 impl<Adapter: StorageAdapter> ReadOnlyStorage for ImplStorage<Adapter> {
     async fn get_block(&self, _ctx: Context, height: u64) -> ProtocolResult<Option<Block>> {
@@ -477,6 +624,72 @@ impl<Adapter: StorageAdapter> ReadOnlyStorage for ImplStorage<Adapter> {
     async fn hardfork_proposal(&self, _ctx: Context) -> ProtocolResult<Option<HardforkInfoInner>> {
         get!(self, *HAEDFORK_PROPOSAL, HardforkSchema)
     }
+
+    #[trace_span(kind = "storage")]
+    async fn get_log_block_numbers(
+        &self,
+        ctx: Context,
+        from: u64,
+        to: u64,
+        addresses: &[H160],
+        topics: &[Option<Vec<Option<H256>>>],
+    ) -> ProtocolResult<Option<Vec<u64>>> {
+        if !self.log_index {
+            return Ok(None);
+        }
+
+        match self.log_index_range()? {
+            Some((start, end)) if start <= from && to < end => (),
+            _ => return Ok(None),
+        }
+
+        // The items in a criterion are OR-ed, and the criteria are AND-ed. A
+        // wildcard in the topics matches every block so it is not a criterion.
+        let mut criteria = Vec::new();
+        if !addresses.is_empty() {
+            criteria.push(
+                addresses
+                    .iter()
+                    .map(|address| LogIndexKey::address(*address, from))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        for (position, topic) in topics.iter().enumerate() {
+            match topic {
+                Some(items) if !items.is_empty() && !items.contains(&None) => criteria.push(
+                    items
+                        .iter()
+                        .flatten()
+                        .map(|item| LogIndexKey::topic(position as u8, *item, from))
+                        .collect(),
+                ),
+                _ => (),
+            }
+        }
+
+        if criteria.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ret: Option<BTreeSet<u64>> = None;
+        for criterion in criteria.into_iter() {
+            let mut matched = BTreeSet::new();
+            for key in criterion.into_iter() {
+                matched.append(&mut self.scan_log_index(key, to)?);
+            }
+
+            let matched = match ret {
+                Some(prev) => prev.intersection(&matched).copied().collect(),
+                None => matched,
+            };
+            if matched.is_empty() {
+                return Ok(Some(Vec::new()));
+            }
+            ret = Some(matched);
+        }
+
+        Ok(ret.map(|numbers| numbers.into_iter().collect()))
+    }
 }
 
 #[async_trait]
@@ -544,9 +757,39 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         block_height: u64,
         receipts: Vec<Receipt>,
     ) -> ProtocolResult<()> {
-        self.batch_insert_receipts(receipts, block_height).await?;
+        // The receipts, the log index entries and the marker are written
+        // together, so that the index is consistent with the marker after a
+        // crash.
+        let mut batch = StorageBatch::default();
+        if self.log_index {
+            match self.log_index_range()? {
+                Some((start, end)) if start <= block_height && block_height <= end => {
+                    let entries = log_index_entries(&receipts, block_height);
+
+                    // The block is overwritten, remove the entries of the logs
+                    // which are gone.
+                    if block_height < end {
+                        let stored = self.stored_receipts(block_height)?;
+                        for key in log_index_entries(&stored, block_height).into_keys() {
+                            if !entries.contains_key(&key) {
+                                batch.remove::<LogIndexSchema>(key)?;
+                            }
+                        }
+                    }
+
+                    for (key, count) in entries.into_iter() {
+                        batch.insert::<LogIndexSchema>(key, count)?;
+                    }
+                    if block_height == end {
+                        batch.insert::<LogIndexMarkerSchema>(*LOG_INDEX_END_KEY, end + 1)?;
+                    }
+                }
+                _ => (),
+            }
+        }
 
-        Ok(())
+        self.batch_insert_receipts(&mut batch, receipts, block_height)?;
+        self.adapter.write_batch(batch)
     }
 
     async fn update_latest_proof(&self, _ctx: Context, proof: Proof) -> ProtocolResult<()> {
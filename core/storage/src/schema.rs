@@ -3,7 +3,7 @@ use protocol::types::{
     Block, Bytes, DBBytes, HardforkInfoInner, Hash, Header, Proof, Receipt, SignedTransaction,
 };
 
-use crate::hash_key::{BlockKey, CommonHashKey};
+use crate::hash_key::{BlockKey, CommonHashKey, LogIndexKey};
 
 macro_rules! impl_storage_schema_for {
     ($name: ident, $key: ty, $val: ty, $category: ident) => {
@@ -44,3 +44,5 @@ impl_storage_schema_for!(OverlordWalSchema, Hash, Bytes, Wal);
 impl_storage_schema_for!(EvmCodeSchema, Hash, Bytes, Code);
 impl_storage_schema_for!(EvmCodeAddressSchema, Hash, Hash, Code);
 impl_storage_schema_for!(HardforkSchema, Hash, HardforkInfoInner, Version);
+impl_storage_schema_for!(LogIndexSchema, LogIndexKey, u64, LogIndex);
+impl_storage_schema_for!(LogIndexMarkerSchema, Hash, u64, Version);
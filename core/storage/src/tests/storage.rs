@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use protocol::traits::{Context, ReadOnlyStorage, Storage};
-use protocol::types::Hasher;
+use protocol::types::{Hasher, Log, Receipt, H160, H256};
 
 use core_db::MemoryAdapter;
 
@@ -60,6 +60,148 @@ fn test_storage_receipts_insert() {
     }
 }
 
+#[test]
+fn test_storage_log_index() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()), 10).with_log_index(true);
+    storage.prepare_log_index().unwrap();
+
+    let (address_a, address_b) = (H160::random(), H160::random());
+    let (topic_a, topic_b) = (H256::random(), H256::random());
+    let logs = [
+        (address_a, vec![topic_a]),
+        (address_b, vec![topic_b, topic_a]),
+        (address_a, vec![topic_b]),
+    ];
+
+    for (height, (address, topics)) in logs.into_iter().enumerate() {
+        let receipt = mock_log_receipt(address, topics);
+        exec!(storage.insert_receipts(Context::new(), height as u64, vec![receipt]));
+    }
+
+    let get = |addresses: &[H160], topics: &[Option<Vec<Option<H256>>>]| {
+        exec!(storage.get_log_block_numbers(Context::new(), 0, 2, addresses, topics))
+    };
+    assert_eq!(get(&[address_a], &[]), Some(vec![0, 2]));
+    assert_eq!(get(&[address_a, address_b], &[]), Some(vec![0, 1, 2]));
+    assert_eq!(get(&[], &[Some(vec![Some(topic_a)])]), Some(vec![0]));
+    assert_eq!(get(&[], &[None, Some(vec![Some(topic_a)])]), Some(vec![1]));
+    assert_eq!(
+        get(&[address_a], &[Some(vec![Some(topic_b)])]),
+        Some(vec![2])
+    );
+    assert_eq!(get(&[], &[Some(vec![None])]), None);
+
+    // The items of a position are OR-ed and the positions are AND-ed.
+    assert_eq!(
+        get(&[], &[Some(vec![Some(topic_a), Some(topic_b)])]),
+        Some(vec![0, 1, 2])
+    );
+    assert_eq!(
+        get(&[], &[Some(vec![Some(topic_b)]), Some(vec![Some(topic_a)])]),
+        Some(vec![1])
+    );
+    assert_eq!(
+        get(&[address_a, address_b], &[Some(vec![Some(topic_b)])]),
+        Some(vec![1, 2])
+    );
+    assert_eq!(
+        get(&[address_b], &[Some(vec![Some(topic_a)])]),
+        Some(vec![])
+    );
+
+    // The range is not covered by the index.
+    let numbers = exec!(storage.get_log_block_numbers(Context::new(), 0, 3, &[address_a], &[]));
+    assert_eq!(numbers, None);
+}
+
+#[test]
+fn test_storage_log_index_overwrite() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()), 10).with_log_index(true);
+    storage.prepare_log_index().unwrap();
+
+    let (address_a, address_b) = (H160::random(), H160::random());
+    let topic = H256::random();
+    let receipts = [
+        (0, mock_log_receipt(address_a, vec![topic])),
+        (1, mock_log_receipt(address_a, vec![])),
+        // The block 0 is inserted again with the other transaction.
+        (0, mock_log_receipt(address_b, vec![topic])),
+    ];
+    for (height, receipt) in receipts.into_iter() {
+        exec!(storage.insert_receipts(Context::new(), height, vec![receipt]));
+    }
+
+    let get = |addresses: &[H160], topics: &[Option<Vec<Option<H256>>>]| {
+        exec!(storage.get_log_block_numbers(Context::new(), 0, 1, addresses, topics))
+    };
+    assert_eq!(get(&[address_a], &[]), Some(vec![1]));
+    assert_eq!(get(&[address_b], &[]), Some(vec![0]));
+    assert_eq!(get(&[], &[Some(vec![Some(topic)])]), Some(vec![0]));
+
+    // The end of the index is not moved by the overwritten block.
+    let numbers = exec!(storage.get_log_block_numbers(Context::new(), 0, 2, &[address_a], &[]));
+    assert_eq!(numbers, None);
+}
+
+#[test]
+fn test_storage_log_index_recovery() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    let address = H160::random();
+    let insert = |storage: &ImplStorage<MemoryAdapter>, height: u64| {
+        let receipt = mock_log_receipt(address, vec![]);
+        let mut block = mock_block(height, Hasher::digest(get_random_bytes(10)));
+        block.tx_hashes = vec![receipt.tx_hash];
+
+        exec!(storage.insert_block(Context::new(), block));
+        exec!(storage.insert_receipts(Context::new(), height, vec![receipt]));
+    };
+    let get = |storage: &ImplStorage<MemoryAdapter>, from: u64, to: u64| {
+        exec!(storage.get_log_block_numbers(Context::new(), from, to, &[address], &[]))
+    };
+
+    // The blocks are inserted without the log index.
+    let storage = ImplStorage::new(Arc::clone(&adapter), 10);
+    (0..3).for_each(|height| insert(&storage, height));
+
+    // The index starts from the next block once enabled.
+    let storage = ImplStorage::new(Arc::clone(&adapter), 10).with_log_index(true);
+    storage.prepare_log_index().unwrap();
+    insert(&storage, 3);
+    assert_eq!(get(&storage, 3, 3), Some(vec![3]));
+    assert_eq!(get(&storage, 0, 3), None);
+
+    // The existing blocks are indexed backwards.
+    exec!(storage.rebuild_log_index(Context::new()));
+    assert_eq!(get(&storage, 0, 3), Some(vec![0, 1, 2, 3]));
+
+    // The markers are kept after a restart.
+    let storage = ImplStorage::new(Arc::clone(&adapter), 10).with_log_index(true);
+    storage.prepare_log_index().unwrap();
+    assert_eq!(get(&storage, 0, 3), Some(vec![0, 1, 2, 3]));
+
+    // The index is reset if a block is inserted while it is disabled, since
+    // the coverage must be continuous.
+    insert(&ImplStorage::new(Arc::clone(&adapter), 10), 4);
+    let storage = ImplStorage::new(Arc::clone(&adapter), 10).with_log_index(true);
+    storage.prepare_log_index().unwrap();
+    assert_eq!(get(&storage, 0, 4), None);
+    insert(&storage, 5);
+    assert_eq!(get(&storage, 5, 5), Some(vec![5]));
+
+    exec!(storage.rebuild_log_index(Context::new()));
+    assert_eq!(get(&storage, 0, 5), Some(vec![0, 1, 2, 3, 4, 5]));
+}
+
+fn mock_log_receipt(address: H160, topics: Vec<H256>) -> Receipt {
+    let mut receipt = mock_receipt(Hasher::digest(get_random_bytes(10)));
+    receipt.logs = vec![Log {
+        address,
+        topics,
+        data: vec![],
+    }];
+    receipt
+}
+
 #[test]
 fn test_storage_transactions_insert() {
     let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()), 10);
@@ -15,6 +15,13 @@ pub const MAX_BLOCK_GAS_LIMIT: u64 = 30_000_000;
 // fee history request. Between 1 and 1024 blocks can be requested in a single
 // query. reference: https://docs.infura.io/infura/networks/ethereum/json-rpc-methods/eth_feehistory/
 pub const MAX_FEE_HISTORY: u64 = 1024;
+/// The base fee per gas of the genesis block, the dynamic base fee never drops
+/// below it.
 pub const BASE_FEE_PER_GAS: u64 = 0x539;
+/// The base fee changes by at most 1/8 between blocks, according to
+/// [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559).
+pub const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+/// The gas target of a block is half of its gas limit.
+pub const ELASTICITY_MULTIPLIER: u64 = 2;
 
 const GWEI: u64 = 1_000_000_000;
@@ -1,9 +1,14 @@
+use std::sync::{atomic::AtomicBool, Arc};
+use std::time::Duration;
+
+use crate::traits::{ConnectedPeer, Context, NetworkNodeInfo};
 use crate::types::{
-    Account, Block, BlockNumber, Bytes, CkbRelatedInfo, EthAccountProof, HardforkInfo,
-    HardforkInfoInner, Hash, Header, Metadata, Proposal, Receipt, SignedTransaction, TxResp, H160,
+    AccessList, Account, Block, BlockNumber, Bytes, CkbRelatedInfo, EthAccountProof, HardforkInfo,
+    HardforkInfoInner, Hash, Header, MemPoolContent, Metadata, PendingBlock, Proof, Proposal,
+    Receipt, SignedTransaction, SimulateBlock, StateOverride, TraceResult, Tracer, TxResp, H160,
     H256, U256, U64,
 };
-use crate::{async_trait, traits::Context, ProtocolResult};
+use crate::{async_trait, tokio::sync::broadcast, ProtocolResult};
 
 #[async_trait]
 pub trait APIAdapter: Send + Sync {
@@ -15,6 +20,20 @@ pub trait APIAdapter: Send + Sync {
 
     async fn mempool_contains_tx(&self, ctx: Context, tx_hash: &Hash) -> bool;
 
+    async fn get_mempool_status(&self, ctx: Context) -> ProtocolResult<(usize, usize)>;
+
+    async fn get_mempool_content(
+        &self,
+        ctx: Context,
+        sender: Option<H160>,
+    ) -> ProtocolResult<MemPoolContent>;
+
+    fn subscribe_new_txs(&self, ctx: Context) -> broadcast::Receiver<SignedTransaction>;
+
+    fn subscribe_committed_blocks(&self, ctx: Context) -> broadcast::Receiver<Block>;
+
+    fn subscribe_proofs(&self, ctx: Context) -> broadcast::Receiver<Proof>;
+
     async fn get_block_by_number(
         &self,
         ctx: Context,
@@ -35,6 +54,9 @@ pub trait APIAdapter: Send + Sync {
         hash: Hash,
     ) -> ProtocolResult<Option<BlockNumber>>;
 
+    /// Returns the latest aggregated proof stored by consensus.
+    async fn get_latest_proof(&self, ctx: Context) -> ProtocolResult<Proof>;
+
     async fn get_receipt_by_tx_hash(
         &self,
         ctx: Context,
@@ -48,6 +70,17 @@ pub trait APIAdapter: Send + Sync {
         tx_hashes: &[Hash],
     ) -> ProtocolResult<Vec<Option<Receipt>>>;
 
+    /// Returns the numbers of the blocks which may contain the matching logs,
+    /// or `None` if the log index is not available for the range.
+    async fn get_log_block_numbers(
+        &self,
+        ctx: Context,
+        from: u64,
+        to: u64,
+        addresses: &[H160],
+        topics: &[Option<Vec<Option<H256>>>],
+    ) -> ProtocolResult<Option<Vec<u64>>>;
+
     async fn get_transaction_by_hash(
         &self,
         ctx: Context,
@@ -74,6 +107,10 @@ pub trait APIAdapter: Send + Sync {
         address: H160,
     ) -> ProtocolResult<(U256, Option<BlockNumber>)>;
 
+    /// Returns the pending block built by speculatively executing the
+    /// transactions the mempool would package next on top of the latest block.
+    async fn get_pending_block(&self, ctx: Context) -> ProtocolResult<Arc<PendingBlock>>;
+
     async fn evm_call(
         &self,
         ctx: Context,
@@ -84,14 +121,89 @@ pub trait APIAdapter: Send + Sync {
         value: U256,
         data: Vec<u8>,
         estimate: bool,
+        state_override: Option<StateOverride>,
         state_root: Hash,
         proposal: Proposal,
     ) -> ProtocolResult<TxResp>;
 
+    /// Generate the access list of a call and return it with the response of
+    /// the call executed with the list applied.
+    async fn create_access_list(
+        &self,
+        ctx: Context,
+        from: Option<H160>,
+        to: Option<H160>,
+        gas_price: Option<U64>,
+        gas_limit: Option<U64>,
+        value: U256,
+        data: Vec<u8>,
+        access_list: AccessList,
+        state_root: Hash,
+        proposal: Proposal,
+    ) -> ProtocolResult<(AccessList, TxResp)>;
+
+    async fn trace_call(
+        &self,
+        ctx: Context,
+        from: Option<H160>,
+        to: Option<H160>,
+        gas_price: Option<U64>,
+        gas_limit: Option<U64>,
+        value: U256,
+        data: Vec<u8>,
+        state_root: Hash,
+        proposal: Proposal,
+        tracer: Tracer,
+        cancel: Arc<AtomicBool>,
+    ) -> ProtocolResult<TraceResult>;
+
+    /// Re-execute the transactions on the given state and trace the ones whose
+    /// index is not less than `trace_from`. The tracing is aborted once the
+    /// `cancel` flag is set.
+    async fn trace_transactions(
+        &self,
+        ctx: Context,
+        state_root: Hash,
+        proposal: Proposal,
+        txs: Vec<SignedTransaction>,
+        trace_from: usize,
+        tracer: Tracer,
+        cancel: Arc<AtomicBool>,
+    ) -> ProtocolResult<Vec<TraceResult>>;
+
+    /// Simulate the calls of the blocks in sequence on the given state, the
+    /// state changes are carried between the calls and the blocks. The
+    /// simulation is aborted once the `cancel` flag is set.
+    async fn simulate_blocks(
+        &self,
+        ctx: Context,
+        state_root: Hash,
+        blocks: Vec<SimulateBlock>,
+        cancel: Arc<AtomicBool>,
+    ) -> ProtocolResult<Vec<Vec<TxResp>>>;
+
     async fn get_code_by_hash(&self, ctx: Context, hash: &Hash) -> ProtocolResult<Option<Bytes>>;
 
     async fn peer_count(&self, ctx: Context) -> ProtocolResult<U256>;
 
+    async fn get_peers(&self, ctx: Context) -> ProtocolResult<Vec<ConnectedPeer>>;
+
+    async fn get_network_node_info(&self, ctx: Context) -> ProtocolResult<NetworkNodeInfo>;
+
+    async fn add_peer(&self, ctx: Context, multiaddr: String) -> ProtocolResult<()>;
+
+    async fn remove_peer(&self, ctx: Context, peer_id: String) -> ProtocolResult<bool>;
+
+    async fn ban_peer(
+        &self,
+        ctx: Context,
+        peer_id: String,
+        timeout: Duration,
+        reason: String,
+    ) -> ProtocolResult<Vec<String>>;
+
+    async fn unban(&self, ctx: Context, ip_network: String) -> ProtocolResult<()>;
+
     async fn get_storage_at(
         &self,
         ctx: Context,
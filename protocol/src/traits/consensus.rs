@@ -217,6 +217,9 @@ pub trait ConsensusAdapter: CommonConsensusAdapter + Send + Sync {
         order_txs: &[Hash],
     ) -> ProtocolResult<Vec<SignedTransaction>>;
 
+    /// Drop the given transactions from the mempool.
+    fn drop_txs_from_mempool(&self, ctx: Context, txs: &[Hash]);
+
     /// Consensus transmit a message to the given target.
     async fn transmit(
         &self,
@@ -1,7 +1,8 @@
 use crate::types::{
-    BlockNumber, Hash, MerkleRoot, PackedTxHashes, SignedTransaction, H160, U256, U64,
+    BlockNumber, Hash, MemPoolContent, MerkleRoot, PackedTxHashes, SignedTransaction, H160, U256,
+    U64,
 };
-use crate::{async_trait, traits::Context, ProtocolResult};
+use crate::{async_trait, tokio::sync::broadcast, traits::Context, ProtocolResult};
 
 #[async_trait]
 pub trait MemPool: Send + Sync {
@@ -23,6 +24,10 @@ pub trait MemPool: Send + Sync {
         current_number: BlockNumber,
     ) -> ProtocolResult<()>;
 
+    /// Marks the transactions dropped, so they are never packaged again and
+    /// are removed by the next flush.
+    fn drop_txs(&self, ctx: Context, tx_hashes: &[Hash]);
+
     async fn get_full_txs(
         &self,
         ctx: Context,
@@ -44,6 +49,23 @@ pub trait MemPool: Send + Sync {
     ) -> ProtocolResult<(usize, Option<BlockNumber>)>;
 
     fn get_tx_from_mem(&self, ctx: Context, tx_hash: &Hash) -> Option<SignedTransaction>;
+
+    /// Returns the transactions `package` would return next without changing
+    /// the mempool, for building the pending block.
+    fn peek_package(&self, ctx: Context, tx_num_limit: u64) -> PackedTxHashes;
+
+    /// Returns the numbers of the pending and the queued transactions.
+    fn get_status(&self, ctx: Context) -> (usize, usize);
+
+    /// Returns the transactions in the mempool, only the ones sent by `sender`
+    /// if it is given.
+    fn get_content(&self, ctx: Context, sender: Option<H160>) -> MemPoolContent;
+
+    /// Subscribes to the transactions accepted by the mempool. The receiver
+    /// lags behind and loses the missed transactions if it is not drained in
+    /// time.
+    fn subscribe_new_txs(&self, ctx: Context) -> broadcast::Receiver<SignedTransaction>;
+
     fn set_args(&self, context: Context, state_root: MerkleRoot, gas_limit: u64, max_tx_size: u64);
 }
 
@@ -20,9 +20,10 @@ pub use interoperation::{
 };
 pub use mempool::{MemPool, MemPoolAdapter};
 pub use network::{
-    Gossip, MessageCodec, MessageHandler, Network, PeerTag, PeerTrust, Priority, Rpc, TrustFeedback,
+    ConnectedPeer, Gossip, MessageCodec, MessageHandler, Network, NetworkNodeInfo, PeerDirection,
+    PeerTag, PeerTrust, Priority, Rpc, TrustFeedback,
 };
 pub use storage::{
     IntoIteratorByRef, ReadOnlyStorage, StateStorageCategory, Storage, StorageAdapter,
-    StorageBatchModify, StorageCategory, StorageIterator, StorageSchema,
+    StorageBatch, StorageBatchModify, StorageCategory, StorageIterator, StorageSchema,
 };
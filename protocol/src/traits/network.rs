@@ -1,6 +1,7 @@
 use std::{
     fmt::Debug,
     hash::{Hash, Hasher},
+    time::Duration,
 };
 
 use crate::types::Bytes;
@@ -79,6 +80,33 @@ impl Hash for PeerTag {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerDirection {
+    Inbound,
+    Outbound,
+}
+
+/// A peer connected to the local node.
+#[derive(Clone, Debug)]
+pub struct ConnectedPeer {
+    pub peer_id:      String,
+    pub multiaddr:    String,
+    pub direction:    PeerDirection,
+    /// The round trip time of the latest ping, none before the first pong.
+    pub latency:      Option<Duration>,
+    pub is_consensus: bool,
+}
+
+/// The network status of the local node.
+#[derive(Clone, Debug)]
+pub struct NetworkNodeInfo {
+    pub peer_id:      String,
+    pub listen_addrs: Vec<String>,
+    pub inbound:      usize,
+    pub outbound:     usize,
+    pub banned:       usize,
+}
+
 pub trait MessageCodec: Sized + Send + Debug + 'static {
     fn encode_msg(&mut self) -> ProtocolResult<Bytes>;
 
@@ -148,6 +176,21 @@ pub trait Network: Send + Sync {
     fn untag(&self, ctx: Context, peer_id: Bytes, tag: &PeerTag) -> ProtocolResult<()>;
     fn tag_consensus(&self, ctx: Context, peer_ids: Vec<Bytes>) -> ProtocolResult<()>;
     fn peer_count(&self, ctx: Context) -> ProtocolResult<usize>;
+    fn peers(&self, ctx: Context) -> ProtocolResult<Vec<ConnectedPeer>>;
+    fn node_info(&self, ctx: Context) -> ProtocolResult<NetworkNodeInfo>;
+    /// Dial the multiaddr, which must end with the `/p2p/` peer id.
+    fn add_peer(&self, ctx: Context, multiaddr: &str) -> ProtocolResult<()>;
+    /// Disconnect the peer, returns false if it is not connected.
+    fn remove_peer(&self, ctx: Context, peer_id: &str) -> ProtocolResult<bool>;
+    /// Ban the addresses of the peer, returns the banned ip networks.
+    fn ban_peer(
+        &self,
+        ctx: Context,
+        peer_id: &str,
+        timeout: Duration,
+        reason: String,
+    ) -> ProtocolResult<Vec<String>>;
+    fn unban(&self, ctx: Context, ip_network: &str) -> ProtocolResult<()>;
 }
 
 pub trait PeerTrust: Send + Sync {
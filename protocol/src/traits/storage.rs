@@ -1,5 +1,5 @@
 use crate::types::{
-    Block, Bytes, HardforkInfoInner, Hash, Header, Proof, Receipt, SignedTransaction, H256,
+    Block, Bytes, HardforkInfoInner, Hash, Header, Proof, Receipt, SignedTransaction, H160, H256,
 };
 use crate::{async_trait, codec::ProtocolCodec, traits::Context, Display, ProtocolResult};
 
@@ -16,6 +16,7 @@ pub enum StorageCategory {
     MetadataState,
     CkbLightClientState,
     Version,
+    LogIndex,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -114,6 +115,18 @@ pub trait ReadOnlyStorage: Sync + Send {
     async fn get_latest_proof(&self, ctx: Context) -> ProtocolResult<Proof>;
 
     async fn hardfork_proposal(&self, _ctx: Context) -> ProtocolResult<Option<HardforkInfoInner>>;
+
+    /// Returns the numbers of the blocks in `[from, to]` which may contain the
+    /// logs matching the filter, by the log index. Returns `None` if the log
+    /// index is disabled or it does not cover the range.
+    async fn get_log_block_numbers(
+        &self,
+        ctx: Context,
+        from: u64,
+        to: u64,
+        addresses: &[H160],
+        topics: &[Option<Vec<Option<H256>>>],
+    ) -> ProtocolResult<Option<Vec<u64>>>;
 }
 
 #[async_trait]
@@ -164,6 +177,47 @@ pub enum StorageBatchModify<S: StorageSchema> {
     Insert(<S as StorageSchema>::Value),
 }
 
+/// The modifications of the schemas which are written atomically by
+/// `StorageAdapter::write_batch`.
+#[derive(Default)]
+pub struct StorageBatch {
+    ops: Vec<(StorageCategory, Bytes, Option<Bytes>)>,
+}
+
+impl StorageBatch {
+    pub fn insert<S: StorageSchema>(
+        &mut self,
+        key: <S as StorageSchema>::Key,
+        val: <S as StorageSchema>::Value,
+    ) -> ProtocolResult<()> {
+        self.ops
+            .push((S::category(), key.encode()?, Some(val.encode()?)));
+        Ok(())
+    }
+
+    pub fn remove<S: StorageSchema>(
+        &mut self,
+        key: <S as StorageSchema>::Key,
+    ) -> ProtocolResult<()> {
+        self.ops.push((S::category(), key.encode()?, None));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Returns the encoded keys and values in the order of the modifications,
+    /// in which `None` means a removal.
+    pub fn into_ops(self) -> Vec<(StorageCategory, Bytes, Option<Bytes>)> {
+        self.ops
+    }
+}
+
 pub trait StorageAdapter: Send + Sync + 'static {
     fn insert<S: StorageSchema>(
         &self,
@@ -199,6 +253,8 @@ pub trait StorageAdapter: Send + Sync + 'static {
         vals: Vec<StorageBatchModify<S>>,
     ) -> ProtocolResult<()>;
 
+    fn write_batch(&self, batch: StorageBatch) -> ProtocolResult<()>;
+
     fn prepare_iter<'a, 'b: 'a, S: StorageSchema + 'static, P: AsRef<[u8]> + 'a>(
         &'b self,
         prefix: &'a P,
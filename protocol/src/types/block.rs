@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use derive_more::Display;
 use faster_hex::withpfx_lowercase;
 use rlp_derive::{RlpDecodable, RlpEncodable};
@@ -5,6 +7,7 @@ use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "hex-serialize")]
 use crate::codec::serialize_uint;
+use crate::constants::{BASE_FEE_MAX_CHANGE_DENOMINATOR, BASE_FEE_PER_GAS, ELASTICITY_MULTIPLIER};
 use crate::types::{
     logs_bloom, Bloom, BloomInput, Bytes, ExecResp, Hash, Hasher, Log, MerkleRoot, Receipt,
     SignedTransaction, VecDisplayHelper, H160, U64,
@@ -135,6 +138,14 @@ pub struct PackedTxHashes {
     pub call_system_script_count: u32,
 }
 
+/// The transactions in the mempool. The pending transactions can be packaged,
+/// and the queued transactions are waiting for the previous nonces.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemPoolContent {
+    pub pending: Vec<SignedTransaction>,
+    pub queued:  Vec<SignedTransaction>,
+}
+
 #[derive(
     RlpEncodable,
     RlpDecodable,
@@ -300,6 +311,30 @@ impl Header {
     pub fn hash(&self) -> Hash {
         Hasher::digest(&self.encode().unwrap())
     }
+
+    /// Calculate the base fee per gas of the next block from the gas used of
+    /// this block as [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559)
+    /// specifies. The base fee never drops below `BASE_FEE_PER_GAS`.
+    pub fn next_base_fee_per_gas(&self) -> U64 {
+        let base_fee = self.base_fee_per_gas.low_u64().max(BASE_FEE_PER_GAS) as u128;
+        let gas_target = (self.gas_limit.low_u64() / ELASTICITY_MULTIPLIER) as u128;
+        let gas_used = self.gas_used.low_u64() as u128;
+
+        if gas_target == 0 {
+            return U64::from(base_fee as u64);
+        }
+
+        let denominator = gas_target * BASE_FEE_MAX_CHANGE_DENOMINATOR as u128;
+        let next = match gas_used.cmp(&gas_target) {
+            Ordering::Equal => base_fee,
+            Ordering::Greater => {
+                base_fee + (base_fee * (gas_used - gas_target) / denominator).max(1)
+            }
+            Ordering::Less => base_fee - base_fee * (gas_target - gas_used) / denominator,
+        };
+
+        U64::from(next.clamp(BASE_FEE_PER_GAS as u128, u64::MAX as u128) as u64)
+    }
 }
 
 #[derive(
@@ -379,6 +414,7 @@ impl RichBlock {
 
 #[cfg(test)]
 mod tests {
+    use crate::constants::BASE_FEE_PER_GAS;
     use crate::types::{
         primitive::default_max_contract_limit, Block, BlockVersion, ConsensusConfig, Header, Hex,
         Metadata, MetadataVersion, ProposeCount, RichBlock, ValidatorExtend, H160,
@@ -405,6 +441,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_next_base_fee_per_gas() {
+        let header = |gas_used: u64, base_fee: u64| Header {
+            gas_used: gas_used.into(),
+            gas_limit: 30_000_000u64.into(),
+            base_fee_per_gas: base_fee.into(),
+            ..Default::default()
+        };
+
+        let base_fee = 1_000_000_000;
+        assert_eq!(
+            header(15_000_000, base_fee).next_base_fee_per_gas(),
+            base_fee.into()
+        );
+        assert_eq!(
+            header(30_000_000, base_fee).next_base_fee_per_gas(),
+            (base_fee + base_fee / 8).into()
+        );
+        assert_eq!(
+            header(0, base_fee).next_base_fee_per_gas(),
+            (base_fee - base_fee / 8).into()
+        );
+        assert_eq!(
+            header(0, BASE_FEE_PER_GAS).next_base_fee_per_gas(),
+            BASE_FEE_PER_GAS.into()
+        );
+        assert_eq!(
+            header(15_000_001, BASE_FEE_PER_GAS).next_base_fee_per_gas(),
+            (BASE_FEE_PER_GAS + 1).into()
+        );
+    }
+
     #[test]
     fn print_genesis() {
         let genesis = RichBlock {
@@ -2,11 +2,14 @@ pub use ethereum::{AccessList, AccessListItem, Account};
 pub use evm::{backend::Log, Config, ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed};
 pub use hasher::HasherKeccak;
 
+use std::collections::BTreeMap;
+
 use rlp_derive::{RlpDecodable, RlpEncodable};
 use serde::{Deserialize, Serialize};
 
 use crate::types::{
-    Bloom, ExtraData, Hash, Hasher, Header, MerkleRoot, Proposal, H160, H256, U256, U64,
+    Block, Bloom, ExtraData, Hash, Hasher, Header, MerkleRoot, Proposal, SignedTransaction, H160,
+    H256, U256, U64,
 };
 
 use super::Hex;
@@ -113,6 +116,52 @@ pub struct EthStorageProof {
     pub proof: Vec<Hex>,
 }
 
+/// The overrides of an account which are applied before an RPC call. The
+/// `state` replaces the whole storage, while the `state_diff` only replaces
+/// the given slots, so they can not be given at the same time.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct AccountOverride {
+    pub balance:    Option<U256>,
+    pub nonce:      Option<U64>,
+    pub code:       Option<Hex>,
+    pub state:      Option<BTreeMap<H256, H256>>,
+    pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+pub type StateOverride = BTreeMap<H160, AccountOverride>;
+
+/// A simulated call, which is executed like `eth_call` but on the state
+/// changed by the previous calls.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimulateCall {
+    pub from:      H160,
+    pub to:        Option<H160>,
+    pub gas_limit: u64,
+    pub gas_price: U64,
+    pub value:     U256,
+    pub data:      Vec<u8>,
+}
+
+/// The calls simulated in a block, the state overrides are applied before
+/// the calls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulateBlock {
+    pub proposal:       Proposal,
+    pub state_override: Option<StateOverride>,
+    pub calls:          Vec<SimulateCall>,
+}
+
+/// The block built by speculatively executing the transactions the mempool
+/// would package next on top of the latest block. The state root of the header
+/// is the latest one, and the state changes are kept as the overrides of it.
+#[derive(Clone, Debug, Default)]
+pub struct PendingBlock {
+    pub block:          Block,
+    pub txs:            Vec<SignedTransaction>,
+    pub state_override: StateOverride,
+}
+
 pub fn logs_bloom<'a, I>(logs: I) -> Bloom
 where
     I: Iterator<Item = &'a Log>,
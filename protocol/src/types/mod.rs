@@ -6,12 +6,14 @@ pub use bytes::{Buf, BufMut, Bytes, BytesMut};
 pub use ckb_client::*;
 pub use evm::{backend::*, ExitError, ExitRevert, ExitSucceed};
 pub use executor::{
-    logs_bloom, AccessList, AccessListItem, Account, Config, EthAccountProof, EthStorageProof,
-    ExecResp, ExecutorContext, ExitReason, HasherKeccak, TxResp,
+    logs_bloom, AccessList, AccessListItem, Account, AccountOverride, Config, EthAccountProof,
+    EthStorageProof, ExecResp, ExecutorContext, ExitReason, HasherKeccak, PendingBlock,
+    SimulateBlock, SimulateCall, StateOverride, TxResp,
 };
 pub use interoperation::*;
 pub use primitive::*;
 pub use receipt::*;
+pub use trace::*;
 pub use transaction::*;
 
 pub mod batch;
@@ -21,6 +23,7 @@ pub mod executor;
 pub mod interoperation;
 pub mod primitive;
 pub mod receipt;
+pub mod trace;
 pub mod transaction;
 
 use std::error::Error;
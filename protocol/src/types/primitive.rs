@@ -639,6 +639,16 @@ impl HardforkInfo {
             self.inner.push(other);
         }
     }
+
+    /// The hardforks determined at the given block.
+    pub fn flags_at(&self, block_number: BlockNumber) -> H256 {
+        self.inner
+            .iter()
+            .rev()
+            .find(|i| i.block_number <= block_number)
+            .map(|i| i.flags)
+            .unwrap_or_default()
+    }
 }
 
 #[derive(RlpEncodable, RlpDecodable, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
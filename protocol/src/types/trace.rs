@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Hex, H160, H256, U256, U64};
+
+/// The tracer used to trace an EVM execution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Tracer {
+    StructLogger(StructLoggerConfig),
+    CallTracer(CallTracerConfig),
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Tracer::StructLogger(StructLoggerConfig::default())
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StructLoggerConfig {
+    pub disable_stack:      bool,
+    pub disable_storage:    bool,
+    pub enable_memory:      bool,
+    pub enable_return_data: bool,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CallTracerConfig {
+    pub only_top_call: bool,
+    pub with_log:      bool,
+}
+
+/// The result of tracing one transaction, compatible with the output of the
+/// geth `debug_trace*` RPCs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum TraceResult {
+    StructLogs(StructLogResult),
+    CallFrame(CallFrame),
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogResult {
+    pub gas:          u64,
+    pub failed:       bool,
+    /// Hex encoded return value without `0x` prefix.
+    pub return_value: String,
+    pub struct_logs:  Vec<StructLog>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    pub pc:          u64,
+    pub op:          String,
+    pub gas:         u64,
+    pub gas_cost:    u64,
+    pub depth:       usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error:       Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack:       Option<Vec<U256>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<Hex>,
+    /// Memory split into 32 bytes words, hex encoded without `0x` prefix.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory:      Option<Vec<String>>,
+    /// Storage slots touched by the current contract so far, hex encoded
+    /// without `0x` prefix.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage:     Option<BTreeMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    #[serde(rename = "type")]
+    pub call_type:     String,
+    pub from:          H160,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to:            Option<H160>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value:         Option<U256>,
+    pub gas:           U64,
+    pub gas_used:      U64,
+    pub input:         Hex,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output:        Option<Hex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error:         Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls:         Vec<CallFrame>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs:          Vec<CallLog>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CallLog {
+    pub address: H160,
+    pub topics:  Vec<H256>,
+    pub data:    Hex,
+}
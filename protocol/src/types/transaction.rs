@@ -83,6 +83,16 @@ impl UnsignedTransaction {
         }
     }
 
+    /// The max fee per gas that the transaction is willing to pay, which must
+    /// cover the base fee per gas of the block it is included in.
+    pub fn max_fee_per_gas(&self) -> &U64 {
+        match self {
+            UnsignedTransaction::Legacy(tx) => &tx.gas_price,
+            UnsignedTransaction::Eip2930(tx) => &tx.gas_price,
+            UnsignedTransaction::Eip1559(tx) => &tx.gas_price,
+        }
+    }
+
     pub fn max_priority_fee_per_gas(&self) -> &U64 {
         match self {
             UnsignedTransaction::Legacy(tx) => &tx.gas_price,
@@ -91,6 +101,29 @@ impl UnsignedTransaction {
         }
     }
 
+    /// The priority fee per gas paid to the proposer with the given base fee
+    /// per gas.
+    pub fn effective_priority_fee_per_gas(&self, base_fee_per_gas: U64) -> U64 {
+        match self {
+            UnsignedTransaction::Eip1559(tx) => tx
+                .gas_price
+                .saturating_sub(base_fee_per_gas)
+                .min(tx.max_priority_fee_per_gas),
+            _ => self.gas_price().saturating_sub(base_fee_per_gas),
+        }
+    }
+
+    /// The gas price paid by the transaction with the given base fee per gas,
+    /// which is the base fee plus the effective priority fee.
+    pub fn effective_gas_price(&self, base_fee_per_gas: U64) -> U64 {
+        match self {
+            UnsignedTransaction::Eip1559(tx) => tx
+                .gas_price
+                .min(base_fee_per_gas.saturating_add(tx.max_priority_fee_per_gas)),
+            _ => self.gas_price(),
+        }
+    }
+
     pub fn get_legacy(&self) -> Option<LegacyTransaction> {
         match self {
             UnsignedTransaction::Legacy(tx) => Some(tx.clone()),